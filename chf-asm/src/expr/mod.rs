@@ -0,0 +1,227 @@
+//! Operand expressions
+//!
+//! Numbers are decimal or written the Fairchild way: `H'1F'`, `O'17'`, `B'101'`, `D'10'` and the
+//! character `C'A'`. `$` is the address of the current line. `+`, `-`, `*`, `/` and parentheses
+//! work as usual, on `i32`.
+
+use crate::ErrorKind;
+
+/// Evaluates `text`, `lookup` gives the value of a symbol
+pub fn eval(
+    text: &str,
+    location: u16,
+    lookup: &dyn Fn(&str) -> Option<i32>,
+) -> Result<i32, ErrorKind> {
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        pos: 0,
+        location,
+        lookup,
+    };
+    let value = parser.sum()?;
+    parser.skip_spaces();
+    if parser.pos != parser.text.len() {
+        return Err(ErrorKind::BadExpression(text.trim().to_string()));
+    }
+    Ok(value)
+}
+
+// whether `c` may start a symbol, the rest may have digits too
+fn is_symbol_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c == b'.'
+}
+
+fn is_symbol_char(c: u8) -> bool {
+    is_symbol_start(c) || c.is_ascii_digit()
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+    location: u16,
+    lookup: &'a dyn Fn(&str) -> Option<i32>,
+}
+
+impl<'a> Parser<'a> {
+    fn sum(&mut self) -> Result<i32, ErrorKind> {
+        let mut value = self.product()?;
+        loop {
+            value = match self.next_operator(b"+-") {
+                Some(b'+') => value.wrapping_add(self.product()?),
+                Some(_) => value.wrapping_sub(self.product()?),
+                None => return Ok(value),
+            };
+        }
+    }
+
+    fn product(&mut self) -> Result<i32, ErrorKind> {
+        let mut value = self.unary()?;
+        loop {
+            value = match self.next_operator(b"*/") {
+                Some(b'*') => value.wrapping_mul(self.unary()?),
+                Some(_) => {
+                    let divisor = self.unary()?;
+                    if divisor == 0 {
+                        return Err(self.error());
+                    }
+                    value.wrapping_div(divisor)
+                }
+                None => return Ok(value),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<i32, ErrorKind> {
+        match self.next_operator(b"-+") {
+            Some(b'-') => Ok(self.unary()?.wrapping_neg()),
+            Some(_) => self.unary(),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<i32, ErrorKind> {
+        self.skip_spaces();
+        let c = match self.text.get(self.pos) {
+            Some(c) => *c,
+            None => return Err(self.error()),
+        };
+
+        if c == b'(' {
+            self.pos += 1;
+            let value = self.sum()?;
+            return match self.next_operator(b")") {
+                Some(_) => Ok(value),
+                None => Err(self.error()),
+            };
+        }
+
+        if c == b'$' {
+            self.pos += 1;
+            return Ok(self.location as i32);
+        }
+
+        if c.is_ascii_digit() {
+            let start = self.pos;
+            while self.pos < self.text.len() && self.text[self.pos].is_ascii_alphanumeric() {
+                self.pos += 1;
+            }
+            return self.number(start, self.pos, 10);
+        }
+
+        if self.text.get(self.pos + 1) == Some(&b'\'') {
+            return self.quoted(c.to_ascii_uppercase());
+        }
+
+        if is_symbol_start(c) {
+            let start = self.pos;
+            while self.pos < self.text.len() && is_symbol_char(self.text[self.pos]) {
+                self.pos += 1;
+            }
+            let name = core::str::from_utf8(&self.text[start..self.pos]).unwrap();
+            return (self.lookup)(name).ok_or_else(|| ErrorKind::Undefined(name.to_string()));
+        }
+
+        Err(self.error())
+    }
+
+    // H'1F' and friends, the position is at the letter
+    fn quoted(&mut self, kind: u8) -> Result<i32, ErrorKind> {
+        let start = self.pos + 2;
+        let end = match self.text[start..].iter().position(|c| *c == b'\'') {
+            Some(len) => start + len,
+            None => return Err(self.error()),
+        };
+        self.pos = end + 1;
+
+        match kind {
+            b'H' => self.number(start, end, 16),
+            b'O' => self.number(start, end, 8),
+            b'B' => self.number(start, end, 2),
+            b'D' => self.number(start, end, 10),
+            b'C' if end == start + 1 => Ok(self.text[start] as i32),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn number(&self, start: usize, end: usize, radix: u32) -> Result<i32, ErrorKind> {
+        core::str::from_utf8(&self.text[start..end])
+            .ok()
+            .and_then(|digits| u16::from_str_radix(digits, radix).ok())
+            .map(|value| value as i32)
+            .ok_or_else(|| self.error())
+    }
+
+    // takes one of `operators` if it comes next
+    fn next_operator(&mut self, operators: &[u8]) -> Option<u8> {
+        self.skip_spaces();
+        let c = *self.text.get(self.pos)?;
+        if operators.contains(&c) {
+            self.pos += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.pos < self.text.len() && self.text[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self) -> ErrorKind {
+        ErrorKind::BadExpression(String::from_utf8_lossy(self.text).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(name: &str) -> Option<i32> {
+        match name {
+            "font" => Some(0x700),
+            "_x.1" => Some(3),
+            _ => None,
+        }
+    }
+
+    fn value(text: &str) -> Result<i32, ErrorKind> {
+        eval(text, 0x802, &symbols)
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Ok(42), value("42"));
+        assert_eq!(Ok(0x1f), value("H'1F'"));
+        assert_eq!(Ok(0x1f), value("h'1f'"));
+        assert_eq!(Ok(0o17), value("O'17'"));
+        assert_eq!(Ok(5), value("B'101'"));
+        assert_eq!(Ok(10), value("D'10'"));
+        assert_eq!(Ok(65), value("C'A'"));
+        assert_eq!(Ok(0x802), value("$"));
+        assert!(value("H'1G'").is_err());
+        assert!(value("H'10000'").is_err());
+        assert!(value("12AB").is_err());
+        assert!(value("C'AB'").is_err());
+    }
+
+    #[test]
+    fn symbols_and_operators() {
+        assert_eq!(Ok(7), value("font/256"));
+        assert_eq!(Ok(0x703), value("font + _x.1"));
+        assert_eq!(Ok(7), value("1+2*3"));
+        assert_eq!(Ok(9), value("(1+2)*3"));
+        assert_eq!(Ok(-3), value("-_x.1"));
+        assert_eq!(Ok(2), value("127-125"));
+        assert_eq!(Ok(0x800), value("$ - 2"));
+        assert_eq!(
+            Err(ErrorKind::Undefined("nope".to_string())),
+            value("nope+1")
+        );
+        assert!(value("1/0").is_err());
+        assert!(value("(1").is_err());
+        assert!(value("1 2").is_err());
+        assert!(value("").is_err());
+    }
+}
@@ -0,0 +1,424 @@
+//! An assembler for the F8, the CPU of the Channel F
+//!
+//! A line is `label mnemonic operands ; comment`, labels start in the first column (a trailing
+//! `:` is fine), everything else is indented. Mnemonics and register names don't care about case,
+//! labels do. See `opcodes` for the instructions and `expr` for what operands can be.
+//!
+//! Directives:
+//! - `ORG address` continues at `address`
+//! - `name EQU value` defines a symbol
+//! - `DC value, ...` puts bytes
+//! - `DS count` reserves bytes without putting anything there
+//! - `CARTRIDGE` starts a cartridge at 0x0800 with the 0x55 the BIOS looks for, its code follows
+//!   at 0x0802
+//! - `END` ignores the rest
+//!
+//! `ORG`, `EQU` and `DS` only see symbols defined above them, everything else can use labels
+//! defined further down.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+pub mod expr;
+pub mod opcodes;
+
+use opcodes::Context;
+
+/// Where the BIOS looks for a cartridge
+pub const CARTRIDGE_START: u16 = 0x0800;
+
+/// The BIOS starts a cartridge having this byte at 0x0800
+pub const CARTRIDGE_ID: u8 = 0x55;
+
+/// Gaps between the parts of a program, like erased EPROM
+pub const FILL: u8 = 0xff;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ErrorKind {
+    UnknownMnemonic(String),
+    /// The wrong number or kind of operands
+    BadOperands,
+    BadExpression(String),
+    Undefined(String),
+    Redefined(String),
+    MissingLabel,
+    OutOfRange(i32),
+    BranchTooFar(i32),
+    /// Two lines put a byte at this address
+    Overlap(u16),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownMnemonic(mnemonic) => write!(f, "unknown mnemonic {}", mnemonic),
+            ErrorKind::BadOperands => write!(f, "wrong operands"),
+            ErrorKind::BadExpression(text) => write!(f, "bad expression {}", text),
+            ErrorKind::Undefined(name) => write!(f, "{} isn't defined", name),
+            ErrorKind::Redefined(name) => write!(f, "{} is defined twice", name),
+            ErrorKind::MissingLabel => write!(f, "EQU needs a label"),
+            ErrorKind::OutOfRange(value) => write!(f, "{} is out of range", value),
+            ErrorKind::BranchTooFar(target) => write!(f, "{:04x} is too far to branch to", target),
+            ErrorKind::Overlap(address) => write!(f, "{:04x} is already used", address),
+        }
+    }
+}
+
+/// What went wrong on which line, they count from 1
+#[derive(Debug, PartialEq, Clone)]
+pub struct Error {
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+/// The assembled bytes from the lowest address used to the highest
+#[derive(Debug)]
+pub struct Program {
+    origin: u16,
+    bytes: Vec<u8>,
+    symbols: HashMap<String, i32>,
+}
+
+impl Program {
+    /// Address of the first byte
+    pub fn origin(&self) -> u16 {
+        self.origin
+    }
+
+    /// Gaps are filled with `FILL`
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The value of a label or `EQU`
+    pub fn symbol(&self, name: &str) -> Option<i32> {
+        self.symbols.get(name).copied()
+    }
+}
+
+/// Assembles `source`, the first pass finds the labels, the second one puts the bytes
+pub fn assemble(source: &str) -> Result<Program, Error> {
+    let mut symbols = HashMap::new();
+    pass(source, &mut symbols, false)?;
+    let memory = pass(source, &mut symbols, true)?;
+
+    let (origin, bytes) = match (memory.keys().next(), memory.keys().next_back()) {
+        (Some(first), Some(last)) => {
+            let mut bytes = vec![FILL; (last - first) as usize + 1];
+            for (address, byte) in memory.iter() {
+                bytes[(address - first) as usize] = *byte;
+            }
+            (*first, bytes)
+        }
+        _ => (0, Vec::new()),
+    };
+
+    Ok(Program {
+        origin,
+        bytes,
+        symbols,
+    })
+}
+
+fn pass(
+    source: &str,
+    symbols: &mut HashMap<String, i32>,
+    final_pass: bool,
+) -> Result<BTreeMap<u16, u8>, Error> {
+    let mut memory = BTreeMap::new();
+    let mut location = 0u16;
+
+    for (idx, line) in source.lines().enumerate() {
+        let error = |kind| Error {
+            line: idx + 1,
+            kind,
+        };
+
+        let line = Line::parse(line);
+        let mnemonic = match line.mnemonic {
+            Some(mnemonic) => mnemonic.to_ascii_uppercase(),
+            None => {
+                if let Some(label) = line.label {
+                    define(symbols, label, location as i32, final_pass).map_err(error)?;
+                }
+                continue;
+            }
+        };
+
+        match mnemonic.as_str() {
+            "END" => break,
+            "EQU" => {
+                let label = line.label.ok_or(ErrorKind::MissingLabel).map_err(error)?;
+                let value = known(symbols, location, &line.operands).map_err(error)?;
+                define(symbols, label, value, final_pass).map_err(error)?;
+                continue;
+            }
+            "ORG" | "CARTRIDGE" => {
+                let address = if mnemonic == "ORG" {
+                    let address = known(symbols, location, &line.operands).map_err(error)?;
+                    if !(0..=0xffff).contains(&address) {
+                        return Err(error(ErrorKind::OutOfRange(address)));
+                    }
+                    address as u16
+                } else {
+                    CARTRIDGE_START
+                };
+                location = address;
+            }
+            _ => (),
+        }
+
+        if let Some(label) = line.label {
+            define(symbols, label, location as i32, final_pass).map_err(error)?;
+        }
+
+        let bytes = match mnemonic.as_str() {
+            "ORG" => continue,
+            "CARTRIDGE" => {
+                if !line.operands.is_empty() {
+                    return Err(error(ErrorKind::BadOperands));
+                }
+                vec![CARTRIDGE_ID, 0x2b]
+            }
+            "DS" if line.operands.len() == 1 && opcodes::register(line.operands[0]).is_none() => {
+                let count = known(symbols, location, &line.operands).map_err(error)?;
+                if count < 0 || location as i32 + count > 0x10000 {
+                    return Err(error(ErrorKind::OutOfRange(count)));
+                }
+                location = location.wrapping_add(count as u16);
+                continue;
+            }
+            _ => {
+                // labels further down are 0 until the final pass
+                let lookup = |name: &str| symbols.get(name).copied();
+                let value = |text: &str| match expr::eval(text, location, &lookup) {
+                    Err(ErrorKind::Undefined(_)) if !final_pass => Ok(0),
+                    result => result,
+                };
+
+                if mnemonic == "DC" {
+                    if line.operands.is_empty() {
+                        return Err(error(ErrorKind::BadOperands));
+                    }
+                    let mut bytes = Vec::new();
+                    for operand in line.operands.iter() {
+                        match value(operand).map_err(error)? {
+                            byte if (-0x80..=0xff).contains(&byte) => bytes.push(byte as u8),
+                            byte => return Err(error(ErrorKind::OutOfRange(byte))),
+                        }
+                    }
+                    bytes
+                } else {
+                    let context = Context {
+                        location,
+                        value: &value,
+                        final_pass,
+                    };
+                    opcodes::encode(&mnemonic, &line.operands, &context).map_err(error)?
+                }
+            }
+        };
+
+        for byte in bytes {
+            if final_pass && memory.insert(location, byte).is_some() {
+                return Err(error(ErrorKind::Overlap(location)));
+            }
+            location = location
+                .checked_add(1)
+                .ok_or_else(|| error(ErrorKind::OutOfRange(0x10000)))?;
+        }
+    }
+
+    Ok(memory)
+}
+
+// the single operand of ORG, EQU and DS, it must not depend on labels further down
+fn known(
+    symbols: &HashMap<String, i32>,
+    location: u16,
+    operands: &[&str],
+) -> Result<i32, ErrorKind> {
+    match operands {
+        [operand] => expr::eval(operand, location, &|name| symbols.get(name).copied()),
+        _ => Err(ErrorKind::BadOperands),
+    }
+}
+
+// the first pass defines the symbols, the final one finds them with the same values
+fn define(
+    symbols: &mut HashMap<String, i32>,
+    name: &str,
+    value: i32,
+    final_pass: bool,
+) -> Result<(), ErrorKind> {
+    if final_pass {
+        return Ok(());
+    }
+    if symbols.insert(name.to_string(), value).is_some() {
+        return Err(ErrorKind::Redefined(name.to_string()));
+    }
+    Ok(())
+}
+
+/// A line split into its parts
+struct Line<'a> {
+    label: Option<&'a str>,
+    mnemonic: Option<&'a str>,
+    operands: Vec<&'a str>,
+}
+
+impl<'a> Line<'a> {
+    fn parse(line: &'a str) -> Line<'a> {
+        let line = &line[..find_outside_quotes(line, b';').unwrap_or(line.len())];
+
+        let (label, rest) = if line.starts_with(|c: char| !c.is_whitespace()) {
+            let end = line.find(char::is_whitespace).unwrap_or(line.len());
+            (Some(line[..end].trim_end_matches(':')), &line[end..])
+        } else {
+            (None, line)
+        };
+
+        let rest = rest.trim();
+        let (mnemonic, mut operands) = match rest.find(char::is_whitespace) {
+            Some(end) => (&rest[..end], rest[end..].trim()),
+            None => (rest, ""),
+        };
+
+        let mut result = Vec::new();
+        while !operands.is_empty() {
+            let end = find_outside_quotes(operands, b',').unwrap_or(operands.len());
+            result.push(operands[..end].trim());
+            operands = operands.get(end + 1..).unwrap_or("");
+        }
+
+        Line {
+            label,
+            mnemonic: if mnemonic.is_empty() {
+                None
+            } else {
+                Some(mnemonic)
+            },
+            operands: result,
+        }
+    }
+}
+
+// `;` and `,` may be quoted as C';'
+fn find_outside_quotes(text: &str, wanted: u8) -> Option<usize> {
+    let mut quoted = false;
+    for (idx, c) in text.bytes().enumerate() {
+        if c == b'\'' {
+            quoted = !quoted;
+        } else if c == wanted && !quoted {
+            return Some(idx);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> Error {
+        assemble(source).unwrap_err()
+    }
+
+    #[test]
+    fn labels_and_directives() {
+        let program = assemble(
+            "
+; comment only
+COLOR   EQU     H'40'
+        CARTRIDGE
+start:  LI      COLOR+1         ; forward and backward
+        PI      sub
+        BR      start
+        DS      2
+sub     LR      A,R1
+        POP
+table   DC      1, C';', -1
+        ORG     H'2800'
+buffer  DS      16
+after
+",
+        )
+        .unwrap();
+
+        assert_eq!(0x800, program.origin());
+        assert_eq!(
+            &[
+                0x55, 0x2b, 0x20, 0x41, 0x28, 0x08, 0x0b, 0x90, 0xfa, 0xff, 0xff, 0x41, 0x1c, 0x01,
+                0x3b, 0xff
+            ],
+            program.bytes()
+        );
+        assert_eq!(Some(0x802), program.symbol("start"));
+        assert_eq!(Some(0x80b), program.symbol("sub"));
+        assert_eq!(Some(0x2800), program.symbol("buffer"));
+        assert_eq!(Some(0x2810), program.symbol("after"));
+        assert_eq!(None, program.symbol("START"));
+        assert_eq!(0, assemble("").unwrap().bytes().len());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Error {
+                line: 2,
+                kind: ErrorKind::Undefined("nowhere".to_string())
+            },
+            error("        NOP\n        JMP     nowhere")
+        );
+        assert_eq!(
+            ErrorKind::Redefined("x".to_string()),
+            error("x NOP\nx NOP").kind
+        );
+        assert_eq!(
+            ErrorKind::Overlap(0x10),
+            error(" ORG 16\n NOP\n ORG 16\n NOP").kind
+        );
+        assert_eq!(
+            ErrorKind::Undefined("later".to_string()),
+            error(" ORG later\nlater NOP").kind
+        );
+        assert_eq!(ErrorKind::BranchTooFar(0x100), error(" BR 256").kind);
+        assert_eq!(ErrorKind::OutOfRange(300), error(" DC 300").kind);
+        assert_eq!(ErrorKind::MissingLabel, error(" EQU 1").kind);
+        assert_eq!(ErrorKind::BadOperands, error(" CARTRIDGE 1").kind);
+        assert_eq!(
+            "line 1: unknown mnemonic MOV",
+            std::format!("{}", error(" mov a,b"))
+        );
+    }
+
+    // every instruction of the random cartridge as MAME disassembled it
+    #[test]
+    fn mame_trace() {
+        let log = std::fs::read_to_string("../chf-emulator/testfiles/test.log").unwrap();
+        let binary = std::fs::read("../chf-emulator/testfiles/test.bin").unwrap();
+
+        for line in log.lines().filter(|line| !line.ends_with("(?)")) {
+            let (address, instruction) = line[46..].split_at(4);
+            let address = u16::from_str_radix(address, 16).unwrap();
+            let source = std::format!(" ORG {}\n{}", address, &instruction[1..]);
+            let program = assemble(&source).unwrap();
+
+            let start = (address - CARTRIDGE_START) as usize;
+            assert_eq!(
+                &binary[start..start + program.bytes().len()],
+                program.bytes(),
+                "{}",
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::{env, fs, process};
+
+use chf_asm::assemble;
+
+const USAGE: &str = "chf-asm <source> <binary>";
+
+/// Assembles an F8 source file, for a cartridge start it with `CARTRIDGE`
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+
+    let source = match fs::read_to_string(&args[1]) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{}: {}", args[1], error);
+            process::exit(1);
+        }
+    };
+
+    let program = match assemble(&source) {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("{}: {}", args[1], error);
+            process::exit(1);
+        }
+    };
+
+    if let Err(error) = fs::write(&args[2], program.bytes()) {
+        eprintln!("{}: {}", args[2], error);
+        process::exit(1);
+    }
+    println!(
+        "{:04x} - {:04x}, {} bytes",
+        program.origin(),
+        program.origin() as usize + program.bytes().len().max(1) - 1,
+        program.bytes().len()
+    );
+}
@@ -0,0 +1,327 @@
+//! The F8 instructions with the Fairchild mnemonics
+//!
+//! Scratchpad registers are `R0` - `R11` (9 - 11 also as `J`, `HU` and `HL`) or `S`, `I` and `D`
+//! for the one the ISAR points to, `I` and `D` increment / decrement the ISAR afterwards. They can
+//! be written as numbers too, except for `DS` - with a number that's the directive reserving bytes.
+//!
+//! Branches take the target address, the same as MAME shows them. Besides `BT` and `BF` with the
+//! flags to test there are `BR`, `BP`, `BC`, `BZ`, `BM`, `BNC`, `BNZ`, `BNO` and `BR7`.
+
+use crate::ErrorKind;
+
+/// What an instruction needs to know about the line it's on
+pub(crate) struct Context<'a> {
+    pub location: u16,
+    /// Evaluates an operand
+    pub value: &'a dyn Fn(&str) -> Result<i32, ErrorKind>,
+    /// Branch distances are only known in the final pass
+    pub final_pass: bool,
+}
+
+const IMPLIED: [(&str, u8); 19] = [
+    ("PK", 0x0c),
+    ("LM", 0x16),
+    ("ST", 0x17),
+    ("COM", 0x18),
+    ("LNK", 0x19),
+    ("DI", 0x1a),
+    ("EI", 0x1b),
+    ("POP", 0x1c),
+    ("INC", 0x1f),
+    ("NOP", 0x2b),
+    ("XDC", 0x2c),
+    ("AM", 0x88),
+    ("AMD", 0x89),
+    ("NM", 0x8a),
+    ("OM", 0x8b),
+    ("XM", 0x8c),
+    ("CM", 0x8d),
+    ("ADC", 0x8e),
+    ("CLR", 0x70),
+];
+
+// LR with anything but a scratchpad register and A
+const LR_SPECIAL: [(&str, &str, u8); 19] = [
+    ("A", "KU", 0x00),
+    ("A", "KL", 0x01),
+    ("A", "QU", 0x02),
+    ("A", "QL", 0x03),
+    ("KU", "A", 0x04),
+    ("KL", "A", 0x05),
+    ("QU", "A", 0x06),
+    ("QL", "A", 0x07),
+    ("K", "P", 0x08),
+    ("P", "K", 0x09),
+    ("A", "IS", 0x0a),
+    ("IS", "A", 0x0b),
+    ("P0", "Q", 0x0d),
+    ("Q", "DC", 0x0e),
+    ("DC", "Q", 0x0f),
+    ("DC", "H", 0x10),
+    ("H", "DC", 0x11),
+    ("W", "J", 0x1d),
+    ("J", "W", 0x1e),
+];
+
+const IMMEDIATE: [(&str, u8); 8] = [
+    ("LI", 0x20),
+    ("NI", 0x21),
+    ("OI", 0x22),
+    ("XI", 0x23),
+    ("AI", 0x24),
+    ("CI", 0x25),
+    ("IN", 0x26),
+    ("OUT", 0x27),
+];
+
+const ADDRESS: [(&str, u8); 3] = [("PI", 0x28), ("JMP", 0x29), ("DCI", 0x2a)];
+
+const SCRATCHPAD: [(&str, u8); 5] = [
+    ("DS", 0x30),
+    ("AS", 0xc0),
+    ("ASD", 0xd0),
+    ("XS", 0xe0),
+    ("NS", 0xf0),
+];
+
+// the first operand is added to the opcode, with its largest value
+const SMALL: [(&str, u8, i32); 5] = [
+    ("LISU", 0x60, 7),
+    ("LISL", 0x68, 7),
+    ("LIS", 0x70, 15),
+    ("INS", 0xa0, 15),
+    ("OUTS", 0xb0, 15),
+];
+
+const BRANCH: [(&str, u8); 9] = [
+    ("BR", 0x90),
+    ("BP", 0x81),
+    ("BC", 0x82),
+    ("BZ", 0x84),
+    ("BM", 0x91),
+    ("BNC", 0x92),
+    ("BNZ", 0x94),
+    ("BNO", 0x98),
+    ("BR7", 0x8f),
+];
+
+/// The scratchpad register called `name`
+pub fn register(name: &str) -> Option<u8> {
+    let name = name.trim().to_ascii_uppercase();
+    match name.as_str() {
+        "J" => Some(9),
+        "HU" => Some(10),
+        "HL" => Some(11),
+        "S" => Some(12),
+        "I" => Some(13),
+        "D" => Some(14),
+        _ => name
+            .strip_prefix('R')
+            .and_then(|number| number.parse().ok())
+            .filter(|number| *number <= 11),
+    }
+}
+
+/// The bytes of an instruction
+pub(crate) fn encode(
+    mnemonic: &str,
+    operands: &[&str],
+    context: &Context,
+) -> Result<Vec<u8>, ErrorKind> {
+    let mnemonic = mnemonic.to_ascii_uppercase();
+    let find = |table: &[(&str, u8)]| {
+        table
+            .iter()
+            .find(|(name, _)| *name == mnemonic)
+            .map(|(_, opcode)| *opcode)
+    };
+
+    if let Some(opcode) = find(&IMPLIED) {
+        count(operands, 0)?;
+        return Ok(vec![opcode]);
+    }
+
+    if let Some(opcode) = find(&IMMEDIATE) {
+        count(operands, 1)?;
+        let value = ranged(context, operands[0], -0x80, 0xff)?;
+        return Ok(vec![opcode, value as u8]);
+    }
+
+    if let Some(opcode) = find(&ADDRESS) {
+        count(operands, 1)?;
+        let address = ranged(context, operands[0], 0, 0xffff)?;
+        return Ok(vec![opcode, (address >> 8) as u8, address as u8]);
+    }
+
+    if let Some(opcode) = find(&SCRATCHPAD) {
+        count(operands, 1)?;
+        return Ok(vec![opcode + scratchpad(context, operands[0])?]);
+    }
+
+    if let Some((_, opcode, max)) = SMALL.iter().find(|(name, _, _)| *name == mnemonic) {
+        count(operands, 1)?;
+        return Ok(vec![opcode + ranged(context, operands[0], 0, *max)? as u8]);
+    }
+
+    if let Some(opcode) = find(&BRANCH) {
+        count(operands, 1)?;
+        return branch(context, opcode, operands[0]);
+    }
+
+    match mnemonic.as_str() {
+        "SR" | "SL" => {
+            count(operands, 1)?;
+            let opcode = match (mnemonic.as_str(), (context.value)(operands[0])?) {
+                ("SR", 1) => 0x12,
+                ("SL", 1) => 0x13,
+                ("SR", 4) => 0x14,
+                ("SL", 4) => 0x15,
+                (_, value) => return Err(ErrorKind::OutOfRange(value)),
+            };
+            Ok(vec![opcode])
+        }
+        "BT" | "BF" => {
+            count(operands, 2)?;
+            let (opcode, max) = if mnemonic == "BT" {
+                (0x80, 7)
+            } else {
+                (0x90, 15)
+            };
+            let test = ranged(context, operands[0], 0, max)?;
+            branch(context, opcode + test as u8, operands[1])
+        }
+        "LR" => {
+            count(operands, 2)?;
+            let to = operands[0].trim().to_ascii_uppercase();
+            let from = operands[1].trim().to_ascii_uppercase();
+            if let Some((_, _, opcode)) = LR_SPECIAL.iter().find(|(t, f, _)| *t == to && *f == from)
+            {
+                Ok(vec![*opcode])
+            } else if to == "A" {
+                Ok(vec![0x40 + scratchpad(context, &from)?])
+            } else if from == "A" {
+                Ok(vec![0x50 + scratchpad(context, &to)?])
+            } else {
+                Err(ErrorKind::BadOperands)
+            }
+        }
+        _ => Err(ErrorKind::UnknownMnemonic(mnemonic)),
+    }
+}
+
+fn count(operands: &[&str], expected: usize) -> Result<(), ErrorKind> {
+    if operands.len() == expected {
+        Ok(())
+    } else {
+        Err(ErrorKind::BadOperands)
+    }
+}
+
+fn ranged(context: &Context, operand: &str, min: i32, max: i32) -> Result<i32, ErrorKind> {
+    let value = (context.value)(operand)?;
+    if !(min..=max).contains(&value) {
+        return Err(ErrorKind::OutOfRange(value));
+    }
+    Ok(value)
+}
+
+fn scratchpad(context: &Context, operand: &str) -> Result<u8, ErrorKind> {
+    match register(operand) {
+        Some(register) => Ok(register),
+        None => Ok(ranged(context, operand, 0, 14)? as u8),
+    }
+}
+
+// the offset is relative to the offset byte
+fn branch(context: &Context, opcode: u8, target: &str) -> Result<Vec<u8>, ErrorKind> {
+    let target = (context.value)(target)?;
+    let offset = target - (context.location as i32 + 1);
+    if context.final_pass && !(-0x80..=0x7f).contains(&offset) {
+        return Err(ErrorKind::BranchTooFar(target));
+    }
+    Ok(vec![opcode, offset as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> Result<i32, ErrorKind> {
+        crate::expr::eval(text, 0x900, &|name| match name {
+            "far" => Some(0x1000),
+            _ => None,
+        })
+    }
+
+    fn bytes(line: &str) -> Result<Vec<u8>, ErrorKind> {
+        let context = Context {
+            location: 0x900,
+            value: &value,
+            final_pass: true,
+        };
+        let mut parts = line.splitn(2, ' ');
+        let mnemonic = parts.next().unwrap();
+        let operands: Vec<&str> = match parts.next() {
+            Some(operands) => operands.split(',').collect(),
+            None => Vec::new(),
+        };
+        encode(mnemonic, &operands, &context)
+    }
+
+    #[test]
+    fn instructions() {
+        let expected: [(&str, &[u8]); 30] = [
+            ("LR A,KU", &[0x00]),
+            ("LR J,W", &[0x1e]),
+            ("LR DC,H", &[0x10]),
+            ("LR A,R7", &[0x47]),
+            ("LR A,HL", &[0x4b]),
+            ("LR A,I", &[0x4d]),
+            ("LR D,A", &[0x5e]),
+            ("lr r3,a", &[0x53]),
+            ("LR 3,A", &[0x53]),
+            ("LR A,IS", &[0x0a]),
+            ("SR 4", &[0x14]),
+            ("SL 1", &[0x13]),
+            ("CLR", &[0x70]),
+            ("LI H'C6'", &[0x20, 0xc6]),
+            ("AI -1", &[0x24, 0xff]),
+            ("OUT 7", &[0x27, 0x07]),
+            ("PI H'0679'", &[0x28, 0x06, 0x79]),
+            ("DCI far", &[0x2a, 0x10, 0x00]),
+            ("DS HU", &[0x3a]),
+            ("DS S", &[0x3c]),
+            ("XS J", &[0xe9]),
+            ("NS D", &[0xfe]),
+            ("LISU 7", &[0x67]),
+            ("LISL 3", &[0x6b]),
+            ("LIS H'0A'", &[0x7a]),
+            ("INS 4", &[0xa4]),
+            ("OUTS 0", &[0xb0]),
+            ("BR $", &[0x90, 0xff]),
+            ("BNZ $+10", &[0x94, 0x09]),
+            ("BT 4,$-4", &[0x84, 0xfb]),
+        ];
+        for (line, bytes_of_line) in expected.iter() {
+            assert_eq!(Ok(bytes_of_line.to_vec()), bytes(line), "{}", line);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Err(ErrorKind::UnknownMnemonic("FOO".to_string())),
+            bytes("FOO")
+        );
+        assert_eq!(Err(ErrorKind::BadOperands), bytes("LM 1"));
+        assert_eq!(Err(ErrorKind::BadOperands), bytes("LR KU,KL"));
+        assert_eq!(Err(ErrorKind::OutOfRange(15)), bytes("AS 15"));
+        assert_eq!(Err(ErrorKind::OutOfRange(8)), bytes("LISU 8"));
+        assert_eq!(Err(ErrorKind::OutOfRange(2)), bytes("SR 2"));
+        assert_eq!(Err(ErrorKind::OutOfRange(0x100)), bytes("LI 256"));
+        assert_eq!(Err(ErrorKind::BranchTooFar(0x1000)), bytes("BR far"));
+        assert_eq!(Err(ErrorKind::OutOfRange(8)), bytes("BT 8,$"));
+        assert_eq!(None, register("R12"));
+        assert_eq!(Some(11), register("r11"));
+    }
+}
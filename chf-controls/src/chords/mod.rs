@@ -0,0 +1,228 @@
+//! The chords of the console buttons, all of them start with mode: mode and hold saves the state,
+//! mode and time loads it and mode and start goes back to the menu - held for `CHORD_FRAMES`
+//!
+//! `Chords::update` takes the keys once per frame and returns the keys the game sees. While mode
+//! is held the game sees neither the console buttons nor the left controller (which picks the slot
+//! of a state), after a chord they stay hidden until all console buttons are released. So the game
+//! doesn't run on with the buttons of a chord and the state or save written is the one before the
+//! chord. Mode released without a chord is passed on to the game for `MODE_FRAMES` frames.
+
+use chf_emulator::{Input, Key};
+
+/// Frames a chord has to be held, a second
+pub const CHORD_FRAMES: u32 = 60;
+
+/// Frames the game sees mode pressed once it's released without a chord
+pub const MODE_FRAMES: u8 = 6;
+
+const CONSOLE_KEYS: [Key; 4] = [Key::Time, Key::Mode, Key::Hold, Key::Start];
+
+// hidden from the game while mode is held
+const HIDDEN_KEYS: [Key; 12] = [
+    Key::Time,
+    Key::Mode,
+    Key::Hold,
+    Key::Start,
+    Key::Right0,
+    Key::Left0,
+    Key::Back0,
+    Key::Forward0,
+    Key::CounterClockwise0,
+    Key::Clockwise0,
+    Key::Pull0,
+    Key::Push0,
+];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Chord {
+    /// Saves the state to the slot
+    StoreState(u8),
+    /// Loads the state of the slot
+    LoadState(u8),
+    Menu,
+}
+
+#[derive(Debug)]
+pub struct Chords {
+    held: Option<Chord>,
+    frames: u32,
+    done: Option<Chord>,
+    mode: bool,
+    // a chord was held since mode was pressed
+    chorded: bool,
+    mode_frames: u8,
+}
+
+impl Chords {
+    pub const fn new() -> Chords {
+        Chords {
+            held: None,
+            frames: 0,
+            done: None,
+            mode: false,
+            chorded: false,
+            mode_frames: 0,
+        }
+    }
+
+    /// Takes the keys of a frame and returns the keys the game sees
+    pub fn update(&mut self, mut keys: Input) -> Input {
+        let chord = held(keys);
+        self.frames = match chord {
+            Some(_) if chord == self.held => self.frames.saturating_add(1),
+            Some(_) => 1,
+            None => 0,
+        };
+        self.held = chord;
+        if self.frames == CHORD_FRAMES {
+            self.done = chord;
+        }
+
+        let mode = keys.is_pressed(Key::Mode);
+        self.chorded |= chord.is_some();
+        if self.mode && !mode && !self.chorded {
+            self.mode_frames = MODE_FRAMES;
+        }
+        self.mode = mode;
+        if !CONSOLE_KEYS.iter().any(|key| keys.is_pressed(*key)) {
+            self.chorded = false;
+        }
+
+        if mode || self.chorded {
+            for key in HIDDEN_KEYS.iter() {
+                keys.set(*key, false);
+            }
+        }
+        if self.mode_frames > 0 {
+            self.mode_frames -= 1;
+            keys.set(Key::Mode, true);
+        }
+        keys
+    }
+
+    /// The chord held for `CHORD_FRAMES` frames, once per hold
+    pub fn take(&mut self) -> Option<Chord> {
+        self.done.take()
+    }
+}
+
+impl Default for Chords {
+    fn default() -> Self {
+        Chords::new()
+    }
+}
+
+// the chord of the keys, the left controller forward, right, back or left chooses slot 1 - 4 of
+// a state, otherwise it's slot 0
+fn held(keys: Input) -> Option<Chord> {
+    if !keys.is_pressed(Key::Mode) {
+        return None;
+    }
+
+    let slot = if keys.is_pressed(Key::Forward0) {
+        1
+    } else if keys.is_pressed(Key::Right0) {
+        2
+    } else if keys.is_pressed(Key::Back0) {
+        3
+    } else if keys.is_pressed(Key::Left0) {
+        4
+    } else {
+        0
+    };
+
+    if keys.is_pressed(Key::Hold) {
+        Some(Chord::StoreState(slot))
+    } else if keys.is_pressed(Key::Time) {
+        Some(Chord::LoadState(slot))
+    } else if keys.is_pressed(Key::Start) {
+        Some(Chord::Menu)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(pressed: &[Key]) -> Input {
+        let mut input = Input::default();
+        for key in pressed {
+            input.set(*key, true);
+        }
+        input
+    }
+
+    #[test]
+    fn store_state() {
+        let mut chords = Chords::new();
+
+        assert_eq!(Input(0), chords.update(keys(&[Key::Mode])));
+        let chord = keys(&[Key::Mode, Key::Hold, Key::Back0, Key::Right1]);
+        for _ in 0..CHORD_FRAMES - 1 {
+            assert_eq!(keys(&[Key::Right1]), chords.update(chord));
+            assert_eq!(None, chords.take());
+        }
+        assert_eq!(keys(&[Key::Right1]), chords.update(chord));
+        assert_eq!(Some(Chord::StoreState(3)), chords.take());
+
+        // once per hold
+        chords.update(chord);
+        assert_eq!(None, chords.take());
+
+        // hold stays hidden until it's released, mode isn't passed on after a chord
+        assert_eq!(Input(0), chords.update(keys(&[Key::Hold])));
+        for _ in 0..MODE_FRAMES {
+            assert_eq!(Input(0), chords.update(Input(0)));
+        }
+        assert_eq!(keys(&[Key::Hold]), chords.update(keys(&[Key::Hold])));
+    }
+
+    #[test]
+    fn chords() {
+        let mut chords = Chords::new();
+        for (pressed, chord) in [
+            (&[Key::Mode, Key::Time][..], Chord::LoadState(0)),
+            (&[Key::Mode, Key::Time, Key::Left0][..], Chord::LoadState(4)),
+            (&[Key::Mode, Key::Start][..], Chord::Menu),
+        ]
+        .iter()
+        {
+            for _ in 0..CHORD_FRAMES {
+                chords.update(keys(pressed));
+            }
+            assert_eq!(Some(*chord), chords.take());
+        }
+    }
+
+    #[test]
+    fn released_early() {
+        let mut chords = Chords::new();
+        let chord = keys(&[Key::Mode, Key::Start]);
+        for _ in 0..CHORD_FRAMES - 1 {
+            chords.update(chord);
+        }
+        chords.update(keys(&[Key::Mode]));
+        chords.update(chord);
+        assert_eq!(None, chords.take());
+    }
+
+    #[test]
+    fn mode_alone() {
+        let mut chords = Chords::new();
+        for _ in 0..CHORD_FRAMES {
+            assert_eq!(Input(0), chords.update(keys(&[Key::Mode, Key::Right0])));
+        }
+
+        // passed on once released
+        for _ in 0..MODE_FRAMES {
+            assert_eq!(
+                keys(&[Key::Mode, Key::Right0]),
+                chords.update(keys(&[Key::Right0]))
+            );
+        }
+        assert_eq!(keys(&[Key::Right0]), chords.update(keys(&[Key::Right0])));
+        assert_eq!(None, chords.take());
+    }
+}
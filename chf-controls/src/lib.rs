@@ -0,0 +1,10 @@
+//! The controls of the main MCU
+//!
+//! `scanner` reads the console buttons and both hand controllers from the GPIO input registers and
+//! debounces them, the firmware calls it from a timer interrupt. `chords` finds the chords of the
+//! console buttons which save and load states or go back to the menu and hides them from the game.
+
+#![no_std]
+
+pub mod chords;
+pub mod scanner;
@@ -0,0 +1,164 @@
+//! Reading the console buttons and both hand controllers from GPIO input registers
+//!
+//! The controllers share their lines, a select line per controller chooses the one read. A
+//! frontend calls `Scanner::sample` at a fixed rate (e.g. from a timer interrupt) with both input
+//! registers read in one go, then drives the select line returned until the next call - the
+//! time in between lets the lines settle. Two samples make a scan, a key only changes after
+//! `DEBOUNCE_SCANS` scans agree.
+
+use chf_emulator::{Input, Key};
+
+/// Scans in a row a key has to read its new state before it changes
+pub const DEBOUNCE_SCANS: u8 = 5;
+
+/// A pin of the input registers passed to `Scanner::sample`: 0 - 15 are `PA0` - `PA15`,
+/// 16 - 31 `PB0` - `PB15`
+pub type Pin = u8;
+
+pub const fn pa(n: u8) -> Pin {
+    n
+}
+
+pub const fn pb(n: u8) -> Pin {
+    16 + n
+}
+
+/// Where the keys are connected, the key is pressed while its pin is high
+#[derive(Debug)]
+pub struct Wiring {
+    /// Read once per scan, whatever is selected
+    pub direct: &'static [(Key, Pin)],
+    /// Read while select line 0 / 1 is high
+    pub selected: [&'static [(Key, Pin)]; 2],
+}
+
+#[derive(Debug)]
+pub struct Scanner {
+    wiring: &'static Wiring,
+    select: usize,
+    raw: Input,
+    debounced: Input,
+    counts: [u8; Key::ALL.len()],
+}
+
+impl Scanner {
+    pub const fn new(wiring: &'static Wiring) -> Scanner {
+        Scanner {
+            wiring,
+            select: 0,
+            raw: Input(0),
+            debounced: Input(0),
+            counts: [0; Key::ALL.len()],
+        }
+    }
+
+    /// The select line to drive high before the first `sample`
+    pub fn select(&self) -> usize {
+        self.select
+    }
+
+    /// Takes the input registers read while `select()` was high, `(GPIOB << 16) | GPIOA`, and
+    /// returns the select line for the next sample
+    pub fn sample(&mut self, pins: u32) -> usize {
+        read(&mut self.raw, self.wiring.selected[self.select], pins);
+        if self.select == 1 {
+            read(&mut self.raw, self.wiring.direct, pins);
+            self.debounce();
+        }
+
+        self.select ^= 1;
+        self.select
+    }
+
+    /// The debounced state of all keys
+    pub fn input(&self) -> Input {
+        self.debounced
+    }
+
+    fn debounce(&mut self) {
+        for key in Key::ALL.iter() {
+            let count = &mut self.counts[*key as usize];
+            let pressed = self.raw.is_pressed(*key);
+            if pressed == self.debounced.is_pressed(*key) {
+                *count = 0;
+                continue;
+            }
+
+            *count += 1;
+            if *count == DEBOUNCE_SCANS {
+                *count = 0;
+                self.debounced.set(*key, pressed);
+            }
+        }
+    }
+}
+
+fn read(input: &mut Input, keys: &[(Key, Pin)], pins: u32) {
+    for (key, pin) in keys {
+        input.set(*key, pins & 1 << pin != 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static WIRING: Wiring = Wiring {
+        direct: &[(Key::Reset, pb(11))],
+        selected: [
+            &[(Key::Start, pb(9)), (Key::Left0, pa(3))],
+            &[(Key::Mode, pb(7)), (Key::Left1, pa(3))],
+        ],
+    };
+
+    // one scan, `pins` for both select lines
+    fn scan(scanner: &mut Scanner, pins: [u32; 2]) -> Input {
+        assert_eq!(1, scanner.sample(pins[0]));
+        assert_eq!(0, scanner.sample(pins[1]));
+        scanner.input()
+    }
+
+    #[test]
+    fn decode() {
+        let mut scanner = Scanner::new(&WIRING);
+        assert_eq!(0, scanner.select());
+
+        let left = 1 << pa(3);
+        let pins = [left | 1 << pb(9), left | 1 << pb(11)];
+        for _ in 0..DEBOUNCE_SCANS {
+            scan(&mut scanner, pins);
+        }
+
+        let input = scanner.input();
+        assert!(input.is_pressed(Key::Start));
+        assert!(input.is_pressed(Key::Left0));
+        assert!(input.is_pressed(Key::Left1));
+        assert!(input.is_pressed(Key::Reset));
+        assert!(!input.is_pressed(Key::Mode));
+        assert!(!input.is_pressed(Key::Right0));
+    }
+
+    #[test]
+    fn debounce() {
+        let mut scanner = Scanner::new(&WIRING);
+        let start = [1 << pb(9), 0];
+
+        // a bounce starts counting again, the press needs `DEBOUNCE_SCANS` scans in a row
+        for _ in 0..DEBOUNCE_SCANS - 1 {
+            assert_eq!(Input(0), scan(&mut scanner, start));
+        }
+        assert_eq!(Input(0), scan(&mut scanner, [0, 0]));
+        for _ in 0..DEBOUNCE_SCANS - 1 {
+            assert_eq!(Input(0), scan(&mut scanner, start));
+        }
+        assert!(scan(&mut scanner, start).is_pressed(Key::Start));
+
+        // releasing is debounced, too
+        assert!(scan(&mut scanner, [0, 0]).is_pressed(Key::Start));
+        assert!(scan(&mut scanner, start).is_pressed(Key::Start));
+        for _ in 0..DEBOUNCE_SCANS - 1 {
+            assert!(scan(&mut scanner, [0, 0]).is_pressed(Key::Start));
+        }
+        assert_eq!(Input(0), scan(&mut scanner, [0, 0]));
+    }
+}
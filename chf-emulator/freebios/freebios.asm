@@ -0,0 +1,303 @@
+; Free Channel F BIOS
+;
+; A small replacement for the SL31253 / SL31254 ROMs, good enough to boot homebrew cartridges and
+; to run the tests without the copyrighted dumps. It has no built-in games.
+;
+; The routines homebrew calls are reached from the addresses they have in the original BIOS,
+; their register usage is documented at each routine. The original routines differ in details.
+;
+;   0x008F  delay       waits r5 * 256 loops
+;   0x00D0  clrscrn     clears the screen, r3 chooses the colors
+;   0x0107  pushk       pushes K on the stack in the scratchpad
+;   0x011E  popk        pops K from the stack in the scratchpad
+;   0x0121  joysticks   reads the console buttons and both hand controllers (free BIOS only)
+;   0x0679  drawchar    draws a character from the font
+;
+; Ports: 0 - bit 5 falling writes a pixel, bit 6 clear enables the controllers, 1 - bits 7-6 color,
+; 4 - x, 5 - y (both inverted), bits 7-6 of port 5 are the sound, drawing silences it.
+;
+; chf-asm freebios.asm freebios.bin assembles it
+
+STACK   EQU     H'37'           ; r59 points to the stack, it grows down from o67 and fits four
+BLUE    EQU     H'80'
+
+        ORG     H'0000'
+
+reset   CLR
+        OUTS    0
+        OUTS    1
+        OUTS    4
+        OUTS    5               ; silence
+        LISU    7               ; r59
+        LISL    3
+        LI      STACK
+        LR      S,A
+        DCI     H'0800'         ; a cartridge starts with 0x55
+        LM
+        CI      H'55'
+        BNZ     nocart
+        JMP     H'0802'
+
+nocart  LI      H'C6'
+        LR      R3,A
+        PI      clrscrn
+        LI      10
+        LR      R1,A
+        LI      29
+        LR      R2,A
+        DCI     message
+msgchar LM
+        CI      H'FF'
+        BZ      idle
+        LR      R0,A
+        LR      Q,DC
+        PI      drawchar
+        LR      DC,Q
+        BR      msgchar
+idle    BR      idle
+
+; NO CARTRIDGE
+message DC      BLUE+23,BLUE+24,BLUE+36,BLUE+12,BLUE+10,BLUE+27,BLUE+29,BLUE+27
+        DC      BLUE+18,BLUE+13,BLUE+16,BLUE+14,H'FF'
+
+; port 1 values of palette column 126 for r3 bits 1-0: green, black / white, grey, blue
+palette DC      H'00',H'C0',H'40',H'80'
+
+        ORG     H'008F'
+delay   JMP     delay_
+
+        ORG     H'00D0'
+clrscrn JMP     clrscrn_
+
+        ORG     H'0107'
+pushk   JMP     pushk_
+
+        ORG     H'011E'
+popk    JMP     popk_
+
+; r0 - console buttons, r1 - left controller, r4 - right controller, a set bit is a pressed button
+; changes A
+joysticks
+        CLR
+        OUTS    0
+        OUTS    1
+        OUTS    4
+        INS     0
+        COM
+        NI      H'0F'
+        LR      R0,A
+        INS     1
+        COM
+        LR      R1,A
+        INS     4
+        COM
+        LR      R4,A
+        POP
+
+; changes A, r5, r6
+delay_  CLR
+        LR      R6,A
+dl_loop DS      R6
+        BNZ     dl_loop
+        DS      R5
+        BNZ     dl_loop
+        POP
+
+; r3 bits 7-6 - port 1 value of the pixels, bits 1-0 - the palette (see palette)
+; changes A, r1, r2, DC
+clrscrn_
+        LR      A,R3
+        NI      H'C0'
+        OUTS    1
+        LI      H'3F'
+        LR      R2,A
+cl_row  LR      A,R2
+        OUTS    5
+        LI      H'7F'
+        LR      R1,A
+cl_col  LR      A,R1
+        OUTS    4
+        LI      H'60'
+        OUTS    0
+        CLR
+        OUTS    0
+        DS      R1
+        BP      cl_col
+        DS      R2
+        BP      cl_row
+
+        DCI     palette         ; column 125 is color 0, column 126 picks the palette
+        LR      A,R3
+        NI      3
+        ADC
+        LM
+        LR      R1,A
+        LI      H'3F'
+        LR      R2,A
+cl_pal  LR      A,R2
+        OUTS    5
+        LIS     127-125
+        OUTS    4
+        LI      H'C0'
+        OUTS    1
+        LI      H'60'
+        OUTS    0
+        CLR
+        OUTS    0
+        LIS     127-126
+        OUTS    4
+        LR      A,R1
+        OUTS    1
+        LI      H'60'
+        OUTS    0
+        CLR
+        OUTS    0
+        DS      R2
+        BP      cl_pal
+        POP
+
+; K is stored at the stack pointer, changes A, J
+pushk_  LR      A,IS
+        LR      J,A
+        LISU    7               ; r59
+        LISL    3
+        LR      A,S
+        LR      IS,A
+        LR      A,KL
+        LR      D,A
+        LR      A,KU
+        LR      D,A
+        LR      A,IS
+        LISU    7               ; r59
+        LISL    3
+        LR      S,A
+        LR      A,J
+        LR      IS,A
+        POP
+
+; changes A, J
+popk_   LR      A,IS
+        LR      J,A
+        LISU    7               ; r59
+        LISL    3
+        LR      A,S
+        INC
+        LR      IS,A
+        LR      A,I
+        LR      KU,A
+        LR      A,S
+        LR      KL,A
+        LR      A,IS
+        LISU    7               ; r59
+        LISL    3
+        LR      S,A
+        LR      A,J
+        LR      IS,A
+        POP
+
+; r0 bits 5-0 - the character (0 - 9, A - Z, space), bits 7-6 - port 1 value of its pixels
+; r1 - x, r2 - y of the top left corner, the other pixels of the 5 x 5 cell get color 0
+; r1 is moved to the next character, changes A, r4 - r8, H, DC
+drawchar_
+        LR      A,R0
+        NI      H'3F'
+        LR      R4,A
+        SL      1
+        SL      1
+        AS      R4
+        LR      HL,A
+        LI      font/256
+        LR      HU,A
+        LR      DC,H
+        LR      A,R2
+        LR      R5,A
+        LIS     5
+        LR      R6,A
+dc_row  LM                      ; bit 7 is the leftmost pixel
+        LR      R4,A
+        LR      A,R5
+        COM
+        NI      H'3F'
+        OUTS    5
+        LR      A,R1
+        LR      R7,A
+        LIS     5
+        LR      R8,A
+dc_col  LR      A,R7
+        COM
+        NI      H'7F'
+        OUTS    4
+        LR      A,R4
+        NI      H'80'
+        LI      H'C0'
+        BZ      dc_out
+        LR      A,R0
+        NI      H'C0'
+dc_out  OUTS    1
+        LI      H'60'
+        OUTS    0
+        CLR
+        OUTS    0
+        LR      A,R4
+        SL      1
+        LR      R4,A
+        LR      A,R7
+        INC
+        LR      R7,A
+        DS      R8
+        BNZ     dc_col
+        LR      A,R5
+        INC
+        LR      R5,A
+        DS      R6
+        BNZ     dc_row
+        LR      A,R1
+        AI      6
+        LR      R1,A
+        POP
+
+        ORG     H'0679'
+drawchar
+        JMP     drawchar_
+
+        ORG     H'0700'
+font    DC      H'F8',H'88',H'88',H'88',H'F8'   ; 0
+        DC      H'20',H'60',H'20',H'20',H'70'   ; 1
+        DC      H'F8',H'08',H'F8',H'80',H'F8'   ; 2
+        DC      H'F8',H'08',H'78',H'08',H'F8'   ; 3
+        DC      H'88',H'88',H'F8',H'08',H'08'   ; 4
+        DC      H'F8',H'80',H'F8',H'08',H'F8'   ; 5
+        DC      H'F8',H'80',H'F8',H'88',H'F8'   ; 6
+        DC      H'F8',H'08',H'10',H'20',H'20'   ; 7
+        DC      H'F8',H'88',H'F8',H'88',H'F8'   ; 8
+        DC      H'F8',H'88',H'F8',H'08',H'F8'   ; 9
+        DC      H'F8',H'88',H'F8',H'88',H'88'   ; A
+        DC      H'F0',H'88',H'F0',H'88',H'F0'   ; B
+        DC      H'F8',H'80',H'80',H'80',H'F8'   ; C
+        DC      H'F0',H'88',H'88',H'88',H'F0'   ; D
+        DC      H'F8',H'80',H'E0',H'80',H'F8'   ; E
+        DC      H'F8',H'80',H'E0',H'80',H'80'   ; F
+        DC      H'F8',H'80',H'B8',H'88',H'F8'   ; G
+        DC      H'88',H'88',H'F8',H'88',H'88'   ; H
+        DC      H'F8',H'20',H'20',H'20',H'F8'   ; I
+        DC      H'08',H'08',H'08',H'88',H'F8'   ; J
+        DC      H'88',H'90',H'E0',H'90',H'88'   ; K
+        DC      H'80',H'80',H'80',H'80',H'F8'   ; L
+        DC      H'88',H'D8',H'A8',H'88',H'88'   ; M
+        DC      H'88',H'C8',H'A8',H'98',H'88'   ; N
+        DC      H'F8',H'88',H'88',H'88',H'F8'   ; O
+        DC      H'F8',H'88',H'F8',H'80',H'80'   ; P
+        DC      H'F8',H'88',H'A8',H'90',H'E8'   ; Q
+        DC      H'F8',H'88',H'F8',H'90',H'88'   ; R
+        DC      H'F8',H'80',H'F8',H'08',H'F8'   ; S
+        DC      H'F8',H'20',H'20',H'20',H'20'   ; T
+        DC      H'88',H'88',H'88',H'88',H'F8'   ; U
+        DC      H'88',H'88',H'88',H'50',H'20'   ; V
+        DC      H'88',H'88',H'A8',H'D8',H'88'   ; W
+        DC      H'88',H'50',H'20',H'50',H'88'   ; X
+        DC      H'88',H'50',H'20',H'20',H'20'   ; Y
+        DC      H'F8',H'10',H'20',H'40',H'F8'   ; Z
+        DC      H'00',H'00',H'00',H'00',H'00'   ; space
+
+        ORG     H'07FF'         ; fills both ROMs
+        DC      H'FF'
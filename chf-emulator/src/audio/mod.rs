@@ -0,0 +1,313 @@
+//! The sound circuit as PCM samples, for frontends playing them through a DAC
+//!
+//! `Synth` turns what `ChannelF::sound` reports and the cycles the CPU ran into 8 bit samples at
+//! `CORE_RATE`, a tone starts at full volume and decays like on the console (half-life of about
+//! 9 ms). `Resampler` converts them to the rate of the DAC, `SampleQueue` hands them over to an
+//! interrupt filling the halves of a DMA double buffer.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Sound;
+
+/// Clock of the CPU in Hz
+pub const CPU_HZ: u32 = 1_789_772;
+
+/// CPU cycles per sample of `Synth`
+pub const CYCLES_PER_SAMPLE: u32 = 64;
+
+/// Samples per second of `Synth`
+pub const CORE_RATE: u32 = CPU_HZ / CYCLES_PER_SAMPLE;
+
+/// The sample of silence, samples are unsigned
+pub const SILENCE: u8 = 128;
+
+// the volume of a tone when it starts, in 1/256
+const MAX_ENVELOPE: u32 = 127 << 8;
+
+// the envelope is multiplied by this (in 1/65536) every sample - 2^(-1 / (9 ms * CORE_RATE))
+const DECAY: u32 = 65355;
+
+/// Samples kept by a `SampleQueue`, one less fit in
+pub const QUEUE_LEN: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct Synth {
+    sound: Sound,
+    // position in the period of the tone, in cycles
+    phase: u32,
+    // cycles not turned into a sample yet
+    cycles: u32,
+    envelope: u32,
+}
+
+impl Synth {
+    pub fn new() -> Synth {
+        Synth {
+            sound: Sound::Silence,
+            phase: 0,
+            cycles: 0,
+            envelope: 0,
+        }
+    }
+
+    /// Called with every sound reported, a changed tone starts at full volume
+    pub fn set_sound(&mut self, sound: Sound) {
+        if sound == self.sound {
+            return;
+        }
+
+        self.sound = sound;
+        self.phase = 0;
+        self.envelope = match sound {
+            Sound::Silence => 0,
+            _ => MAX_ENVELOPE,
+        };
+    }
+
+    /// Calls `out` with the samples of `cycles` more CPU cycles
+    pub fn run<F: FnMut(u8)>(&mut self, cycles: u32, out: &mut F) {
+        self.cycles += cycles;
+        while self.cycles >= CYCLES_PER_SAMPLE {
+            self.cycles -= CYCLES_PER_SAMPLE;
+            out(self.sample());
+        }
+    }
+
+    fn sample(&mut self) -> u8 {
+        let period = match self.sound {
+            Sound::Silence => return SILENCE,
+            Sound::Frequency1Khz => CPU_HZ / 1000,
+            Sound::Frequency500Hz => CPU_HZ / 500,
+            Sound::Frequency120Hz => CPU_HZ / 120,
+        };
+
+        let level = (self.envelope >> 8) as u8;
+        let sample = if self.phase < period / 2 {
+            SILENCE + level
+        } else {
+            SILENCE - level
+        };
+
+        self.phase = (self.phase + CYCLES_PER_SAMPLE) % period;
+        self.envelope = (self.envelope * DECAY) >> 16;
+        sample
+    }
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Synth::new()
+    }
+}
+
+/// Converts samples from one rate to another by linear interpolation
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    // input samples per output sample, in 1/65536
+    step: u32,
+    // time of the next output sample after `previous`, in 1/65536 input samples
+    position: u32,
+    previous: u8,
+    current: u8,
+}
+
+impl Resampler {
+    pub fn new(from: u32, to: u32) -> Resampler {
+        Resampler {
+            step: (((from as u64) << 16) / to as u64) as u32,
+            position: 0,
+            previous: SILENCE,
+            current: SILENCE,
+        }
+    }
+
+    /// Takes the next input sample, calls `out` with the output samples due
+    pub fn push<F: FnMut(u8)>(&mut self, sample: u8, out: &mut F) {
+        self.previous = self.current;
+        self.current = sample;
+
+        while self.position < 1 << 16 {
+            let previous = self.previous as u32;
+            let current = self.current as u32;
+            let sample = (previous * ((1 << 16) - self.position) + current * self.position) >> 16;
+            out(sample as u8);
+            self.position += self.step;
+        }
+        self.position -= 1 << 16;
+    }
+}
+
+/// Samples passed from one producer to one consumer, e.g. the main loop and an interrupt
+pub struct SampleQueue {
+    samples: UnsafeCell<[u8; QUEUE_LEN]>,
+    // written by the consumer
+    head: AtomicUsize,
+    // written by the producer
+    tail: AtomicUsize,
+}
+
+// `push` and `pop` only touch the slots they own until they move `tail` / `head`
+unsafe impl Sync for SampleQueue {}
+
+impl SampleQueue {
+    pub const fn new() -> SampleQueue {
+        SampleQueue {
+            samples: UnsafeCell::new([SILENCE; QUEUE_LEN]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of samples queued
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        (tail + QUEUE_LEN - head) % QUEUE_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Only called by the producer, false if the queue is full and `sample` dropped
+    pub fn push(&self, sample: u8) -> bool {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next = (tail + 1) % QUEUE_LEN;
+        if next == self.head.load(Ordering::Acquire) {
+            return false;
+        }
+
+        unsafe { (*self.samples.get())[tail] = sample };
+        self.tail.store(next, Ordering::Release);
+        true
+    }
+
+    /// Only called by the consumer
+    pub fn pop(&self) -> Option<u8> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let sample = unsafe { (*self.samples.get())[head] };
+        self.head.store((head + 1) % QUEUE_LEN, Ordering::Release);
+        Some(sample)
+    }
+
+    /// Only called by the consumer: fills the half of the DMA buffer just played, `last` is
+    /// repeated when the queue runs empty - returns the number of samples missing
+    pub fn fill(&self, half: &mut [u8], last: &mut u8) -> usize {
+        let mut missing = 0;
+        for slot in half.iter_mut() {
+            match self.pop() {
+                Some(sample) => *last = sample,
+                None => missing += 1,
+            }
+            *slot = *last;
+        }
+        missing
+    }
+}
+
+impl Default for SampleQueue {
+    fn default() -> Self {
+        SampleQueue::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    fn samples(synth: &mut Synth, cycles: u32) -> Vec<u8> {
+        let mut samples = Vec::new();
+        synth.run(cycles, &mut |sample| samples.push(sample));
+        samples
+    }
+
+    #[test]
+    fn tones() {
+        let mut synth = Synth::new();
+        assert_eq!(
+            vec![SILENCE; 10],
+            samples(&mut synth, 10 * CYCLES_PER_SAMPLE + 5)
+        );
+
+        // half a period of 1 kHz is 14 samples, the rest of the cycles before is kept
+        synth.set_sound(Sound::Frequency1Khz);
+        let tone = samples(&mut synth, 28 * CYCLES_PER_SAMPLE);
+        assert_eq!(28, tone.len());
+        assert_eq!(SILENCE + 127, tone[0]);
+        assert!(tone[..14].iter().all(|sample| *sample > SILENCE));
+        assert!(tone[14..].iter().all(|sample| *sample < SILENCE));
+
+        // the volume decays to half in about 9 ms, the tone written again doesn't restart it
+        synth.set_sound(Sound::Frequency1Khz);
+        let tone = samples(&mut synth, CPU_HZ / 1000 * 9);
+        let last_high = tone.iter().rev().find(|sample| **sample > SILENCE).unwrap();
+        assert!((SILENCE + 60..=SILENCE + 66).contains(last_high));
+
+        // another tone starts at full volume
+        synth.set_sound(Sound::Frequency120Hz);
+        assert_eq!(vec![SILENCE + 127], samples(&mut synth, CYCLES_PER_SAMPLE));
+
+        synth.set_sound(Sound::Silence);
+        assert_eq!(vec![SILENCE; 3], samples(&mut synth, 3 * CYCLES_PER_SAMPLE));
+    }
+
+    fn resampled(from: u32, to: u32, input: &[u8]) -> Vec<u8> {
+        let mut resampler = Resampler::new(from, to);
+        let mut output = Vec::new();
+        for sample in input {
+            resampler.push(*sample, &mut |sample| output.push(sample));
+        }
+        output
+    }
+
+    #[test]
+    fn resample() {
+        // the same rate delays by one sample
+        assert_eq!(vec![SILENCE, 10, 20], resampled(100, 100, &[10, 20, 30]));
+
+        assert_eq!(
+            vec![SILENCE, 69, 10, 15, 20, 25],
+            resampled(100, 200, &[10, 20, 30])
+        );
+        assert_eq!(
+            vec![SILENCE, 10, 30],
+            resampled(200, 100, &[0, 10, 20, 30, 40, 50])
+        );
+
+        // about the rate asked for
+        let output = resampled(CORE_RATE, 16_000, &[SILENCE; 27_965]);
+        assert!((15_999..=16_001).contains(&output.len()));
+    }
+
+    #[test]
+    fn queue() {
+        let queue = SampleQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(None, queue.pop());
+
+        for sample in 0..QUEUE_LEN - 1 {
+            assert!(queue.push(sample as u8));
+        }
+        assert!(!queue.push(0));
+        assert_eq!(QUEUE_LEN - 1, queue.len());
+        assert_eq!(Some(0), queue.pop());
+        assert!(queue.push(0xff));
+
+        // an empty queue repeats the last sample
+        let mut half = vec![0u8; QUEUE_LEN + 2];
+        let mut last = SILENCE;
+        assert_eq!(3, queue.fill(&mut half, &mut last));
+        assert_eq!(1, half[0]);
+        assert_eq!([0xfe, 0xff, 0xff, 0xff, 0xff], half[QUEUE_LEN - 3..]);
+        assert_eq!(0xff, last);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! The BIOS ROMs, known by their CRC-32
+//!
+//! The console has two 1K ROMs: SL31253 at 0x0000 and SL31254 at 0x0400. The Channel F System II
+//! has SL90025 instead of SL31253.
+//!
+//! The dumps are copyrighted, `freebios/` has a free replacement for both ROMs which boots
+//! cartridges and offers the routines homebrew uses.
+
+use crate::crc::crc32;
+
+/// Bytes of each ROM
+pub const ROM_LEN: usize = 0x400;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Location {
+    Rom0000,
+    Rom0400,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rom {
+    pub name: &'static str,
+    pub crc: u32,
+    pub location: Location,
+}
+
+pub const KNOWN: [Rom; 3] = [
+    Rom {
+        name: "SL31253",
+        crc: 0x0469_4ed9,
+        location: Location::Rom0000,
+    },
+    Rom {
+        name: "SL90025",
+        crc: 0x015c_1e38,
+        location: Location::Rom0000,
+    },
+    Rom {
+        name: "SL31254",
+        crc: 0x9c04_7ba3,
+        location: Location::Rom0400,
+    },
+];
+
+/// The free BIOS, assembled from `freebios/freebios.asm`
+pub const FREE_BIOS: &[u8; 2 * ROM_LEN] = include_bytes!("../../freebios/freebios.bin");
+
+/// The free BIOS split into the ROMs at 0x0000 and 0x0400
+pub fn free_bios() -> (&'static [u8], &'static [u8]) {
+    FREE_BIOS.split_at(ROM_LEN)
+}
+
+/// The known ROM `data` is, `None` for anything else
+pub fn identify(data: &[u8]) -> Option<Rom> {
+    if data.len() != ROM_LEN {
+        return None;
+    }
+
+    let crc = crc32(data);
+    KNOWN.iter().find(|rom| rom.crc == crc).copied()
+}
+
+/// The original BIOS from the files `SL31253.bin` and `SL31254.bin` in `dir`, the free BIOS if
+/// they aren't there
+#[cfg(feature = "std")]
+pub fn load_or_free(dir: &str) -> (std::vec::Vec<u8>, std::vec::Vec<u8>) {
+    let rom = |name| {
+        std::fs::read(std::format!("{}/{}", dir, name))
+            .ok()
+            .filter(|data| identify(data).is_some())
+    };
+    match (rom("SL31253.bin"), rom("SL31254.bin")) {
+        (Some(rom_0000), Some(rom_0400)) => (rom_0000, rom_0400),
+        _ => {
+            std::println!("No BIOS in {}, using the free BIOS", dir);
+            let (rom_0000, rom_0400) = free_bios();
+            (rom_0000.to_vec(), rom_0400.to_vec())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::dump;
+
+    #[test]
+    fn unknown() {
+        assert_eq!(None, identify(&[0u8; ROM_LEN]));
+        assert_eq!(None, identify(&[]));
+        assert_eq!(None, identify(&[0xffu8; ROM_LEN + 1]));
+    }
+
+    #[test]
+    fn roms() {
+        // the ROMs aren't part of the repository, there may be placeholders
+        if let Some(rom) = dump("SL31253.bin").and_then(|data| identify(&data)) {
+            assert_eq!(Location::Rom0000, rom.location);
+        }
+        if let Some(rom) = dump("SL31254.bin").and_then(|data| identify(&data)) {
+            assert_eq!(Location::Rom0400, rom.location);
+        }
+
+        let (rom_0000, rom_0400) = free_bios();
+        assert_eq!(None, identify(rom_0000));
+        assert_eq!(None, identify(rom_0400));
+
+        for (idx, rom) in KNOWN.iter().enumerate() {
+            assert!(KNOWN[idx + 1..].iter().all(|other| other.crc != rom.crc));
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Cheats - memory locations frozen to a value at the end of every frame
+//!
+//! Cheat lists are plain text, one cheat per line
+//!
+//! ```text
+//! # kind address value
+//! S 1a 05     scratchpad register 0x1a
+//! M 2834 09   byte of the cartridge RAM at 0x2800 - 0x2fff
+//! B 03f 1     bit of the 2102 SRAM, 0x000 - 0x3ff
+//! ```
+//!
+//! Numbers are hex, everything after a `#` is ignored.
+
+use core::fmt;
+
+pub const MAX_CHEATS: usize = 32;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CheatTarget {
+    Scratchpad(u8),
+    Ram(u16),
+    Sram(u16),
+}
+
+impl CheatTarget {
+    /// Number of locations which can be addressed by a cheat
+    pub const COUNT: usize = 64 + 0x800 + 0x400;
+
+    /// All locations numbered consecutively: scratchpad, RAM, SRAM - useful for searching
+    pub fn from_index(idx: usize) -> Option<CheatTarget> {
+        match idx {
+            0..=63 => Some(CheatTarget::Scratchpad(idx as u8)),
+            64..=0x83f => Some(CheatTarget::Ram(0x2800 + (idx - 64) as u16)),
+            0x840..=0xc3f => Some(CheatTarget::Sram((idx - 0x840) as u16)),
+            _ => None,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            CheatTarget::Scratchpad(r) => r < 64,
+            CheatTarget::Ram(addr) => (0x2800..=0x2fff).contains(&addr),
+            CheatTarget::Sram(bit) => bit < 0x400,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Cheat {
+    pub target: CheatTarget,
+    pub value: u8,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CheatParseError {
+    UnknownKind,
+    BadNumber,
+    OutOfRange,
+    MissingValue,
+}
+
+impl Cheat {
+    /// Parses a line of a cheat list, returns `None` for empty lines and comments
+    pub fn parse(line: &str) -> Result<Option<Cheat>, CheatParseError> {
+        let line = match line.find('#') {
+            Some(idx) => &line[..idx],
+            None => line,
+        };
+
+        let mut parts = line.split_whitespace();
+        let kind = match parts.next() {
+            Some(kind) => kind,
+            None => return Ok(None),
+        };
+        let address = parse_hex(parts.next().ok_or(CheatParseError::MissingValue)?)?;
+        let value = parse_hex(parts.next().ok_or(CheatParseError::MissingValue)?)?;
+
+        let target = match kind {
+            "S" | "s" if address < 64 => CheatTarget::Scratchpad(address as u8),
+            "M" | "m" => CheatTarget::Ram(address),
+            "B" | "b" => CheatTarget::Sram(address),
+            "S" | "s" => return Err(CheatParseError::OutOfRange),
+            _ => return Err(CheatParseError::UnknownKind),
+        };
+
+        let max_value = match target {
+            CheatTarget::Sram(_) => 1,
+            _ => 0xff,
+        };
+
+        if !target.is_valid() || value > max_value {
+            return Err(CheatParseError::OutOfRange);
+        }
+
+        Ok(Some(Cheat {
+            target,
+            value: value as u8,
+        }))
+    }
+}
+
+fn parse_hex(s: &str) -> Result<u16, CheatParseError> {
+    let s = s.trim_start_matches("0x");
+    u16::from_str_radix(s, 16).map_err(|_| CheatParseError::BadNumber)
+}
+
+impl fmt::Display for CheatTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CheatTarget::Scratchpad(r) => write!(f, "S {:02x}", r),
+            CheatTarget::Ram(addr) => write!(f, "M {:04x}", addr),
+            CheatTarget::Sram(bit) => write!(f, "B {:03x}", bit),
+        }
+    }
+}
+
+impl fmt::Display for Cheat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:02x}", self.target, self.value)
+    }
+}
+
+/// Fixed size list of active cheats
+#[derive(Debug, Clone, Copy)]
+pub struct Cheats {
+    cheats: [Option<Cheat>; MAX_CHEATS],
+}
+
+impl Cheats {
+    pub fn new() -> Cheats {
+        Cheats {
+            cheats: [None; MAX_CHEATS],
+        }
+    }
+
+    /// Adds a cheat, replacing one for the same target. Returns false if the list is full.
+    pub fn add(&mut self, cheat: Cheat) -> bool {
+        self.remove(cheat.target);
+
+        match self.cheats.iter_mut().find(|c| c.is_none()) {
+            Some(slot) => {
+                *slot = Some(cheat);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, target: CheatTarget) {
+        for slot in self.cheats.iter_mut() {
+            if let Some(cheat) = slot {
+                if cheat.target == target {
+                    *slot = None;
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cheats = [None; MAX_CHEATS];
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cheat> {
+        self.cheats.iter().filter_map(|c| c.as_ref())
+    }
+
+    /// Adds every cheat of a cheat list, returns the first error with its line number
+    pub fn load(&mut self, list: &str) -> Result<(), (usize, CheatParseError)> {
+        for (idx, line) in list.lines().enumerate() {
+            if let Some(cheat) = Cheat::parse(line).map_err(|e| (idx + 1, e))? {
+                self.add(cheat);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Cheats {
+    fn default() -> Self {
+        Cheats::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::test_util::NullChannelF;
+    use crate::Cpu;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(None), Cheat::parse("   # just a comment"));
+        assert_eq!(
+            Ok(Some(Cheat {
+                target: CheatTarget::Scratchpad(0x1a),
+                value: 5
+            })),
+            Cheat::parse("S 1a 05 # lives")
+        );
+        assert_eq!(
+            Ok(Some(Cheat {
+                target: CheatTarget::Ram(0x2834),
+                value: 0x99
+            })),
+            Cheat::parse("m 0x2834 99")
+        );
+        assert_eq!(
+            Ok(Some(Cheat {
+                target: CheatTarget::Sram(0x3ff),
+                value: 1
+            })),
+            Cheat::parse("B 3ff 1")
+        );
+
+        assert_eq!(Err(CheatParseError::UnknownKind), Cheat::parse("X 10 10"));
+        assert_eq!(Err(CheatParseError::BadNumber), Cheat::parse("S 1g 10"));
+        assert_eq!(Err(CheatParseError::MissingValue), Cheat::parse("S 10"));
+        assert_eq!(Err(CheatParseError::OutOfRange), Cheat::parse("S 40 10"));
+        assert_eq!(Err(CheatParseError::OutOfRange), Cheat::parse("S 100 10"));
+        assert_eq!(Err(CheatParseError::OutOfRange), Cheat::parse("M 3000 10"));
+        assert_eq!(Err(CheatParseError::OutOfRange), Cheat::parse("B 400 1"));
+        assert_eq!(Err(CheatParseError::OutOfRange), Cheat::parse("B 10 2"));
+    }
+
+    #[test]
+    fn display_parses_back() {
+        for idx in [0, 63, 64, 0x83f, 0x840, 0xc3f].iter() {
+            let cheat = Cheat {
+                target: CheatTarget::from_index(*idx).unwrap(),
+                value: 1,
+            };
+            assert_eq!(Ok(Some(cheat)), Cheat::parse(&std::format!("{}", cheat)));
+        }
+        assert_eq!(None, CheatTarget::from_index(CheatTarget::COUNT));
+    }
+
+    #[test]
+    fn list() {
+        let mut cheats = Cheats::new();
+        assert_eq!(
+            Err((2, CheatParseError::UnknownKind)),
+            cheats.load("S 01 02\nZ 1 1")
+        );
+
+        cheats.clear();
+        cheats.load("S 01 02\n\nM 2800 03\nS 01 04").unwrap();
+        let list: Vec<Cheat> = cheats.iter().cloned().collect();
+        assert_eq!(2, list.len());
+        assert!(list.contains(&Cheat {
+            target: CheatTarget::Scratchpad(1),
+            value: 4
+        }));
+
+        for i in 0..MAX_CHEATS {
+            cheats.add(Cheat {
+                target: CheatTarget::Ram(0x2900 + i as u16),
+                value: 0,
+            });
+        }
+        assert!(!cheats.add(Cheat {
+            target: CheatTarget::Sram(0),
+            value: 0,
+        }));
+    }
+
+    #[test]
+    fn frozen_every_frame() {
+        // 0000 LR A,0 / INC / LR 0,A / BR 0000
+        let program = [0x40, 0x1f, 0x50, 0x90, 0xfc];
+        let channel_f = NullChannelF;
+        let mut cpu = Cpu::new(&program, &[], &[], &channel_f);
+        cpu.cheats.load("S 00 42\nM 2801 17\nB 010 0").unwrap();
+
+        cpu.run_frame();
+        assert_eq!(0x42, cpu.scratchpad[0]);
+        assert_eq!(0x17, cpu.ram()[1]);
+        assert_eq!(0, cpu.xmemory[0x10]);
+
+        cpu.cheats.clear();
+        cpu.run_frame();
+        assert_ne!(0x42, cpu.scratchpad[0]);
+    }
+}
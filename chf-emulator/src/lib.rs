@@ -1,7 +1,38 @@
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::{usize};
 
+pub mod audio;
+pub mod bios;
+pub mod cheat;
+pub mod crc;
+pub mod movie;
+pub mod save;
+pub mod state;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
+use cheat::{CheatTarget, Cheats};
+use state::{StateError, StateReader, StateWriter};
+
+/// Number of CPU cycles in one frame (1.7897725 MHz at 60 Hz)
+pub const CYCLES_PER_FRAME: u32 = 1_789_772 / 60;
+
+/// The palette of a row from the values of its columns 125 and 126, the last three columns are
+/// special:
+///
+/// 127 - unknown
+/// 126 - bit 1 = palette bit 1
+/// 125 - bit 1 = palette bit 0 (or with 126 bit 0)
+///
+/// The color of a pixel is the entry `palette * 4 + value` of the color table.
+pub fn palette(column_125: u8, column_126: u8) -> usize {
+    ((((column_125 & 2) >> 1) | column_126) & 0b11) as usize
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Sound {
     Silence,
@@ -10,6 +41,7 @@ pub enum Sound {
     Frequency120Hz,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Key {
     Reset,
 
@@ -37,12 +69,70 @@ pub enum Key {
     Push1,
 }
 
+impl Key {
+    pub const ALL: [Key; 21] = [
+        Key::Reset,
+        Key::Time,
+        Key::Mode,
+        Key::Hold,
+        Key::Start,
+        Key::Right0,
+        Key::Left0,
+        Key::Back0,
+        Key::Forward0,
+        Key::CounterClockwise0,
+        Key::Clockwise0,
+        Key::Pull0,
+        Key::Push0,
+        Key::Right1,
+        Key::Left1,
+        Key::Back1,
+        Key::Forward1,
+        Key::CounterClockwise1,
+        Key::Clockwise1,
+        Key::Pull1,
+        Key::Push1,
+    ];
+
+    fn mask(self) -> u32 {
+        1 << (self as u32)
+    }
+}
+
+/// State of the console buttons and both hand controllers - one bit per `Key`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Input(pub u32);
+
+impl Input {
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.0 & key.mask() != 0
+    }
+
+    pub fn set(&mut self, key: Key, pressed: bool) {
+        if pressed {
+            self.0 |= key.mask();
+        } else {
+            self.0 &= !key.mask();
+        }
+    }
+}
+
 pub trait ChannelF {
     fn sound(&self, frequency: Sound);
 
     fn set_pixel(&self, x: u8, y: u8, value: u8);
 
     fn key_pressed(&self, key: Key) -> bool;
+
+    /// Called once per frame by `Cpu::step` - the emulated program only ever sees
+    /// the state returned here which makes runs reproducible (see `movie`)
+    fn input(&self) -> Input {
+        let mut input = Input::default();
+        for key in Key::ALL.iter() {
+            input.set(*key, self.key_pressed(*key));
+        }
+        input
+    }
 }
 
 pub struct Cpu<'a> {
@@ -74,6 +164,14 @@ pub struct Cpu<'a> {
     x: u8,
     y: u8,
     color: u8,
+    vram: [u8; 128 * 64 / 4], // 2 bits per pixel
+    dirty_rows: u64,
+
+    input: Input,
+    frame_cycles: u32,
+    pub frame: u32,
+
+    pub cheats: Cheats,
 }
 
 impl<'a> Cpu<'a> {
@@ -108,12 +206,222 @@ impl<'a> Cpu<'a> {
             x: 0,
             y: 0,
             color: 0,
+            vram: [0u8; 128 * 64 / 4],
+            dirty_rows: 0,
+
+            input: Input::default(),
+            frame_cycles: 0,
+            frame: 0,
+
+            cheats: Cheats::new(),
         }
     }
 
+    /// Warm reset like the RESET button of the console, the program starts over at 0
+    ///
+    /// As the reset line of the 3850 does, PC1 gets the old PC0 and interrupts are disabled.
+    /// Everything else including the RAM, scratchpad and SRAM keeps its contents.
     pub fn reset(&mut self) {
+        self.pc1 = self.pc0;
         self.pc0 = 0;
-        self.pc1 = 0;
+        self.icb_flag = 0;
+    }
+
+    /// Like switching the console off and on, everything but the cheats is as after `new`
+    ///
+    /// The sound stops and the cleared screen is sent to `ChannelF::set_pixel`.
+    pub fn power_on(&mut self) {
+        let cheats = core::mem::take(&mut self.cheats);
+        *self = Cpu::new(
+            self.memory_0000,
+            self.memory_0400,
+            self.memory_0800,
+            self.channel_f,
+        );
+        self.cheats = cheats;
+
+        self.dirty_rows = u64::MAX;
+        self.channel_f.sound(Sound::Silence);
+        self.repaint();
+    }
+
+    /// Executes one instruction.
+    ///
+    /// Returns true if this instruction completed a frame. The inputs for the next frame
+    /// are sampled from the `ChannelF` right then. The first frame sees no keys pressed.
+    /// Pressing `Key::Reset` resets the CPU once, see `reset`.
+    pub fn step(&mut self) -> bool {
+        self.cycles = 0;
+        let opcode = self.fetch();
+        self.execute(opcode);
+
+        self.frame_cycles += self.cycles as u32;
+        if self.frame_cycles >= CYCLES_PER_FRAME {
+            self.frame_cycles -= CYCLES_PER_FRAME;
+            self.frame = self.frame.wrapping_add(1);
+            self.apply_cheats();
+
+            let input = self.channel_f.input();
+            let reset = input.is_pressed(Key::Reset) && !self.input.is_pressed(Key::Reset);
+            self.input = input;
+            if reset {
+                self.reset();
+            }
+            return true;
+        }
+
+        false
+    }
+
+    /// Executes instructions until the current frame is completed
+    pub fn run_frame(&mut self) {
+        while !self.step() {}
+    }
+
+    /// Reads memory without side effects, e.g. for debugging
+    pub fn peek(&self, addr: u16) -> u8 {
+        self.read(addr)
+    }
+
+    /// The RAM at 0x2800 - 0x2fff
+    pub fn ram(&self) -> &[u8; 0x800] {
+        &self.ram_2800
+    }
+
+    pub fn ram_mut(&mut self) -> &mut [u8; 0x800] {
+        &mut self.ram_2800
+    }
+
+    /// Current value of a location cheats can freeze
+    pub fn cheat_target_value(&self, target: CheatTarget) -> u8 {
+        match target {
+            CheatTarget::Scratchpad(r) => self.scratchpad[r as usize],
+            CheatTarget::Ram(addr) => self.ram_2800[addr as usize - 0x2800],
+            CheatTarget::Sram(bit) => self.xmemory[bit as usize],
+        }
+    }
+
+    fn apply_cheats(&mut self) {
+        for cheat in self.cheats.iter() {
+            match cheat.target {
+                CheatTarget::Scratchpad(r) => self.scratchpad[r as usize] = cheat.value,
+                CheatTarget::Ram(addr) => self.ram_2800[addr as usize - 0x2800] = cheat.value,
+                CheatTarget::Sram(bit) => self.xmemory[bit as usize] = cheat.value,
+            }
+        }
+    }
+
+    /// Color index of a pixel as last written by the program
+    pub fn pixel(&self, x: u8, y: u8) -> u8 {
+        let idx = x as usize + y as usize * 128;
+        (self.vram[idx / 4] >> ((idx % 4) * 2)) & 0b11
+    }
+
+    fn store_pixel(&mut self, x: u8, y: u8, color: u8) {
+        let idx = x as usize + y as usize * 128;
+        let shift = (idx % 4) * 2;
+        self.vram[idx / 4] = (self.vram[idx / 4] & !(0b11 << shift)) | ((color & 0b11) << shift);
+        self.dirty_rows |= 1 << (y & 63);
+    }
+
+    /// The packed VRAM: 32 bytes per row, four pixels per byte, the first pixel in bits 0 - 1
+    pub fn vram(&self) -> &[u8; 128 * 64 / 4] {
+        &self.vram
+    }
+
+    /// Rows written since the last call, bit n is set if row n was written
+    pub fn take_dirty_rows(&mut self) -> u64 {
+        core::mem::replace(&mut self.dirty_rows, 0)
+    }
+
+    /// Sends every pixel to `ChannelF::set_pixel` again, e.g. after `load_state`
+    pub fn repaint(&self) {
+        for y in 0..64 {
+            for x in 0..128 {
+                self.channel_f.set_pixel(x, y, self.pixel(x, y));
+            }
+        }
+    }
+
+    /// Writes the machine state, see `state` for the format
+    pub fn save_state<W: StateWriter>(&self, writer: &mut W) -> Result<(), W::Error> {
+        writer.write(&state::MAGIC)?;
+        writer.write(&[state::VERSION])?;
+        writer.write(&[self.a, self.isar, self.flags, self.icb_flag])?;
+        for v in [self.pc0, self.pc1, self.dc0, self.dc1, self.xregister].iter() {
+            writer.write(&v.to_le_bytes())?;
+        }
+        writer.write(&[self.x, self.y, self.color])?;
+        for v in [self.input.0, self.frame_cycles, self.frame].iter() {
+            writer.write(&v.to_le_bytes())?;
+        }
+        writer.write(&self.scratchpad)?;
+        writer.write(&self.io_latch)?;
+        writer.write(&self.ram_2800)?;
+
+        for bits in self.xmemory.chunks(8) {
+            let mut packed = 0u8;
+            for (i, bit) in bits.iter().enumerate() {
+                packed |= (bit & 1) << i;
+            }
+            writer.write(&[packed])?;
+        }
+
+        writer.write(&self.vram)
+    }
+
+    /// Restores a state written by `save_state`
+    ///
+    /// The screen is not updated, call `repaint` for that.
+    /// A state of the wrong length isn't loaded at all, on a read error it's left partially loaded.
+    pub fn load_state<R: StateReader>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<(), StateError<R::Error>> {
+        if reader.remaining() != state::STATE_LEN {
+            return Err(StateError::Truncated);
+        }
+
+        let mut magic = [0u8; 4];
+        reader.read(&mut magic)?;
+        if magic != state::MAGIC {
+            return Err(StateError::BadMagic);
+        }
+
+        let version = state::read_u8(reader)?;
+        if version != state::VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+
+        self.a = state::read_u8(reader)?;
+        self.isar = state::read_u8(reader)?;
+        self.flags = state::read_u8(reader)?;
+        self.icb_flag = state::read_u8(reader)?;
+        self.pc0 = state::read_u16(reader)?;
+        self.pc1 = state::read_u16(reader)?;
+        self.dc0 = state::read_u16(reader)?;
+        self.dc1 = state::read_u16(reader)?;
+        self.xregister = state::read_u16(reader)?;
+        self.x = state::read_u8(reader)?;
+        self.y = state::read_u8(reader)?;
+        self.color = state::read_u8(reader)?;
+        self.input = Input(state::read_u32(reader)?);
+        self.frame_cycles = state::read_u32(reader)?;
+        self.frame = state::read_u32(reader)?;
+        reader.read(&mut self.scratchpad)?;
+        reader.read(&mut self.io_latch)?;
+        reader.read(&mut self.ram_2800)?;
+
+        for bits in self.xmemory.chunks_mut(8) {
+            let packed = state::read_u8(reader)?;
+            for (i, bit) in bits.iter_mut().enumerate() {
+                *bit = (packed >> i) & 1;
+            }
+        }
+
+        reader.read(&mut self.vram)?;
+        self.dirty_rows = u64::MAX;
+        Ok(())
     }
 
     fn result_0czs0o(&mut self, v: u8) -> u8 {
@@ -266,19 +574,19 @@ impl<'a> Cpu<'a> {
         if port == 0 {
             let mut res = 0xf;
 
-            if self.channel_f.key_pressed(Key::Start) {
+            if self.input.is_pressed(Key::Start) {
                 res ^= 0x1;
             }
 
-            if self.channel_f.key_pressed(Key::Hold) {
+            if self.input.is_pressed(Key::Hold) {
                 res ^= 0x2;
             }
 
-            if self.channel_f.key_pressed(Key::Mode) {
+            if self.input.is_pressed(Key::Mode) {
                 res ^= 0x4;
             }
 
-            if self.channel_f.key_pressed(Key::Time) {
+            if self.input.is_pressed(Key::Time) {
                 res ^= 0x8;
             }
 
@@ -288,35 +596,35 @@ impl<'a> Cpu<'a> {
         if port == 1 {
             let mut input = 0;
 
-            if self.channel_f.key_pressed(Key::Right0) {
+            if self.input.is_pressed(Key::Right0) {
                 input += 1;
             }
 
-            if self.channel_f.key_pressed(Key::Left0) {
+            if self.input.is_pressed(Key::Left0) {
                 input += 2;
             }
 
-            if self.channel_f.key_pressed(Key::Back0) {
+            if self.input.is_pressed(Key::Back0) {
                 input += 4;
             }
 
-            if self.channel_f.key_pressed(Key::Forward0) {
+            if self.input.is_pressed(Key::Forward0) {
                 input += 8;
             }
 
-            if self.channel_f.key_pressed(Key::CounterClockwise0) {
+            if self.input.is_pressed(Key::CounterClockwise0) {
                 input += 16;
             }
 
-            if self.channel_f.key_pressed(Key::Clockwise0) {
+            if self.input.is_pressed(Key::Clockwise0) {
                 input += 32;
             }
 
-            if self.channel_f.key_pressed(Key::Pull0) {
+            if self.input.is_pressed(Key::Pull0) {
                 input += 64;
             }
 
-            if self.channel_f.key_pressed(Key::Push0) {
+            if self.input.is_pressed(Key::Push0) {
                 input += 128;
             }
 
@@ -328,35 +636,35 @@ impl<'a> Cpu<'a> {
         if port == 4 {
             let mut input = 0;
 
-            if self.channel_f.key_pressed(Key::Right1) {
+            if self.input.is_pressed(Key::Right1) {
                 input += 1;
             }
 
-            if self.channel_f.key_pressed(Key::Left1) {
+            if self.input.is_pressed(Key::Left1) {
                 input += 2;
             }
 
-            if self.channel_f.key_pressed(Key::Back1) {
+            if self.input.is_pressed(Key::Back1) {
                 input += 4;
             }
 
-            if self.channel_f.key_pressed(Key::Forward1) {
+            if self.input.is_pressed(Key::Forward1) {
                 input += 8;
             }
 
-            if self.channel_f.key_pressed(Key::CounterClockwise1) {
+            if self.input.is_pressed(Key::CounterClockwise1) {
                 input += 16;
             }
 
-            if self.channel_f.key_pressed(Key::Clockwise1) {
+            if self.input.is_pressed(Key::Clockwise1) {
                 input += 32;
             }
 
-            if self.channel_f.key_pressed(Key::Pull1) {
+            if self.input.is_pressed(Key::Pull1) {
                 input += 64;
             }
 
-            if self.channel_f.key_pressed(Key::Push1) {
+            if self.input.is_pressed(Key::Push1) {
                 input += 128;
             }
 
@@ -398,6 +706,7 @@ impl<'a> Cpu<'a> {
             }
             0 => {
                 if v & 0x20 == 0 && old & 0x20 != 0 {
+                    self.store_pixel(self.x, self.y, self.color);
                     self.channel_f.set_pixel(self.x, self.y, self.color);
                 }
             }
@@ -930,15 +1239,29 @@ fn signed_byte(v: u8) -> i8 {
 #[cfg(test)]
 mod tests {
     extern crate std;
-    use core::cell::RefCell;
+    use core::{cell::RefCell, ops::Range};
     use std::prelude::v1::*;
 
     use super::*;
 
-    const ROM_0000: &'static [u8] = include_bytes!("../roms/SL31253.bin");
-    const ROM_0400: &'static [u8] = include_bytes!("../roms/SL31254.bin");
-    const CARTRIDGE: &'static [u8] = include_bytes!("../roms/demo.bin");
     const CARTRIDGE_TEST: &'static [u8] = include_bytes!("../testfiles/test.bin");
+    const CARTRIDGE_TEST_SOURCE: &str = include_str!("../testfiles/test.asm");
+    const FREE_BIOS_SOURCE: &str = include_str!("../freebios/freebios.asm");
+
+    /// A file from `roms/`, the dumps aren't part of the repository
+    pub(crate) fn dump(name: &str) -> Option<Vec<u8>> {
+        std::fs::read(std::format!("{}/roms/{}", env!("CARGO_MANIFEST_DIR"), name)).ok()
+    }
+
+    /// The original BIOS, `roms/` mustn't have placeholders
+    fn original_bios() -> (Vec<u8>, Vec<u8>) {
+        let rom = |name| {
+            dump(name)
+                .filter(|rom| bios::identify(rom).is_some())
+                .expect("needs the original BIOS in roms/")
+        };
+        (rom("SL31253.bin"), rom("SL31254.bin"))
+    }
 
     struct DummyChannelF {
         pixels: RefCell<[u8; 128 * 64]>,
@@ -1016,13 +1339,136 @@ mod tests {
     }
 
     #[test]
+    fn dirty_rows() {
+        let dummy_channel_f = DummyChannelF {
+            pixels: RefCell::new([0u8; 128 * 64]),
+            key_pressed: RefCell::new(false),
+        };
+        let mut cpu = Cpu::new(&[], &[], &[], &dummy_channel_f);
+
+        cpu.store_pixel(5, 0, 1);
+        cpu.store_pixel(127, 63, 3);
+        assert_eq!(1 | 1 << 63, cpu.take_dirty_rows());
+        assert_eq!(0, cpu.take_dirty_rows());
+
+        assert_eq!(0b01 << 2, cpu.vram()[1]);
+        assert_eq!(0b11 << 6, cpu.vram()[63 * 32 + 31]);
+    }
+
+    struct ResetChannelF {
+        reset: RefCell<bool>,
+    }
+
+    impl ChannelF for ResetChannelF {
+        fn sound(&self, _frequency: Sound) {}
+
+        fn set_pixel(&self, _x: u8, _y: u8, _value: u8) {}
+
+        fn key_pressed(&self, key: Key) -> bool {
+            key == Key::Reset && *self.reset.borrow()
+        }
+    }
+
+    #[test]
+    fn reset_key() {
+        // NOPs and a jump back to 0
+        let mut rom = [0x2bu8; 0x400];
+        rom[0x3fd..].copy_from_slice(&[0x29, 0x00, 0x00]);
+        let channel_f = ResetChannelF {
+            reset: RefCell::new(false),
+        };
+        let mut cpu = Cpu::new(&rom, &[], &[], &channel_f);
+
+        cpu.run_frame();
+        assert_ne!(0, cpu.pc0);
+
+        cpu.scratchpad[5] = 0x55;
+        cpu.ram_mut()[0] = 0xaa;
+        cpu.xmemory[3] = 0;
+        cpu.icb_flag = 0x10;
+        *channel_f.reset.borrow_mut() = true;
+
+        // the reset happens when the input is sampled at the end of the frame
+        cpu.run_frame();
+        assert_eq!(0, cpu.pc0);
+        assert_ne!(0, cpu.pc1);
+        assert_eq!(0, cpu.icb_flag);
+        assert_eq!(0x55, cpu.scratchpad[5]);
+        assert_eq!(0xaa, cpu.ram()[0]);
+        assert_eq!(0, cpu.xmemory[3]);
+
+        // holding the button doesn't reset again
+        cpu.run_frame();
+        assert_ne!(0, cpu.pc0);
+
+        *channel_f.reset.borrow_mut() = false;
+        cpu.run_frame();
+        *channel_f.reset.borrow_mut() = true;
+        cpu.run_frame();
+        assert_eq!(0, cpu.pc0);
+    }
+
+    #[test]
+    fn palettes() {
+        // rows are column 125, columns are column 126
+        let expected = [
+            [0, 1, 2, 3],
+            [0, 1, 2, 3],
+            [1, 1, 3, 3],
+            [1, 1, 3, 3],
+        ];
+        for column_125 in 0..4 {
+            for column_126 in 0..4 {
+                assert_eq!(expected[column_125 as usize][column_126 as usize], palette(column_125, column_126));
+            }
+        }
+    }
+
+    #[test]
+    fn power_on() {
+        let dummy_channel_f = DummyChannelF {
+            pixels: RefCell::new([0u8; 128 * 64]),
+            key_pressed: RefCell::new(false),
+        };
+        let mut cpu = Cpu::new(&[], &[], &[], &dummy_channel_f);
+
+        cpu.a = 1;
+        cpu.pc0 = 0x123;
+        cpu.scratchpad[5] = 0x55;
+        cpu.ram_mut()[0] = 0xaa;
+        cpu.xmemory[3] = 0;
+        cpu.outport(1, 0x40);
+        cpu.outport(4, 0x7f - 10);
+        cpu.outport(5, 0x3f - 20);
+        cpu.outport(0, 0x20);
+        cpu.outport(0, 0);
+        assert_eq!(2, dummy_channel_f.pixels.borrow()[10 + 20 * 128]);
+        cpu.take_dirty_rows();
+        cpu.cheats.load("S 05 12").unwrap();
+
+        cpu.power_on();
+
+        assert_eq!((0, 0, 0), (cpu.a, cpu.pc0, cpu.scratchpad[5]));
+        assert_eq!(0, cpu.ram()[0]);
+        assert_eq!(1, cpu.xmemory[3]);
+        assert_eq!(0, cpu.io_latch[1]);
+        assert_eq!(0, cpu.pixel(10, 20));
+        assert_eq!(0, dummy_channel_f.pixels.borrow()[10 + 20 * 128]);
+        assert_eq!(u64::MAX, cpu.take_dirty_rows());
+        assert_eq!(1, cpu.cheats.iter().count());
+    }
+
+    #[test]
+    #[ignore = "needs the original BIOS and demo.bin in roms/"]
     fn startup() {
+        let (rom_0000, rom_0400) = original_bios();
+        let catridge = dump("demo.bin").expect("needs demo.bin in roms/");
+
         let dummy_channel_f = DummyChannelF {
             pixels: RefCell::new([0u8; 128 * 64]),
             key_pressed: RefCell::new(false),
         };
-        let catridge = CARTRIDGE;
-        let mut cpu = Cpu::new(ROM_0000, ROM_0400, catridge, &dummy_channel_f);
+        let mut cpu = Cpu::new(&rom_0000, &rom_0400, &catridge, &dummy_channel_f);
         cpu.reset();
 
         for _ in 0..55591320 {
@@ -1119,12 +1565,15 @@ mod tests {
     }
 
     #[test]
+    #[ignore = "needs the original BIOS in roms/"]
     fn startup_no_cartridge() {
+        let (rom_0000, rom_0400) = original_bios();
+
         let dummy_channel_f = DummyChannelF {
             pixels: RefCell::new([0u8; 128 * 64]),
             key_pressed: RefCell::new(false),
         };
-        let mut cpu = Cpu::new(ROM_0000, ROM_0400, &[], &dummy_channel_f);
+        let mut cpu = Cpu::new(&rom_0000, &rom_0400, &[], &dummy_channel_f);
         cpu.reset();
 
         for _ in 0..55591320 {
@@ -1136,8 +1585,168 @@ mod tests {
         assert_eq!(195, cpu.pc0);
     }
 
+    fn free_bios_cpu<'a>(cartridge: &'a [u8], channel_f: &'a DummyChannelF) -> Cpu<'a> {
+        let (rom_0000, rom_0400) = bios::free_bios();
+        let mut cpu = Cpu::new(rom_0000, rom_0400, cartridge, channel_f);
+        cpu.reset();
+        // clearing the screen takes a quarter of a second
+        for _ in 0..60 {
+            cpu.run_frame();
+        }
+        cpu
+    }
+
+    // the pixels of a part of the screen as text
+    fn screen(channel_f: &DummyChannelF, xs: Range<usize>, ys: Range<usize>) -> String {
+        let mut screen = String::new();
+        for y in ys {
+            screen.push('[');
+            for x in xs.clone() {
+                screen.push(match channel_f.pixels.borrow()[y * 128 + x] {
+                    3 => '█',
+                    2 => 'X',
+                    1 => '|',
+                    _ => ' ',
+                });
+            }
+            screen.push(']');
+        }
+        screen
+    }
+
+    #[test]
+    fn free_bios_no_cartridge() {
+        let dummy_channel_f = DummyChannelF {
+            pixels: RefCell::new([3u8; 128 * 64]),
+            key_pressed: RefCell::new(false),
+        };
+        let cpu = free_bios_cpu(&[], &dummy_channel_f);
+
+        // waiting in the idle loop
+        let bios = chf_asm::assemble(FREE_BIOS_SOURCE).unwrap();
+        assert_eq!(bios.symbol("idle"), Some(cpu.pc0 as i32));
+        assert_eq!(
+"[                                                                          ]\
+[ |   | |||||       ||||| ||||| ||||| ||||| ||||| ||||| ||||  ||||| |||||  ]\
+[ ||  | |   |       |     |   | |   |   |   |   |   |   |   | |     |      ]\
+[ | | | |   |       |     ||||| |||||   |   |||||   |   |   | | ||| |||    ]\
+[ |  || |   |       |     |   | |  |    |   |  |    |   |   | |   | |      ]\
+[ |   | |||||       ||||| |   | |   |   |   |   | ||||| ||||  ||||| |||||  ]\
+[                                                                          ]",
+            screen(&dummy_channel_f, 9..83, 28..35)
+        );
+
+        // grey background, the palette is chosen by column 126
+        for y in 0..64 {
+            assert_eq!(0, dummy_channel_f.pixels.borrow()[y * 128]);
+            assert_eq!(0, dummy_channel_f.pixels.borrow()[y * 128 + 125]);
+            assert_eq!(2, dummy_channel_f.pixels.borrow()[y * 128 + 126]);
+        }
+    }
+
+    #[test]
+    fn free_bios_cartridge() {
+        let cartridge = chf_asm::assemble(
+            "
+        CARTRIDGE
+        LI      H'93'           ; blue
+        LR      R3,A
+        PI      H'00D0'         ; clrscrn
+        LI      H'40'+7         ; a red 7
+        LR      R0,A
+        LI      20
+        LR      R1,A
+        LI      30
+        LR      R2,A
+        PI      H'0679'         ; drawchar
+done    BR      done
+",
+        )
+        .unwrap();
+        let dummy_channel_f = DummyChannelF {
+            pixels: RefCell::new([0u8; 128 * 64]),
+            key_pressed: RefCell::new(false),
+        };
+        let cpu = free_bios_cpu(cartridge.bytes(), &dummy_channel_f);
+
+        assert_eq!(cartridge.symbol("done"), Some(cpu.pc0 as i32));
+        assert_eq!(26, cpu.scratchpad[1]);
+        assert_eq!(
+"[||||||||]\
+[||XXXXX|]\
+[||    X|]\
+[||   X |]\
+[||  X  |]\
+[||  X  |]\
+[||||||||]",
+            screen(&dummy_channel_f, 18..26, 29..36)
+        );
+        for y in 0..64 {
+            assert_eq!(0, dummy_channel_f.pixels.borrow()[y * 128 + 125]);
+            assert_eq!(1, dummy_channel_f.pixels.borrow()[y * 128 + 126]);
+        }
+    }
+
     #[test]
+    fn free_bios_routines() {
+        let cartridge = chf_asm::assemble(
+            "
+delay   EQU     H'008F'
+pushk   EQU     H'0107'
+popk    EQU     H'011E'
+joysticks EQU   H'0121'
+
+        CARTRIDGE
+loop    LIS     1
+        LR      R5,A
+        PI      sub
+back    LR      A,R0
+        NS      R0
+        BZ      loop            ; until the input is sampled
+done    BR      done
+
+sub     LR      K,P
+        PI      pushk
+        PI      delay
+        PI      joysticks
+        PI      popk
+        PK
+",
+        )
+        .unwrap();
+        let dummy_channel_f = DummyChannelF {
+            pixels: RefCell::new([0u8; 128 * 64]),
+            key_pressed: RefCell::new(true),
+        };
+        let cpu = free_bios_cpu(cartridge.bytes(), &dummy_channel_f);
+
+        assert_eq!(cartridge.symbol("done"), Some(cpu.pc0 as i32));
+        let back = cartridge.symbol("back").unwrap();
+        assert_eq!(back >> 8, cpu.scratchpad[12] as i32);
+        assert_eq!(back & 0xff, cpu.scratchpad[13] as i32);
+        assert_eq!(0x37, cpu.scratchpad[59]);
+        assert_eq!(0, cpu.scratchpad[5]);
+        // start is pressed
+        assert_eq!((1, 0, 0), (cpu.scratchpad[0], cpu.scratchpad[1], cpu.scratchpad[4]));
+    }
+
+    #[test]
+    fn sources() {
+        let program = chf_asm::assemble(CARTRIDGE_TEST_SOURCE).unwrap();
+        assert_eq!(0x800, program.origin());
+        assert_eq!(CARTRIDGE_TEST, program.bytes());
+
+        let program = chf_asm::assemble(FREE_BIOS_SOURCE).unwrap();
+        assert_eq!(0, program.origin());
+        assert_eq!(&bios::FREE_BIOS[..], program.bytes());
+    }
+
+    // the log was recorded with the original BIOS, the registers it leaves behind matter
+    #[test]
+    #[ignore = "needs the original BIOS in roms/"]
     fn test_generated() {
+        let (rom_0000, rom_0400) = original_bios();
+
         let mut pcs: Vec<String> = Vec::new();
         let f = std::fs::File::open("./testfiles/test.log").unwrap();
         let file = std::io::BufReader::new(&f);
@@ -1152,7 +1761,7 @@ mod tests {
             key_pressed: RefCell::new(false),
         };
         let catridge = CARTRIDGE_TEST;
-        let mut cpu = Cpu::new(ROM_0000, ROM_0400, catridge, &dummy_channel_f);
+        let mut cpu = Cpu::new(&rom_0000, &rom_0400, catridge, &dummy_channel_f);
         cpu.reset();
 
 
@@ -0,0 +1,259 @@
+//! Input movies
+//!
+//! A movie is the `Input` of every frame together with the CRCs of the BIOS and the
+//! cartridge it was recorded with. Since the core samples inputs only at frame boundaries
+//! (see `Cpu::step`) replaying the same inputs reproduces the same run.
+//!
+//! File layout (all numbers little endian)
+//!
+//! |Offset|Size|Contents|
+//! |---|---|---|
+//! |0|4|magic `CHFM`|
+//! |4|2|format version|
+//! |6|2|reserved, 0|
+//! |8|4|CRC-32 of the cartridge|
+//! |12|4|CRC-32 of the BIOS (0x0000 ROM followed by the 0x0400 ROM)|
+//! |16|4|number of frames|
+//! |20|4 * frames|`Input` of every frame|
+
+use crate::crc::{crc32, Crc32};
+use crate::Input;
+
+pub const MAGIC: [u8; 4] = *b"CHFM";
+pub const VERSION: u16 = 1;
+pub const HEADER_LEN: usize = 20;
+pub const FRAME_LEN: usize = 4;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MovieError {
+    TooShort,
+    BadMagic,
+    UnsupportedVersion(u16),
+    Truncated,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MovieHeader {
+    pub cartridge_crc: u32,
+    pub bios_crc: u32,
+    pub frames: u32,
+}
+
+impl MovieHeader {
+    pub fn new(rom_0: &[u8], rom_400: &[u8], cartridge: &[u8]) -> MovieHeader {
+        MovieHeader {
+            cartridge_crc: crc32(cartridge),
+            bios_crc: bios_crc(rom_0, rom_400),
+            frames: 0,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut res = [0u8; HEADER_LEN];
+        res[0..4].copy_from_slice(&MAGIC);
+        res[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        res[8..12].copy_from_slice(&self.cartridge_crc.to_le_bytes());
+        res[12..16].copy_from_slice(&self.bios_crc.to_le_bytes());
+        res[16..20].copy_from_slice(&self.frames.to_le_bytes());
+        res
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<MovieHeader, MovieError> {
+        if data.len() < HEADER_LEN {
+            return Err(MovieError::TooShort);
+        }
+
+        if data[0..4] != MAGIC {
+            return Err(MovieError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        if version != VERSION {
+            return Err(MovieError::UnsupportedVersion(version));
+        }
+
+        Ok(MovieHeader {
+            cartridge_crc: read_u32(&data[8..]),
+            bios_crc: read_u32(&data[12..]),
+            frames: read_u32(&data[16..]),
+        })
+    }
+
+    /// True if the movie was recorded with the given BIOS and cartridge
+    pub fn matches(&self, other: &MovieHeader) -> bool {
+        self.cartridge_crc == other.cartridge_crc && self.bios_crc == other.bios_crc
+    }
+}
+
+pub fn bios_crc(rom_0: &[u8], rom_400: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(rom_0);
+    crc.update(rom_400);
+    crc.finish()
+}
+
+pub fn encode_frame(input: Input) -> [u8; FRAME_LEN] {
+    input.0.to_le_bytes()
+}
+
+/// Reads the frames of a movie, see `MovieReader::new`
+pub struct MovieReader<'a> {
+    pub header: MovieHeader,
+    data: &'a [u8],
+    frame: u32,
+}
+
+impl<'a> MovieReader<'a> {
+    pub fn new(data: &'a [u8]) -> Result<MovieReader<'a>, MovieError> {
+        let header = MovieHeader::from_bytes(data)?;
+        let data = &data[HEADER_LEN..];
+
+        if data.len() < header.frames as usize * FRAME_LEN {
+            return Err(MovieError::Truncated);
+        }
+
+        Ok(MovieReader {
+            header,
+            data,
+            frame: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for MovieReader<'a> {
+    type Item = Input;
+
+    fn next(&mut self) -> Option<Input> {
+        if self.frame >= self.header.frames {
+            return None;
+        }
+
+        let offset = self.frame as usize * FRAME_LEN;
+        self.frame += 1;
+        Some(Input(read_u32(&self.data[offset..])))
+    }
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use core::cell::RefCell;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::test_util::PROGRAM;
+    use crate::{ChannelF, Cpu, Key, Sound};
+
+    struct MovieChannelF {
+        pixels: RefCell<[u8; 128 * 64]>,
+        recorded: RefCell<Vec<u8>>,
+        inputs: RefCell<Vec<Input>>,
+    }
+
+    impl MovieChannelF {
+        fn new(inputs: Vec<Input>) -> MovieChannelF {
+            MovieChannelF {
+                pixels: RefCell::new([0u8; 128 * 64]),
+                recorded: RefCell::new(Vec::new()),
+                inputs: RefCell::new(inputs),
+            }
+        }
+    }
+
+    impl ChannelF for MovieChannelF {
+        fn sound(&self, _frequency: Sound) {}
+
+        fn set_pixel(&self, x: u8, y: u8, value: u8) {
+            self.pixels.borrow_mut()[x as usize + y as usize * 128usize] = value;
+        }
+
+        fn key_pressed(&self, _key: Key) -> bool {
+            false
+        }
+
+        fn input(&self) -> Input {
+            let input = self.inputs.borrow_mut().remove(0);
+            self.recorded
+                .borrow_mut()
+                .extend_from_slice(&encode_frame(input));
+            input
+        }
+    }
+
+    fn run(channel_f: &MovieChannelF, frames: usize) -> u32 {
+        let mut cpu = Cpu::new(PROGRAM, &[], &[], channel_f);
+        cpu.reset();
+        for _ in 0..frames {
+            cpu.run_frame();
+        }
+        crc32(&channel_f.pixels.borrow()[..])
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let mut header = MovieHeader::new(&[1, 2, 3], &[4], &[5, 6]);
+        header.frames = 1234;
+        let bytes = header.to_bytes();
+
+        assert_eq!(Ok(header), MovieHeader::from_bytes(&bytes));
+        assert_eq!(bios_crc(&[1, 2, 3], &[4]), crc32(&[1, 2, 3, 4]));
+        assert_eq!(
+            Err(MovieError::TooShort),
+            MovieHeader::from_bytes(&bytes[..10])
+        );
+
+        let mut bytes = bytes;
+        bytes[4] = 9;
+        assert_eq!(
+            Err(MovieError::UnsupportedVersion(9)),
+            MovieHeader::from_bytes(&bytes)
+        );
+        bytes[0] = b'X';
+        assert_eq!(Err(MovieError::BadMagic), MovieHeader::from_bytes(&bytes));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut header = MovieHeader::new(&[], &[], &[]);
+        header.frames = 2;
+        let mut data = header.to_bytes().to_vec();
+        data.extend_from_slice(&encode_frame(Input(1)));
+
+        assert_eq!(Some(MovieError::Truncated), MovieReader::new(&data).err());
+    }
+
+    #[test]
+    fn replay() {
+        const FRAMES: usize = 90;
+
+        // frame N presses some of the left controller's keys
+        let mut inputs = Vec::new();
+        for frame in 0..FRAMES {
+            let mut input = Input::default();
+            input.set(Key::Pull0, frame % 3 == 0);
+            input.set(Key::Push0, frame % 5 < 2);
+            input.set(Key::Right0, frame % 7 == 1);
+            inputs.push(input);
+        }
+
+        let recording = MovieChannelF::new(inputs);
+        let recorded_hash = run(&recording, FRAMES);
+
+        let mut header = MovieHeader::new(PROGRAM, &[], &[]);
+        header.frames = FRAMES as u32;
+        let mut movie = header.to_bytes().to_vec();
+        movie.extend_from_slice(&recording.recorded.borrow());
+
+        let reader = MovieReader::new(&movie).unwrap();
+        assert!(reader.header.matches(&MovieHeader::new(PROGRAM, &[], &[])));
+        let playback = MovieChannelF::new(reader.collect());
+        let replayed_hash = run(&playback, FRAMES);
+
+        assert_eq!(recorded_hash, replayed_hash);
+        assert_eq!(0xd9ec_05f3, replayed_hash);
+    }
+}
@@ -0,0 +1,313 @@
+//! Save files - ranges of the scratchpad, the RAM and the SRAM kept across power cycles
+//!
+//! The ranges a cartridge keeps are written like cheats, the kind and the first - last address
+//! in hex. An optional `@` and a decimal frame number delays loading the save until that frame,
+//! games clear their memory when they start:
+//!
+//! ```text
+//! @120 S 20-2f M 2800-280f
+//! ```
+//!
+//! Layout of the file (all numbers little endian)
+//!
+//! |Size|Contents|
+//! |---|---|
+//! |4|magic `CHFV`|
+//! |1|format version|
+//! |4|CRC-32 of the cartridge|
+//! |2|number of bytes saved|
+//! |n|the ranges one after the other, one byte per SRAM bit|
+
+use crate::cheat::CheatTarget;
+use crate::state::{self, StateReader, StateWriter};
+use crate::Cpu;
+
+pub const MAGIC: [u8; 4] = *b"CHFV";
+pub const VERSION: u8 = 1;
+
+pub const MAX_RANGES: usize = 8;
+
+/// Number of bytes before the saved ranges
+pub const HEADER_LEN: usize = 4 + 1 + 4 + 2;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SaveParseError {
+    UnknownKind,
+    BadNumber,
+    OutOfRange,
+    MissingValue,
+    TooManyRanges,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SaveError<E> {
+    Io(E),
+    BadMagic,
+    UnsupportedVersion(u8),
+    /// The save file belongs to another cartridge
+    OtherCartridge,
+    /// The ranges changed since the file was saved
+    OtherRanges,
+    /// The file ends before all values saved
+    Truncated,
+}
+
+impl<E> From<E> for SaveError<E> {
+    fn from(e: E) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+/// What a cartridge keeps, locations numbered like `CheatTarget::from_index`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SaveRanges {
+    ranges: [(usize, usize); MAX_RANGES],
+    count: usize,
+    /// The frame the save file is loaded in
+    pub load_frame: u32,
+}
+
+impl SaveRanges {
+    pub fn parse(text: &str) -> Result<SaveRanges, SaveParseError> {
+        let mut result = SaveRanges {
+            ranges: [(0, 0); MAX_RANGES],
+            count: 0,
+            load_frame: 0,
+        };
+
+        let mut parts = text.split_whitespace().peekable();
+        if let Some(frame) = parts.peek().and_then(|part| part.strip_prefix('@')) {
+            result.load_frame = frame.parse().map_err(|_| SaveParseError::BadNumber)?;
+            parts.next();
+        }
+
+        while let Some(kind) = parts.next() {
+            let (first, last) = match parts.next() {
+                Some(range) => parse_range(range)?,
+                None => return Err(SaveParseError::MissingValue),
+            };
+            let (base, start, len) = match kind {
+                "S" | "s" => (0, 0, 64),
+                "M" | "m" => (64, 0x2800, 0x800),
+                "B" | "b" => (0x840, 0, 0x400),
+                _ => return Err(SaveParseError::UnknownKind),
+            };
+            if first < start || last < first || last - start >= len {
+                return Err(SaveParseError::OutOfRange);
+            }
+            if result.count == MAX_RANGES {
+                return Err(SaveParseError::TooManyRanges);
+            }
+
+            result.ranges[result.count] = (base + first - start, last - first + 1);
+            result.count += 1;
+        }
+
+        Ok(result)
+    }
+
+    /// Number of bytes saved
+    pub fn len(&self) -> usize {
+        self.ranges[..self.count].iter().map(|(_, len)| len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    fn targets(&self) -> impl Iterator<Item = CheatTarget> + '_ {
+        self.ranges[..self.count]
+            .iter()
+            .flat_map(|(first, len)| *first..*first + *len)
+            .filter_map(CheatTarget::from_index)
+    }
+}
+
+fn parse_range(range: &str) -> Result<(usize, usize), SaveParseError> {
+    let mut bounds = range.splitn(2, '-');
+    let first = parse_hex(bounds.next().unwrap_or_default())?;
+    let last = match bounds.next() {
+        Some(last) => parse_hex(last)?,
+        None => first,
+    };
+    Ok((first, last))
+}
+
+fn parse_hex(s: &str) -> Result<usize, SaveParseError> {
+    let s = s.trim_start_matches("0x");
+    usize::from_str_radix(s, 16).map_err(|_| SaveParseError::BadNumber)
+}
+
+/// Writes the ranges of the cartridge with the CRC-32 `crc`
+pub fn write<W: StateWriter>(
+    cpu: &Cpu,
+    ranges: &SaveRanges,
+    crc: u32,
+    writer: &mut W,
+) -> Result<(), W::Error> {
+    writer.write(&MAGIC)?;
+    writer.write(&[VERSION])?;
+    writer.write(&crc.to_le_bytes())?;
+    writer.write(&(ranges.len() as u16).to_le_bytes())?;
+
+    for target in ranges.targets() {
+        writer.write(&[cpu.cheat_target_value(target)])?;
+    }
+    Ok(())
+}
+
+/// Restores what `write` saved, nothing is changed if the file doesn't fit the cartridge
+pub fn read<R: StateReader>(
+    cpu: &mut Cpu,
+    ranges: &SaveRanges,
+    crc: u32,
+    reader: &mut R,
+) -> Result<(), SaveError<R::Error>> {
+    let mut magic = [0u8; 4];
+    reader.read(&mut magic)?;
+    if magic != MAGIC {
+        return Err(SaveError::BadMagic);
+    }
+
+    let version = state::read_u8(reader)?;
+    if version != VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+    if state::read_u32(reader)? != crc {
+        return Err(SaveError::OtherCartridge);
+    }
+    if state::read_u16(reader)? as usize != ranges.len() {
+        return Err(SaveError::OtherRanges);
+    }
+    if reader.remaining() < ranges.len() {
+        return Err(SaveError::Truncated);
+    }
+
+    for target in ranges.targets() {
+        let value = state::read_u8(reader)?;
+        match target {
+            CheatTarget::Scratchpad(r) => cpu.scratchpad[r as usize] = value,
+            CheatTarget::Ram(addr) => cpu.ram_mut()[addr as usize - 0x2800] = value,
+            CheatTarget::Sram(bit) => cpu.xmemory[bit as usize] = value & 1,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::state::{EndOfData, SliceReader, SliceWriter};
+    use crate::test_util::NullChannelF;
+
+    #[test]
+    fn parse() {
+        let ranges = SaveRanges::parse("@120 S 20-2f m 2800-280f B 3ff").unwrap();
+        assert_eq!(120, ranges.load_frame);
+        assert_eq!(16 + 16 + 1, ranges.len());
+        let targets: Vec<CheatTarget> = ranges.targets().collect();
+        assert_eq!(CheatTarget::Scratchpad(0x20), targets[0]);
+        assert_eq!(CheatTarget::Ram(0x2800), targets[16]);
+        assert_eq!(CheatTarget::Ram(0x280f), targets[31]);
+        assert_eq!(CheatTarget::Sram(0x3ff), targets[32]);
+
+        let ranges = SaveRanges::parse("").unwrap();
+        assert!(ranges.is_empty());
+        assert_eq!(0, ranges.load_frame);
+
+        assert_eq!(Err(SaveParseError::UnknownKind), SaveRanges::parse("X 0-1"));
+        assert_eq!(Err(SaveParseError::MissingValue), SaveRanges::parse("S"));
+        assert_eq!(Err(SaveParseError::BadNumber), SaveRanges::parse("S 0-xy"));
+        assert_eq!(Err(SaveParseError::BadNumber), SaveRanges::parse("@x S 0"));
+        assert_eq!(
+            Err(SaveParseError::OutOfRange),
+            SaveRanges::parse("S 30-40")
+        );
+        assert_eq!(
+            Err(SaveParseError::OutOfRange),
+            SaveRanges::parse("S 2f-20")
+        );
+        assert_eq!(Err(SaveParseError::OutOfRange), SaveRanges::parse("M 27ff"));
+        assert_eq!(
+            Err(SaveParseError::TooManyRanges),
+            SaveRanges::parse("S 0 S 1 S 2 S 3 S 4 S 5 S 6 S 7 S 8")
+        );
+    }
+
+    #[test]
+    fn save_and_restore() {
+        let ranges = SaveRanges::parse("S 20-21 M 2fff B 10-11").unwrap();
+        let mut cpu = Cpu::new(&[], &[], &[], &NullChannelF);
+        cpu.scratchpad[0x20] = 1;
+        cpu.scratchpad[0x21] = 2;
+        cpu.scratchpad[0x22] = 3;
+        cpu.ram_mut()[0x7ff] = 4;
+        cpu.xmemory[0x10] = 0;
+
+        let mut saved = vec![0u8; HEADER_LEN + ranges.len()];
+        write(
+            &cpu,
+            &ranges,
+            0x1234_5678,
+            &mut SliceWriter::new(&mut saved),
+        )
+        .unwrap();
+        assert_eq!(b"CHFV\x01\x78\x56\x34\x12\x05\x00", &saved[..HEADER_LEN]);
+        assert_eq!([1, 2, 4, 0, 1], saved[HEADER_LEN..]);
+
+        let mut restored = Cpu::new(&[], &[], &[], &NullChannelF);
+        read(
+            &mut restored,
+            &ranges,
+            0x1234_5678,
+            &mut SliceReader::new(&saved),
+        )
+        .unwrap();
+        assert_eq!([1, 2, 0], restored.scratchpad[0x20..0x23]);
+        assert_eq!(4, restored.ram()[0x7ff]);
+        assert_eq!([0, 1], restored.xmemory[0x10..0x12]);
+
+        // a file of another cartridge or with other ranges changes nothing
+        let mut other = Cpu::new(&[], &[], &[], &NullChannelF);
+        assert_eq!(
+            Err(SaveError::OtherCartridge),
+            read(&mut other, &ranges, 1, &mut SliceReader::new(&saved))
+        );
+        let fewer = SaveRanges::parse("S 20-21").unwrap();
+        assert_eq!(
+            Err(SaveError::OtherRanges),
+            read(
+                &mut other,
+                &fewer,
+                0x1234_5678,
+                &mut SliceReader::new(&saved)
+            )
+        );
+        assert_eq!(
+            Err(SaveError::BadMagic),
+            read(
+                &mut other,
+                &ranges,
+                0x1234_5678,
+                &mut SliceReader::new(b"CHFS")
+            )
+        );
+        assert_eq!(
+            Err(SaveError::Io(EndOfData)),
+            read(&mut other, &ranges, 0x1234_5678, &mut SliceReader::new(b""))
+        );
+        assert_eq!(
+            Err(SaveError::Truncated),
+            read(
+                &mut other,
+                &ranges,
+                0x1234_5678,
+                &mut SliceReader::new(&saved[..saved.len() - 1])
+            )
+        );
+        assert_eq!(0, other.scratchpad[0x20]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Saving and loading the machine state
+//!
+//! The state is written and read as a stream (see `StateWriter` / `StateReader`) so it can
+//! go straight to a file without a buffer for the whole state.
+//! ROMs and the cartridge are not part of the state.
+//!
+//! Layout (all numbers little endian)
+//!
+//! |Size|Contents|
+//! |---|---|
+//! |4|magic `CHFS`|
+//! |1|format version|
+//! |4|A, ISAR, flags, ICB|
+//! |8|PC0, PC1, DC0, DC1|
+//! |2|2102 SRAM address/data register|
+//! |3|video x, y, color|
+//! |12|input, cycles into the current frame, frame counter|
+//! |64|scratchpad|
+//! |256|I/O port latches|
+//! |2048|RAM at 0x2800|
+//! |128|2102 SRAM, one bit per cell|
+//! |2048|VRAM, four 2 bit pixels per byte|
+//!
+//! The frontends keep `SLOTS` states per cartridge in files named by `slot_file_name`, so a
+//! state can be copied between the SD-card and the desktop.
+
+pub const MAGIC: [u8; 4] = *b"CHFS";
+pub const VERSION: u8 = 1;
+
+/// Number of bytes written by `Cpu::save_state`
+pub const STATE_LEN: usize = 4 + 1 + 4 + 8 + 2 + 3 + 12 + 64 + 256 + 0x800 + 0x80 + 0x800;
+
+/// Save states kept per cartridge
+pub const SLOTS: u8 = 5;
+
+/// The 8.3 name of a save state, the CRC-32 of the cartridge and the slot: `0123ABCD.ST0`
+pub fn slot_file_name(crc: u32, slot: u8) -> [u8; 12] {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut name = *b"00000000.ST0";
+    for (idx, c) in name[..8].iter_mut().enumerate() {
+        *c = HEX[((crc >> (28 - 4 * idx)) & 0xf) as usize];
+    }
+    name[11] = b'0' + slot % 10;
+    name
+}
+
+pub trait StateWriter {
+    type Error;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+pub trait StateReader {
+    type Error;
+
+    fn read(&mut self, data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Bytes left to read
+    fn remaining(&self) -> usize;
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StateError<E> {
+    Io(E),
+    BadMagic,
+    UnsupportedVersion(u8),
+    /// Not `STATE_LEN` bytes long
+    Truncated,
+}
+
+impl<E> From<E> for StateError<E> {
+    fn from(e: E) -> Self {
+        StateError::Io(e)
+    }
+}
+
+/// Returned by `SliceWriter` and `SliceReader` when the slice is too short
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EndOfData;
+
+pub struct SliceWriter<'a> {
+    data: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub fn new(data: &'a mut [u8]) -> SliceWriter<'a> {
+        SliceWriter { data, pos: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+}
+
+impl<'a> StateWriter for SliceWriter<'a> {
+    type Error = EndOfData;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), EndOfData> {
+        if self.pos + data.len() > self.data.len() {
+            return Err(EndOfData);
+        }
+
+        self.data[self.pos..self.pos + data.len()].copy_from_slice(data);
+        self.pos += data.len();
+        Ok(())
+    }
+}
+
+pub struct SliceReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    pub fn new(data: &'a [u8]) -> SliceReader<'a> {
+        SliceReader { data, pos: 0 }
+    }
+}
+
+impl<'a> StateReader for SliceReader<'a> {
+    type Error = EndOfData;
+
+    fn read(&mut self, data: &mut [u8]) -> Result<(), EndOfData> {
+        if self.pos + data.len() > self.data.len() {
+            return Err(EndOfData);
+        }
+
+        data.copy_from_slice(&self.data[self.pos..self.pos + data.len()]);
+        self.pos += data.len();
+        Ok(())
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+}
+
+pub(crate) fn read_u8<R: StateReader>(reader: &mut R) -> Result<u8, R::Error> {
+    let mut buffer = [0u8; 1];
+    reader.read(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+pub(crate) fn read_u16<R: StateReader>(reader: &mut R) -> Result<u16, R::Error> {
+    let mut buffer = [0u8; 2];
+    reader.read(&mut buffer)?;
+    Ok(u16::from_le_bytes(buffer))
+}
+
+pub(crate) fn read_u32<R: StateReader>(reader: &mut R) -> Result<u32, R::Error> {
+    let mut buffer = [0u8; 4];
+    reader.read(&mut buffer)?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use core::cell::RefCell;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::test_util::PROGRAM;
+    use crate::{ChannelF, Cpu, Key, Sound};
+
+    struct DummyChannelF {
+        pixels: RefCell<[u8; 128 * 64]>,
+    }
+
+    impl ChannelF for DummyChannelF {
+        fn sound(&self, _frequency: Sound) {}
+
+        fn set_pixel(&self, x: u8, y: u8, value: u8) {
+            self.pixels.borrow_mut()[x as usize + y as usize * 128usize] = value;
+        }
+
+        fn key_pressed(&self, key: Key) -> bool {
+            key == Key::Push0
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let channel_f = DummyChannelF {
+            pixels: RefCell::new([0u8; 128 * 64]),
+        };
+        let mut cpu = Cpu::new(PROGRAM, &[], &[], &channel_f);
+        cpu.reset();
+        for _ in 0..3 {
+            cpu.run_frame();
+        }
+
+        let mut saved = vec![0u8; STATE_LEN];
+        let mut writer = SliceWriter::new(&mut saved);
+        cpu.save_state(&mut writer).unwrap();
+        assert_eq!(STATE_LEN, writer.len());
+        let saved_pixels = *channel_f.pixels.borrow();
+
+        for _ in 0..3 {
+            cpu.run_frame();
+        }
+
+        let mut other = vec![0u8; STATE_LEN];
+        cpu.save_state(&mut SliceWriter::new(&mut other)).unwrap();
+        assert_ne!(saved, other);
+
+        // loading the state and repainting brings back the same state and screen
+        let repainted = DummyChannelF {
+            pixels: RefCell::new([0u8; 128 * 64]),
+        };
+        let mut loaded = Cpu::new(PROGRAM, &[], &[], &repainted);
+        loaded.load_state(&mut SliceReader::new(&saved)).unwrap();
+        loaded.repaint();
+        assert_eq!(saved_pixels[..], repainted.pixels.borrow()[..]);
+
+        loaded
+            .save_state(&mut SliceWriter::new(&mut other))
+            .unwrap();
+        assert_eq!(saved, other);
+    }
+
+    #[test]
+    fn slot_file_names() {
+        assert_eq!(b"0123ABCD.ST0", &slot_file_name(0x0123_abcd, 0));
+        assert_eq!(b"FFFFFFFF.ST4", &slot_file_name(u32::MAX, SLOTS - 1));
+    }
+
+    #[test]
+    fn bad_state() {
+        let channel_f = DummyChannelF {
+            pixels: RefCell::new([0u8; 128 * 64]),
+        };
+        let mut cpu = Cpu::new(PROGRAM, &[], &[], &channel_f);
+
+        let mut saved = vec![0u8; STATE_LEN];
+        cpu.save_state(&mut SliceWriter::new(&mut saved)).unwrap();
+
+        // nothing is loaded from a state of the wrong length
+        let a = cpu.a;
+        saved[5] = !a;
+        assert_eq!(
+            Err(StateError::Truncated),
+            cpu.load_state(&mut SliceReader::new(&saved[..100]))
+        );
+        assert_eq!(
+            Err(StateError::Truncated),
+            cpu.load_state(&mut SliceReader::new(&[&saved[..], &[0]].concat()))
+        );
+        assert_eq!(a, cpu.a);
+        assert_eq!(
+            Err(EndOfData),
+            cpu.save_state(&mut SliceWriter::new(&mut saved[..100]))
+        );
+
+        saved[4] = 2;
+        assert_eq!(
+            Err(StateError::UnsupportedVersion(2)),
+            cpu.load_state(&mut SliceReader::new(&saved))
+        );
+
+        saved[0] = 0;
+        assert_eq!(
+            Err(StateError::BadMagic),
+            cpu.load_state(&mut SliceReader::new(&saved))
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Fixtures shared by the tests, here and in the crates using the core
+
+use crate::{ChannelF, Key, Sound};
+
+/// A console without sound, screen and pressed keys
+pub struct NullChannelF;
+
+impl ChannelF for NullChannelF {
+    fn sound(&self, _frequency: Sound) {}
+
+    fn set_pixel(&self, _x: u8, _y: u8, _value: u8) {}
+
+    fn key_pressed(&self, _key: Key) -> bool {
+        false
+    }
+}
+
+/// Draws the state of the left controller as pixels - a pixel per instruction loop
+///
+/// ```text
+/// 0000 LIS 0 / LR 1,A
+/// 0002 INS 1 / COM / OUTS 1        color from pull/push
+/// 0005 LR A,1 / OUTS 4 / OUTS 5    x, y
+/// 0008 LI $20 / OUTS 0 / LIS 0 / OUTS 0
+/// 000d LR A,1 / INC / LR 1,A
+/// 0010 BR 0002
+/// ```
+pub const PROGRAM: &[u8] = &[
+    0x70, 0x51, 0xa1, 0x18, 0xb1, 0x41, 0xb4, 0xb5, 0x20, 0x20, 0xb0, 0x70, 0xb0, 0x41, 0x1f, 0x51,
+    0x90, 0xf1,
+];
@@ -0,0 +1,1982 @@
+; The cartridge of random instructions gen-test-bin made, test.log is MAME's trace of it
+;
+; chf-asm test.asm test.bin gives test.bin again
+
+        ORG     H'0800'
+        DC      H'55',H'00'
+        ASD     R1
+        DS      HU
+        CM
+        XI      H'1F'
+        LR      R7,A
+        LIS     H'0A'
+        LR      Q,DC
+        LIS     H'01'
+        NS      S
+        AI      H'BA'
+        LNK
+        DS      R2
+        LR      A,I
+        LR      A,QL
+        NS      R1
+        LR      HL,A
+        LI      H'51'
+        LR      A,HU
+        LR      R2,A
+        OI      H'78'
+        LISU    3
+        DS      R2
+        ASD     I
+        DS      R7
+        AS      J
+        AS      R2
+        DS      R1
+        LR      KU,A
+        NS      R8
+        LM
+        XS      R1
+        NS      S
+        LR      A,J
+        SL      1
+        XS      R6
+        ASD     I
+        LR      A,R2
+        NS      R1
+        NS      HU
+        DS      HL
+        LR      P,K
+        NS      HU
+        LISL    5
+        AS      J
+        LIS     H'0D'
+        AS      R4
+        LIS     H'08'
+        LR      A,R7
+        OM
+        LIS     H'03'
+        NS      R3
+        DS      D
+        XS      R3
+        ASD     R4
+        DI
+        NS      R5
+        LIS     H'0E'
+        LR      HU,A
+        ASD     R4
+        AM
+        LR      A,HL
+        LIS     H'0F'
+        ASD     R5
+        NS      J
+        LIS     H'0A'
+        LR      A,I
+        NS      R8
+        NS      HL
+        LR      Q,DC
+        XS      R3
+        LR      QL,A
+        LR      A,R6
+        NS      R7
+        XS      D
+        LR      R5,A
+        LISU    6
+        DS      R4
+        AS      J
+        LIS     H'03'
+        XS      R1
+        LR      A,QL
+        DS      R2
+        LISL    2
+        LR      J,A
+        LISL    5
+        NS      J
+        LIS     H'0E'
+        DS      HL
+        XDC
+        DS      HU
+        NS      S
+        DS      R4
+        AS      R0
+        NS      R3
+        DS      D
+        LISU    2
+        AS      R1
+        XS      R6
+        LR      A,R6
+        LIS     H'0D'
+        AS      R3
+        AI      H'B4'
+        XS      R8
+        XS      J
+        SR      4
+        AS      HU
+        XS      R5
+        AS      R8
+        LNK
+        LIS     H'0C'
+        NM
+        LR      R8,A
+        LISL    7
+        AMD
+        DS      R5
+        LR      A,KU
+        LISL    6
+        NOP
+        LR      W,J
+        AS      R3
+        LR      A,R5
+        LR      KU,A
+        LR      S,A
+        DS      R0
+        XS      R1
+        LR      I,A
+        ASD     R0
+        LR      J,W
+        AS      R4
+        ASD     D
+        LR      A,R6
+        XS      D
+        LIS     H'0B'
+        LI      H'E7'
+        XS      R3
+        XS      R5
+        COM
+        CM
+        LISU    0
+        ASD     R4
+        ASD     R0
+        LIS     H'0E'
+        LR      J,W
+        DS      R2
+        LR      A,R2
+        NS      R5
+        XS      R3
+        ASD     R3
+        LR      J,A
+        ASD     HU
+        NS      I
+        LR      A,R1
+        DS      I
+        DS      R1
+        NS      R7
+        LR      HU,A
+        LIS     H'0E'
+        LIS     H'0F'
+        LIS     H'01'
+        DS      R5
+        ASD     R6
+        LR      A,R4
+        XS      HU
+        AS      R1
+        XM
+        AS      R4
+        NS      R2
+        LR      DC,H
+        DS      J
+        LIS     H'0B'
+        LISL    1
+        LR      A,I
+        LIS     H'0F'
+        EI
+        LIS     H'05'
+        LIS     H'02'
+        DS      HL
+        LR      I,A
+        ASD     I
+        LR      KU,A
+        LR      DC,H
+        SR      1
+        DS      R3
+        DS      R5
+        LR      A,R8
+        LR      QU,A
+        AS      R8
+        LR      A,R1
+        LIS     H'03'
+        XS      R4
+        ASD     R3
+        NS      R8
+        LR      D,A
+        LR      R2,A
+        ASD     I
+        LR      R4,A
+        AS      R7
+        LIS     H'05'
+        LISL    4
+        NOP
+        SL      1
+        AS      R2
+        LI      H'BA'
+        LR      P,K
+        AS      R2
+        ASD     R2
+        LR      KL,A
+        LR      A,R6
+        XS      I
+        AS      R4
+        AS      R6
+        LR      KU,A
+        LIS     H'05'
+        LISU    7
+        ASD     R2
+        XS      R2
+        ASD     R1
+        SR      4
+        DS      D
+        AS      R8
+        AS      R3
+        DS      R2
+        ASD     R2
+        LR      A,KU
+        LIS     H'0F'
+        LIS     H'0B'
+        LISL    2
+        DS      HL
+        LIS     H'0D'
+        ASD     R1
+        XS      R6
+        DS      R6
+        LR      R8,A
+        LISU    5
+        AS      HU
+        OI      H'9F'
+        LR      KL,A
+        AS      R7
+        LR      A,R6
+        AS      R5
+        XS      HL
+        EI
+        LR      A,IS
+        DS      R1
+        DS      R7
+        LIS     H'0D'
+        LISL    4
+        XS      R2
+        LISL    0
+        LR      QL,A
+        LR      QL,A
+        LR      P,K
+        OM
+        NOP
+        LR      A,R5
+        XS      D
+        AS      D
+        LR      I,A
+        XS      R4
+        LIS     H'0B'
+        ASD     HU
+        LISU    7
+        NS      R7
+        ASD     HU
+        XS      J
+        LISL    6
+        DI
+        LR      P,K
+        LR      J,A
+        ASD     R0
+        CLR
+        SR      1
+        XM
+        LR      A,IS
+        AS      D
+        CLR
+        XS      R6
+        DS      I
+        DS      R3
+        OI      H'70'
+        LR      A,R0
+        LIS     H'01'
+        LISU    0
+        LR      DC,H
+        LISU    1
+        LR      R4,A
+        OI      H'8D'
+        AS      I
+        LR      R5,A
+        SL      1
+        DS      R2
+        NM
+        NM
+        LISL    6
+        LIS     H'04'
+        CI      H'CF'
+        LR      R1,A
+        LNK
+        LR      R4,A
+        NS      R6
+        LR      A,IS
+        LR      I,A
+        NS      R1
+        AS      R4
+        ASD     HL
+        LISL    5
+        CI      H'B4'
+        NS      R6
+        AS      R0
+        XS      R8
+        ASD     R4
+        LI      H'31'
+        LR      A,R6
+        NS      HL
+        ASD     D
+        DS      R6
+        XS      R1
+        XS      R4
+        SR      4
+        LR      A,HL
+        DS      HL
+        XS      R3
+        LR      A,HL
+        NS      D
+        ASD     R0
+        LR      A,R0
+        SL      4
+        AS      R5
+        NS      HU
+        AS      R1
+        ASD     R4
+        NS      S
+        LIS     H'0B'
+        ASD     I
+        DS      D
+        LIS     H'07'
+        DS      HL
+        LR      A,R0
+        OM
+        DS      R2
+        LNK
+        LR      IS,A
+        AMD
+        XS      R1
+        DI
+        ASD     R8
+        NS      I
+        XS      HU
+        LIS     H'02'
+        XS      S
+        LR      R6,A
+        LR      HU,A
+        DS      R8
+        ASD     I
+        NS      R2
+        LISL    5
+        LR      HL,A
+        LIS     H'0C'
+        LR      HU,A
+        NI      H'72'
+        LR      A,QU
+        LR      DC,Q
+        LR      A,R8
+        ASD     R7
+        LR      J,A
+        LR      W,J
+        SR      4
+        LIS     H'07'
+        DS      R0
+        LR      A,IS
+        LISL    7
+        SR      1
+        LR      P,K
+        LR      A,R8
+        LR      R1,A
+        XM
+        LR      A,R5
+        AS      HL
+        LR      H,DC
+        NS      R2
+        COM
+        LIS     H'0F'
+        XS      I
+        ASD     R3
+        XS      R0
+        AS      R8
+        LM
+        AS      J
+        XS      R1
+        DS      R5
+        XS      S
+        LR      A,S
+        XDC
+        XS      R0
+        LISU    5
+        NS      D
+        XS      R4
+        ASD     R3
+        LR      KL,A
+        LR      W,J
+        DS      HU
+        LR      K,P
+        AS      R0
+        DS      R1
+        LR      R0,A
+        DS      R6
+        LIS     H'01'
+        LR      D,A
+        DS      D
+        LM
+        DS      HL
+        LR      HU,A
+        XS      R4
+        XS      R4
+        LM
+        AI      H'6C'
+        LISU    5
+        DS      R8
+        LR      W,J
+        NS      R2
+        LIS     H'04'
+        NS      R3
+        NS      R1
+        NI      H'19'
+        LR      A,R1
+        XDC
+        XI      H'17'
+        CI      H'78'
+        LIS     H'0A'
+        LISL    3
+        LR      R6,A
+        LR      A,I
+        LR      A,R1
+        LR      DC,H
+        LR      J,W
+        CI      H'12'
+        DS      HL
+        DS      R7
+        LR      HU,A
+        AS      R0
+        AMD
+        XS      R5
+        NS      HU
+        NS      R8
+        XS      R1
+        ASD     D
+        ASD     HL
+        ASD     R1
+        ASD     HL
+        LISL    5
+        LR      QU,A
+        LISL    2
+        LR      A,R1
+        XS      HL
+        NOP
+        ASD     HL
+        AS      R0
+        DS      R0
+        LR      R8,A
+        XS      HU
+        NS      HU
+        LR      W,J
+        XS      R1
+        AMD
+        NS      R6
+        LR      R8,A
+        XS      HL
+        LR      R8,A
+        LIS     H'03'
+        AS      J
+        LR      DC,H
+        AS      HL
+        LIS     H'05'
+        XS      R6
+        LISL    3
+        LISU    0
+        LIS     H'02'
+        LR      R1,A
+        AS      R1
+        ASD     HU
+        AS      HU
+        NS      HL
+        AS      S
+        XS      R3
+        LR      QL,A
+        LR      QU,A
+        LR      IS,A
+        NS      HL
+        LIS     H'0E'
+        LR      DC,Q
+        DS      R5
+        LR      R1,A
+        ASD     D
+        NM
+        XS      I
+        AM
+        XS      HL
+        AS      J
+        XS      R4
+        DS      R3
+        XS      I
+        LIS     H'0A'
+        AS      R7
+        ASD     HU
+        ASD     I
+        LR      R7,A
+        DS      R1
+        NS      S
+        LR      K,P
+        CM
+        ASD     HL
+        NOP
+        LISL    7
+        LR      R4,A
+        AS      D
+        AS      J
+        CI      H'16'
+        LR      S,A
+        LR      W,J
+        NS      R7
+        LR      W,J
+        LR      R4,A
+        DS      R4
+        XS      R2
+        LISL    3
+        LR      H,DC
+        DS      R7
+        LR      A,J
+        ASD     I
+        LR      R6,A
+        NS      J
+        AM
+        LR      I,A
+        LR      I,A
+        LR      A,J
+        LR      KU,A
+        ASD     R6
+        NI      H'8D'
+        NS      D
+        DS      S
+        LNK
+        LR      IS,A
+        ASD     R1
+        LIS     H'0B'
+        LR      A,S
+        NS      R6
+        CM
+        LIS     H'06'
+        LR      R0,A
+        LR      J,A
+        XS      HL
+        LIS     H'0E'
+        AS      R6
+        LR      A,R2
+        DI
+        AS      D
+        XS      D
+        ASD     R6
+        LIS     H'03'
+        COM
+        LIS     H'0F'
+        DS      R2
+        OI      H'CF'
+        LIS     H'03'
+        AS      R0
+        NS      HU
+        DS      R8
+        XM
+        AS      J
+        LM
+        XS      R1
+        XS      R1
+        LR      A,I
+        XS      R8
+        ASD     S
+        LNK
+        LR      A,R6
+        LR      W,J
+        CM
+        DS      J
+        LISU    5
+        LR      DC,Q
+        LR      DC,H
+        DS      HU
+        NS      HL
+        LIS     H'08'
+        LISU    7
+        LR      A,S
+        LIS     H'0C'
+        DS      J
+        NS      R8
+        XS      J
+        ASD     R1
+        DI
+        AI      H'9E'
+        AS      R3
+        ASD     R6
+        AS      R1
+        NS      HL
+        XS      R4
+        ASD     I
+        ASD     R4
+        ASD     R7
+        LR      W,J
+        LIS     H'0B'
+        ASD     R2
+        DI
+        LR      K,P
+        XI      H'96'
+        XS      R8
+        AS      I
+        LR      A,HL
+        LR      R8,A
+        LR      R1,A
+        LR      W,J
+        AS      R8
+        NS      R4
+        LR      R3,A
+        LR      A,R4
+        ASD     R8
+        AS      R4
+        LR      R8,A
+        LISL    6
+        DS      R3
+        LR      R8,A
+        NS      S
+        LISL    3
+        NS      S
+        XS      J
+        LIS     H'07'
+        AI      H'CC'
+        LISU    4
+        LR      R5,A
+        LR      A,R1
+        LR      W,J
+        XS      R7
+        ASD     R6
+        LISL    4
+        DS      S
+        LR      DC,H
+        AS      HL
+        LISU    7
+        LR      A,R5
+        DS      D
+        ASD     D
+        DS      R2
+        AS      D
+        LISL    2
+        XS      I
+        ASD     R1
+        ASD     HU
+        LR      J,W
+        NS      J
+        ASD     S
+        ASD     R0
+        DS      R2
+        XS      R0
+        DS      R6
+        LR      R0,A
+        ASD     R4
+        NS      D
+        DI
+        LR      KL,A
+        LR      A,R5
+        AI      H'B9'
+        LISL    4
+        ASD     R8
+        LR      HL,A
+        XS      I
+        NS      HL
+        AS      R8
+        NS      R1
+        AM
+        LISL    0
+        XS      S
+        LR      IS,A
+        LR      R5,A
+        LR      HU,A
+        LR      P,K
+        LR      A,R2
+        NS      R4
+        LISL    2
+        LR      A,QL
+        SR      1
+        ADC
+        LR      R4,A
+        AS      R8
+        LR      P,K
+        SR      4
+        LIS     H'0B'
+        ASD     R1
+        INC
+        AS      R6
+        AS      S
+        ASD     R2
+        SL      4
+        LR      R2,A
+        LISU    6
+        DS      R4
+        LR      A,HL
+        NS      R3
+        LM
+        LIS     H'03'
+        LR      A,HU
+        LIS     H'0E'
+        LR      A,R7
+        LISL    2
+        LR      A,R4
+        XS      R1
+        XS      R2
+        AS      HU
+        LISL    2
+        DS      I
+        OI      H'96'
+        DS      R0
+        XS      R5
+        LISL    2
+        XS      R1
+        LR      A,R1
+        LR      R1,A
+        DS      R4
+        LIS     H'01'
+        LNK
+        EI
+        LISL    0
+        LR      A,S
+        LISU    7
+        LR      A,QU
+        LIS     H'03'
+        NS      R6
+        LR      HL,A
+        LR      W,J
+        NOP
+        OI      H'A0'
+        LISU    4
+        LIS     H'05'
+        ASD     D
+        NS      D
+        AS      R1
+        LR      DC,Q
+        XDC
+        LR      A,R8
+        LIS     H'0E'
+        XS      R8
+        ASD     D
+        LR      KL,A
+        LR      Q,DC
+        LIS     H'0F'
+        LR      HU,A
+        LISL    1
+        ASD     R6
+        ASD     R3
+        LIS     H'07'
+        LISU    7
+        LR      R4,A
+        LIS     H'06'
+        XS      R0
+        LR      A,QU
+        LR      A,IS
+        ASD     R1
+        XS      HU
+        AS      D
+        LR      A,QU
+        AMD
+        DS      J
+        XDC
+        AS      R5
+        ADC
+        LIS     H'08'
+        LR      QU,A
+        LR      R5,A
+        ASD     R7
+        LR      A,IS
+        LIS     H'0F'
+        ADC
+        AS      HU
+        AS      R3
+        LISL    3
+        LR      R5,A
+        XS      R4
+        DS      R8
+        LR      IS,A
+        LR      A,D
+        LNK
+        DI
+        LR      A,R2
+        ADC
+        LR      R2,A
+        DS      J
+        XS      I
+        LR      QL,A
+        AS      D
+        AS      R4
+        LR      QL,A
+        XS      HL
+        AS      R2
+        DS      R5
+        ASD     R4
+        NOP
+        XS      HL
+        LR      IS,A
+        CLR
+        COM
+        SL      4
+        AS      R2
+        DS      R7
+        LNK
+        LR      H,DC
+        DS      S
+        LM
+        LR      QU,A
+        NS      R0
+        LR      A,IS
+        XS      R5
+        AS      R1
+        XS      R8
+        LR      A,HL
+        LR      R0,A
+        XS      R4
+        XM
+        XS      R3
+        XS      R2
+        LR      IS,A
+        ASD     R5
+        LR      A,HU
+        LR      A,R5
+        CM
+        LNK
+        XS      R4
+        LR      A,QL
+        LR      DC,Q
+        OI      H'C7'
+        LR      R1,A
+        DS      R0
+        NS      J
+        AMD
+        AS      R8
+        ASD     R6
+        DS      HU
+        LISU    7
+        XS      R6
+        ASD     I
+        DS      R0
+        LIS     H'0D'
+        DS      R1
+        XS      R3
+        LR      DC,Q
+        LIS     H'06'
+        CI      H'37'
+        XS      R6
+        LISU    6
+        LR      DC,Q
+        LR      P,K
+        LR      S,A
+        AI      H'62'
+        NS      R6
+        LR      A,R2
+        XS      R3
+        NS      R5
+        LR      QU,A
+        ASD     HU
+        XS      R3
+        LISL    0
+        ASD     J
+        CI      H'12'
+        LR      Q,DC
+        NS      R3
+        DS      R5
+        NS      I
+        DS      R0
+        XDC
+        LR      A,HL
+        LR      R1,A
+        XM
+        DS      R0
+        AS      R8
+        ASD     R3
+        ASD     I
+        LR      R0,A
+        LR      A,R8
+        LI      H'5F'
+        DS      J
+        LR      R7,A
+        LISU    4
+        NOP
+        AS      R2
+        COM
+        LIS     H'05'
+        XS      HL
+        XDC
+        LISU    4
+        ASD     R8
+        XS      R8
+        LR      IS,A
+        XS      R8
+        XS      R2
+        AS      J
+        ASD     R7
+        NS      R3
+        XS      D
+        LIS     H'08'
+        LR      A,IS
+        LIS     H'08'
+        CM
+        XS      R1
+        LR      A,QL
+        ASD     I
+        LIS     H'0F'
+        XS      HU
+        LR      A,R1
+        LR      A,QL
+        XS      HL
+        DS      R7
+        XS      I
+        LIS     H'05'
+        SR      1
+        DS      R2
+        LR      A,R8
+        NS      HU
+        DS      R6
+        DS      D
+        LR      J,A
+        AS      R2
+        ASD     R0
+        CLR
+        XS      R3
+        DS      R3
+        DS      HU
+        ASD     R3
+        DS      R7
+        OM
+        LR      R8,A
+        ASD     R7
+        ASD     R4
+        LISL    3
+        XS      D
+        LR      HU,A
+        AS      R0
+        CM
+        LR      H,DC
+        LR      A,J
+        AS      R5
+        DI
+        XDC
+        EI
+        LIS     H'07'
+        ASD     HU
+        LR      A,KU
+        DS      R8
+        LR      A,KU
+        EI
+        LR      A,R4
+        LR      QU,A
+        ASD     J
+        LR      A,J
+        CM
+        NS      R0
+        DS      J
+        XS      R1
+        LR      W,J
+        NS      R0
+        ASD     R2
+        XS      D
+        LR      A,R3
+        LR      A,R6
+        LIS     H'08'
+        LISL    3
+        LR      A,R5
+        LIS     H'08'
+        DS      R6
+        AI      H'E3'
+        LISU    7
+        NOP
+        LR      R8,A
+        DS      I
+        DS      J
+        DS      R8
+        LR      I,A
+        OI      H'D8'
+        XS      R8
+        LR      S,A
+        LISU    6
+        SL      4
+        LR      R1,A
+        LR      R0,A
+        LISU    0
+        ASD     R2
+        NS      R7
+        NS      R6
+        LIS     H'01'
+        LISU    0
+        LR      A,R8
+        NS      R0
+        NS      HL
+        XS      J
+        NS      HL
+        XS      R0
+        LR      D,A
+        NS      HL
+        LR      S,A
+        LIS     H'06'
+        NS      I
+        DS      R5
+        LIS     H'0C'
+        ASD     D
+        CI      H'A8'
+        LISL    0
+        INC
+        XS      R7
+        CI      H'B7'
+        LR      R1,A
+        XI      H'0C'
+        LIS     H'05'
+        LR      KU,A
+        LIS     H'08'
+        LR      A,QU
+        LISL    4
+        LR      Q,DC
+        ASD     R0
+        NM
+        LR      A,R2
+        LISU    7
+        CI      H'09'
+        DS      R5
+        LISU    0
+        LR      A,R2
+        XS      R5
+        XS      R4
+        NS      HU
+        LR      R6,A
+        LISL    2
+        AS      HL
+        DS      R6
+        DS      R7
+        LISU    3
+        ASD     R0
+        XI      H'5E'
+        XS      R6
+        INC
+        AS      R0
+        LIS     H'07'
+        LR      I,A
+        NS      HL
+        LIS     H'0B'
+        LISU    6
+        AM
+        LISU    2
+        XI      H'3B'
+        AS      R4
+        NS      I
+        LR      HL,A
+        XS      R4
+        ASD     R0
+        LR      A,QU
+        LR      W,J
+        LR      A,R3
+        LR      HL,A
+        LR      A,HU
+        LR      R1,A
+        ASD     HL
+        XS      I
+        LR      R4,A
+        LR      HU,A
+        LR      A,R1
+        LISU    4
+        LISL    4
+        DS      HU
+        AS      R0
+        NM
+        LR      A,J
+        LISU    6
+        LISU    6
+        LISU    0
+        LR      J,W
+        LISL    7
+        DS      S
+        DS      R6
+        AS      R4
+        AMD
+        DS      D
+        LR      HL,A
+        LIS     H'06'
+        ASD     J
+        LISL    1
+        LISU    3
+        LISL    2
+        DI
+        LR      J,A
+        DI
+        LISU    7
+        AS      HU
+        AS      D
+        DS      R0
+        LIS     H'03'
+        LI      H'43'
+        LR      R0,A
+        LR      QU,A
+        DS      R1
+        NS      R4
+        AS      D
+        AM
+        LR      HU,A
+        CM
+        LR      A,IS
+        LIS     H'0E'
+        INC
+        LR      A,R3
+        LISL    7
+        ASD     R1
+        DS      S
+        NS      R6
+        DS      HL
+        LR      A,R4
+        CI      H'43'
+        LR      A,R4
+        LR      DC,Q
+        SL      4
+        LI      H'BD'
+        LR      A,R1
+        LR      QL,A
+        LR      KU,A
+        LR      QL,A
+        LISL    7
+        LR      J,A
+        LR      A,HL
+        AS      HU
+        LR      A,HL
+        LISU    7
+        DS      R7
+        CLR
+        AS      J
+        XDC
+        AS      R4
+        NOP
+        LR      A,QU
+        NS      R6
+        LISU    1
+        NS      S
+        LR      A,R8
+        LR      A,I
+        XS      R1
+        NS      R5
+        DS      D
+        LR      I,A
+        LIS     H'0D'
+        XS      I
+        LR      A,R4
+        LISL    3
+        AS      R5
+        AS      R4
+        NS      R3
+        CM
+        NS      R5
+        XS      I
+        AS      R7
+        SR      4
+        LR      A,HU
+        LR      A,R0
+        LISU    6
+        LISU    2
+        DS      D
+        OI      H'35'
+        DS      R1
+        NS      R0
+        LISU    4
+        LISL    2
+        AI      H'31'
+        LR      KL,A
+        LISU    5
+        AS      HU
+        AS      J
+        AM
+        LIS     H'03'
+        AS      R2
+        XS      J
+        AS      D
+        XS      R8
+        LIS     H'03'
+        XS      R6
+        LR      R2,A
+        LIS     H'06'
+        DS      R6
+        CM
+        XS      R3
+        LR      A,IS
+        LR      HU,A
+        LR      I,A
+        LR      A,HL
+        LR      A,HU
+        LIS     H'0B'
+        LR      HU,A
+        LR      QU,A
+        AS      R1
+        ASD     S
+        LR      DC,H
+        OI      H'0B'
+        LISL    5
+        XS      I
+        LR      A,R1
+        LISL    7
+        LR      A,R1
+        XS      R2
+        NS      I
+        LIS     H'03'
+        NM
+        LIS     H'0E'
+        AI      H'5F'
+        NS      R6
+        AS      I
+        LISU    5
+        NS      R5
+        NS      J
+        AM
+        LISL    2
+        LR      R6,A
+        LISU    2
+        LR      A,R5
+        DS      D
+        AS      R3
+        LIS     H'0E'
+        LISU    2
+        LR      W,J
+        SR      4
+        LISL    5
+        DS      R5
+        LR      R5,A
+        LR      K,P
+        NS      R2
+        LR      A,KL
+        LM
+        SL      4
+        XS      R4
+        LR      A,R8
+        AS      R5
+        XS      R8
+        ASD     R6
+        LR      J,A
+        NS      HL
+        LR      R0,A
+        LR      J,W
+        LIS     H'06'
+        LR      I,A
+        LR      A,R0
+        LISU    5
+        XS      R7
+        LR      J,W
+        LR      R2,A
+        CM
+        NS      J
+        XS      R3
+        LR      A,KL
+        AM
+        LR      A,D
+        ASD     J
+        LISL    7
+        DS      R7
+        INC
+        ASD     R5
+        NS      HU
+        AM
+        LISL    4
+        XM
+        DS      R1
+        DS      R4
+        NS      R3
+        LR      A,KU
+        EI
+        LISL    6
+        DS      R6
+        DS      R2
+        ASD     R4
+        SR      1
+        ASD     I
+        AI      H'E8'
+        LIS     H'02'
+        XS      S
+        DS      R4
+        LISL    6
+        NI      H'5A'
+        ASD     R0
+        LR      R4,A
+        DS      R7
+        SL      1
+        LR      R4,A
+        XS      R4
+        XS      HU
+        SL      1
+        NS      R6
+        LR      A,R5
+        AS      R3
+        XS      R1
+        NS      R2
+        DI
+        LR      QL,A
+        CM
+        NS      R3
+        LISL    7
+        LR      A,R7
+        LR      DC,H
+        AS      R3
+        NS      R8
+        LIS     H'05'
+        SL      1
+        AS      R2
+        DS      HU
+        DS      S
+        LISL    0
+        DS      R8
+        AS      R5
+        NS      J
+        LR      J,A
+        AM
+        LIS     H'0E'
+        LR      R3,A
+        LIS     H'07'
+        NS      HU
+        DI
+        DS      R3
+        LISU    4
+        LR      R0,A
+        LR      R1,A
+        LR      DC,H
+        LISL    5
+        LR      A,QU
+        NS      R8
+        LR      R6,A
+        AS      R5
+        LR      R2,A
+        DS      HU
+        AMD
+        NS      R1
+        ASD     I
+        ASD     R7
+        LR      A,R0
+        DS      R2
+        AS      R1
+        ADC
+        XM
+        DS      D
+        LR      A,HU
+        LR      K,P
+        LR      K,P
+        XS      R6
+        AS      J
+        AS      R3
+        LIS     H'0C'
+        LR      A,R5
+        LIS     H'08'
+        NS      HL
+        LR      A,S
+        LR      KL,A
+        LR      D,A
+        LR      A,D
+        COM
+        AS      R8
+        OM
+        LISU    2
+        LR      IS,A
+        LR      KU,A
+        LISU    4
+        LR      HL,A
+        LR      A,QL
+        LR      H,DC
+        DS      R5
+        ASD     J
+        LR      IS,A
+        LIS     H'02'
+        LR      A,HL
+        NS      R8
+        ASD     R3
+        SR      1
+        LR      S,A
+        DS      D
+        LIS     H'02'
+        ASD     R8
+        NI      H'B0'
+        LR      J,W
+        LR      QU,A
+        LISU    5
+        DS      R7
+        LR      A,R2
+        LR      A,QL
+        LIS     H'09'
+        XS      R0
+        LR      R5,A
+        DS      R5
+        NS      R8
+        LR      R1,A
+        AS      R6
+        ASD     HU
+        LISL    6
+        XS      HL
+        NS      R6
+        LR      KU,A
+        LR      A,R6
+        XS      J
+        NS      R7
+        LISU    5
+        INC
+        LR      R2,A
+        LR      D,A
+        XM
+        ASD     R0
+        DS      I
+        SR      1
+        LR      QU,A
+        XS      HU
+        LIS     H'0F'
+        LR      A,HU
+        LR      P,K
+        ASD     R5
+        CM
+        AS      HU
+        LR      A,QU
+        LR      HU,A
+        LISU    1
+        ASD     R5
+        LR      D,A
+        LR      I,A
+        ASD     S
+        LR      R0,A
+        DS      R1
+        LIS     H'0C'
+        DS      R1
+        ADC
+        LR      HL,A
+        AS      R8
+        XS      HL
+        NS      R5
+        LR      A,D
+        LISL    1
+        XS      R8
+        LISL    7
+        DI
+        XS      S
+        LR      A,QL
+        NS      D
+        ASD     R2
+        LISU    2
+        LIS     H'0E'
+        XS      R3
+        AS      J
+        LIS     H'07'
+        LIS     H'05'
+        LR      R1,A
+        LISU    0
+        NS      R2
+        XS      R3
+        LR      A,HL
+        DS      R8
+        AS      R8
+        LI      H'E7'
+        LR      W,J
+        LR      A,R7
+        XI      H'BD'
+        XM
+        XS      R0
+        NS      S
+        AS      R8
+        NS      R2
+        LR      P,K
+        LR      S,A
+        LIS     H'0A'
+        NS      D
+        NM
+        LIS     H'0A'
+        LR      HU,A
+        LM
+        LR      IS,A
+        LISL    4
+        LR      R7,A
+        LISL    2
+        XS      R4
+        LIS     H'03'
+        DS      I
+        NS      R2
+        LR      A,R3
+        ASD     R8
+        LISL    5
+        NS      HU
+        LIS     H'0C'
+        DS      R6
+        LR      IS,A
+        XS      R0
+        LR      IS,A
+        LISU    0
+        CM
+        AS      R2
+        SL      4
+        NS      R2
+        LR      A,KL
+        LR      KU,A
+        AS      R7
+        ASD     R2
+        XM
+        LR      A,R0
+        NM
+        SR      4
+        NS      HU
+        LIS     H'01'
+        LR      QL,A
+        LIS     H'0D'
+        NS      HL
+        XS      R4
+        SL      1
+        LR      QL,A
+        LIS     H'0E'
+        AS      R0
+        AS      HL
+        LR      A,R6
+        CI      H'24'
+        ASD     J
+        DS      HU
+        XS      S
+        ASD     R5
+        LM
+        LR      A,R8
+        AS      HU
+        LIS     H'0B'
+        LR      KU,A
+        XS      R2
+        ASD     R6
+        AS      R2
+        LIS     H'0F'
+        LR      R7,A
+        AS      R3
+        LR      A,HL
+        NS      R1
+        LISL    5
+        LISL    5
+        LR      KU,A
+        DS      R4
+        XS      HL
+        LISU    6
+        DS      R7
+        DS      R0
+        XM
+        NS      R7
+        NS      R2
+        LR      KL,A
+        SL      4
+        DS      R3
+        LISU    7
+        XS      I
+        AMD
+        CI      H'C4'
+        DS      I
+        LR      J,W
+        DS      R5
+        LISU    3
+        NOP
+        DS      R5
+        SR      4
+        LISU    2
+        XS      R1
+        DS      D
+        AMD
+        LR      A,R3
+        XS      R6
+        LR      K,P
+        XS      R5
+        NS      R4
+        LR      A,QL
+        XS      R7
+        LR      A,R6
+        CI      H'FE'
+        LR      QU,A
+        LR      W,J
+        LR      A,KL
+        ASD     R2
+        LR      R7,A
+        OM
+        XI      H'1C'
+        LR      KL,A
+        LR      W,J
+        ASD     R1
+        LISU    4
+        LR      A,KU
+        DS      R5
+        XS      R2
+        LNK
+        LR      A,S
+        LR      A,I
+        DS      R7
+        LI      H'EF'
+        LR      A,R0
+        XS      HL
+        AS      R1
+        NS      R5
+        LR      Q,DC
+        LISL    5
+        NS      R6
+        ASD     R3
+        NS      R6
+        ASD     R0
+        LIS     H'0C'
+        LISU    6
+        DS      HL
+        LR      A,J
+        LIS     H'0E'
+        LR      A,HL
+        LR      R8,A
+        AS      R0
+        XS      R0
+        LR      DC,H
+        LR      Q,DC
+        XS      S
+        AS      R3
+        NS      J
+        ASD     HU
+        LR      A,IS
+        COM
+        LR      A,I
+        NM
+        OI      H'EC'
+        LR      A,S
+        LR      A,S
+        LR      D,A
+        XS      HU
+        CM
+        LISL    7
+        LR      Q,DC
+        XS      HL
+        INC
+        LISU    0
+        AS      R2
+        LISL    2
+        ASD     R6
+        LR      A,IS
+        LR      J,W
+        XS      R0
+        AI      H'DD'
+        LR      A,R5
+        NS      HL
+        LR      R1,A
+        DS      R4
+        NM
+        DS      R3
+        LR      HU,A
+        AS      J
+        AS      R3
+        LIS     H'02'
+        AS      R6
+        NS      R4
+        LR      KU,A
+        LISL    3
+        XS      R0
+        LR      R6,A
+        LIS     H'01'
+        ASD     R3
+        AS      S
+        ASD     R7
+        LR      R6,A
+        LIS     H'03'
+        LIS     H'09'
+        AS      D
+        LIS     H'0F'
+        LISU    1
+        ASD     R3
+        AS      HL
+        LISL    7
+        XS      R6
+        DS      R6
+        XS      R4
+        ASD     R0
+        LIS     H'0F'
+        NS      R5
+        LIS     H'0D'
+        LR      A,KU
+        LIS     H'04'
+        LR      R2,A
+        NOP
+        LR      HL,A
+        LR      IS,A
+        LIS     H'0C'
+        NS      D
+        ASD     R3
+        LIS     H'05'
+        NS      R8
+        XS      D
+        ASD     S
+        LISU    6
+        NS      R8
+        LR      KL,A
+        XS      R2
+        LR      R6,A
+        AS      J
+        XM
+        NS      S
+        LIS     H'06'
+        LR      J,A
+        AS      R4
+        DS      R6
+        LR      W,J
+        LR      R6,A
+        LIS     H'08'
+        AM
+        NS      R6
+        LIS     H'0D'
+        LIS     H'03'
+        LISL    7
+        AM
+        LR      J,A
+        LISL    5
+        LR      A,R2
+        LR      A,R4
+        XS      R2
+        LR      A,S
+        XS      D
+        LIS     H'08'
+        DS      S
+        XDC
+        NS      R4
+        DS      HU
+        NM
+        XS      R1
+        XDC
+        OI      H'03'
+        ASD     I
+        NS      R1
+        LR      W,J
+        LISU    0
+        XM
+        LR      A,S
+        LIS     H'0F'
+        LR      R3,A
+        LR      R5,A
+        NS      R0
+        NS      R1
+        NS      R0
+        ASD     R4
+        NS      J
+        XS      R8
+        SR      1
+        LR      A,D
+        NS      S
+        XS      S
+        DS      HU
+        NS      R0
+        LR      A,R2
+        DS      R4
+        XS      R7
+        DS      R7
+        LR      DC,Q
+        NS      R7
+        LR      I,A
+        LR      A,J
+        NS      R0
+        XS      R1
+        NS      HU
+        ASD     R1
+        LR      A,D
+        LR      P,K
+        AS      HU
+        LR      A,R7
+        LR      A,S
+        NS      J
+        LISU    2
+        CLR
+        LR      K,P
+        DS      R1
+        NS      R2
+        DS      HU
+        OM
+        DI
+        XS      R7
+        LIS     H'01'
+        NS      R7
+        EI
+        NS      R5
+        LIS     H'0D'
+        LISL    1
+        NS      R6
+        ADC
+        DS      R8
+        LR      HU,A
+        XS      R3
+        LISL    3
+        LR      A,R5
+        NS      R0
+        NS      HU
+        LIS     H'03'
+        NS      R4
+        LR      HU,A
+        NS      R3
+        LISU    0
+        ASD     R8
+        XS      R3
+        XS      R3
+        LIS     H'07'
+        LIS     H'05'
+        DS      I
+        LR      A,S
+        LIS     H'0D'
+        LR      A,R7
+        XDC
+        XS      HL
+        OI      H'4B'
+        DS      R1
+        LR      R2,A
+        AS      S
+        ADC
+        DS      S
+        LR      R6,A
+        LISU    0
+        LR      R1,A
+        LR      A,KU
+        AS      I
+        LR      R0,A
+        LISL    7
+        ASD     R5
+        AM
+        AS      HU
+        XS      R3
+        AS      J
+        LR      W,J
+        DS      R1
+        LR      I,A
+        NS      I
+        LR      A,R0
+        AS      R3
+        XI      H'9B'
+        LR      P,K
+        LISL    3
+        AS      I
+        LISL    6
+        ASD     R6
+        LR      A,D
+        LISU    0
+        LR      R4,A
+        LIS     H'06'
+        XS      I
+        AM
+        LR      A,IS
+        LR      R1,A
+        SL      1
+        LR      S,A
+        AS      HU
+        LISU    1
+        ASD     HL
+        AS      J
+        LR      A,J
+        DS      D
+        LIS     H'08'
+        SL      1
+        AS      R3
+        LR      A,I
+        SR      1
+        NS      R2
+        XS      S
+        ASD     R0
+        LIS     H'02'
+        ASD     R5
+        DS      J
+        ASD     S
+        XI      H'95'
+        AM
+        LISU    2
+        LISL    2
+        DS      R4
+        DS      R4
+        SL      1
+        LISL    2
+        NI      H'09'
+        LIS     H'09'
+        LISL    4
+        EI
+        NS      I
+        LR      A,R1
+        LR      A,QL
+        AM
+        ASD     R1
+        LR      D,A
+        DS      HU
+        OM
+        LIS     H'0C'
+        CI      H'07'
+        LR      A,R0
+        LIS     H'0E'
+        LISL    4
+        LIS     H'04'
+        AMD
+        LI      H'C9'
+        ASD     S
+        CLR
+        DS      HU
+        LR      K,P
+        AS      R8
+        LR      A,R1
+        LR      A,R2
+        LIS     H'07'
+        DS      I
+        DS      R0
+        LIS     H'02'
+        LR      R6,A
+        AS      J
+        XS      R8
+        LR      D,A
+        DS      R8
+        NS      J
+        XS      I
+        ASD     J
+        LIS     H'0B'
+        LR      R1,A
+        LISU    0
+        LR      KU,A
+        CI      H'63'
+        AS      R8
+        ASD     I
+        LR      A,R0
+        LR      R2,A
+        LM
+        EI
+        LR      A,R1
+        AS      HU
+        LR      A,R8
+        LR      A,HU
+        ASD     R8
+        ASD     R7
+        DS      HU
+        LR      J,W
+        NS      R2
+        ASD     R2
+        LIS     H'0C'
+        ASD     I
+        ASD     R2
+        ASD     R7
+        ASD     R0
+        AS      R0
+        AS      R2
+        XS      R4
+        LIS     H'03'
+        AS      R2
+        AS      R3
+        LR      J,A
+        LR      A,HU
+        LR      R2,A
+        AS      R7
+        NI      H'B5'
+        ASD     J
+        XS      R5
+        LR      D,A
+        NS      R0
+        LR      W,J
+        NS      R1
+        XS      J
+        NS      R0
+        XS      R6
+        LIS     H'0F'
+        LR      I,A
+        XS      R8
+        NS      R4
+        LISU    4
+        XS      R7
+        ASD     J
+        NS      R7
+        LR      D,A
+        DS      HL
+        LR      QU,A
+        COM
+        ASD     R6
+        AI      H'05'
+        LR      R0,A
+        XS      HU
+        XS      R8
+        XS      R1
+        AS      HL
+        XS      R1
+        ADC
+        LR      A,J
+        LR      A,R8
+        DS      R1
+        LISL    3
+        LISL    2
+        AS      J
+        AS      HU
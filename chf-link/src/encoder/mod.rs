@@ -0,0 +1,309 @@
+//! Coalesces single pixels into larger commands
+//!
+//! The BIOS and most games draw in horizontal runs, row by row. Consecutive pixels on a row
+//! become a `Run` - or a `FillRect` if they all have the same color. A run of one color right
+//! below a rectangle of the same color and width makes the rectangle taller.
+//!
+//! Pixels are held back until the run they belong to ends, so call `flush` at the end of every
+//! frame.
+
+use crate::{is_visible, Command, PALETTE_COLUMNS, WIDTH};
+
+struct Run {
+    x: u8,
+    y: u8,
+    len: u8,
+    pixels: [u8; WIDTH],
+}
+
+struct Rect {
+    x: u8,
+    y: u8,
+    width: u8,
+    height: u8,
+    color: u8,
+}
+
+pub struct Encoder {
+    run: Option<Run>,
+    rect: Option<Rect>,
+}
+
+impl Encoder {
+    pub fn new() -> Encoder {
+        Encoder {
+            run: None,
+            rect: None,
+        }
+    }
+
+    /// Feeds a pixel written by the emulator, `send` is called for every complete command
+    ///
+    /// Pixels which are not visible are dropped.
+    pub fn pixel<F: FnMut(&Command)>(&mut self, x: u8, y: u8, color: u8, send: &mut F) {
+        if PALETTE_COLUMNS.contains(&x) {
+            self.flush(send);
+            send(&Command::SetPalette { x, y, value: color });
+            return;
+        }
+
+        if !is_visible(x, y) {
+            return;
+        }
+
+        if let Some(run) = &mut self.run {
+            if run.y == y && run.x + run.len == x {
+                run.pixels[run.len as usize] = color;
+                run.len += 1;
+                return;
+            }
+        }
+
+        self.flush_run(send);
+
+        let mut pixels = [0u8; WIDTH];
+        pixels[0] = color;
+        self.run = Some(Run {
+            x,
+            y,
+            len: 1,
+            pixels,
+        });
+    }
+
+    /// Sends everything held back
+    pub fn flush<F: FnMut(&Command)>(&mut self, send: &mut F) {
+        self.flush_run(send);
+        self.flush_rect(send);
+    }
+
+    fn flush_run<F: FnMut(&Command)>(&mut self, send: &mut F) {
+        let run = match self.run.take() {
+            Some(run) => run,
+            None => return,
+        };
+
+        let color = run.pixels[0];
+        if run.pixels[..run.len as usize].iter().all(|p| *p == color) {
+            if let Some(rect) = &mut self.rect {
+                if rect.x == run.x
+                    && rect.width == run.len
+                    && rect.color == color
+                    && rect.y + rect.height == run.y
+                {
+                    rect.height += 1;
+                    return;
+                }
+            }
+
+            self.flush_rect(send);
+            self.rect = Some(Rect {
+                x: run.x,
+                y: run.y,
+                width: run.len,
+                height: 1,
+                color,
+            });
+        } else {
+            self.flush_rect(send);
+            send(&Command::Run {
+                x: run.x,
+                y: run.y,
+                len: run.len,
+                pixels: run.pixels,
+            });
+        }
+    }
+
+    fn flush_rect<F: FnMut(&Command)>(&mut self, send: &mut F) {
+        let rect = match self.rect.take() {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        if rect.width == 1 && rect.height == 1 {
+            send(&Command::SetPixel {
+                x: rect.x,
+                y: rect.y,
+                color: rect.color,
+            });
+        } else {
+            send(&Command::FillRect {
+                x: rect.x,
+                y: rect.y,
+                width: rect.width,
+                height: rect.height,
+                color: rect.color,
+            });
+        }
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Encoder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use core::cell::RefCell;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::{Decoder, HEIGHT, MAX_FRAME_LEN};
+    use chf_emulator::{bios, ChannelF, Cpu, Key, Sound};
+
+    struct RecordingChannelF {
+        pixels: RefCell<Vec<(u8, u8, u8)>>,
+    }
+
+    impl ChannelF for RecordingChannelF {
+        fn sound(&self, _frequency: Sound) {}
+
+        fn set_pixel(&self, x: u8, y: u8, value: u8) {
+            self.pixels.borrow_mut().push((x, y, value));
+        }
+
+        fn key_pressed(&self, _key: Key) -> bool {
+            false
+        }
+    }
+
+    // what the video MCU would show
+    fn apply(screen: &mut [u8; WIDTH * HEIGHT], command: &Command) {
+        let mut set = |x: u8, y: u8, color: u8| {
+            screen[y as usize * WIDTH + x as usize] = color;
+        };
+
+        match *command {
+            Command::SetPixel { x, y, color } => set(x, y, color),
+            Command::SetPalette { x, y, value } => set(x, y, value),
+            Command::Clear { color } => {
+                for y in 0..HEIGHT as u8 {
+                    for x in 0..WIDTH as u8 {
+                        set(x, y, color);
+                    }
+                }
+            }
+            Command::Row { y, pixels } => {
+                for (x, pixel) in pixels.iter().enumerate() {
+                    set(x as u8, y, *pixel);
+                }
+            }
+            Command::FillRect {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => {
+                for yy in y..y + height {
+                    for xx in x..x + width {
+                        set(xx, yy, color);
+                    }
+                }
+            }
+            Command::Run { x, y, len, pixels } => {
+                for (idx, pixel) in pixels[..len as usize].iter().enumerate() {
+                    set(x + idx as u8, y, *pixel);
+                }
+            }
+            Command::FrameMode => {}
+        }
+    }
+
+    /// Sends the pixels one by one and coalesced, returns the number of bytes sent for both
+    /// and checks both end up with the same screen
+    fn compare(pixels: &[(u8, u8, u8)]) -> (usize, usize) {
+        let mut expected = [0u8; WIDTH * HEIGHT];
+        let mut single_bytes = 0;
+        let mut buffer = [0u8; MAX_FRAME_LEN];
+        for (x, y, color) in pixels.iter().cloned() {
+            if is_visible(x, y) || PALETTE_COLUMNS.contains(&x) {
+                let command = Command::pixel(x, y, color);
+                single_bytes += command.encode(&mut buffer).len();
+                apply(&mut expected, &command);
+            }
+        }
+
+        let mut screen = [0u8; WIDTH * HEIGHT];
+        let mut decoder = Decoder::new();
+        let mut coalesced_bytes = 0;
+        let mut send = |command: &Command| {
+            let mut buffer = [0u8; MAX_FRAME_LEN];
+            for byte in command.encode(&mut buffer) {
+                coalesced_bytes += 1;
+                if let Some(command) = decoder.push(*byte).unwrap() {
+                    apply(&mut screen, &command);
+                }
+            }
+        };
+
+        let mut encoder = Encoder::new();
+        for (x, y, color) in pixels.iter().cloned() {
+            encoder.pixel(x, y, color, &mut send);
+        }
+        encoder.flush(&mut send);
+
+        assert_eq!(expected[..], screen[..]);
+        (single_bytes, coalesced_bytes)
+    }
+
+    #[test]
+    fn coalesce() {
+        let mut pixels = Vec::new();
+
+        // clear the screen row by row, palette columns included
+        for y in 0..64 {
+            for x in 0..128 {
+                pixels.push((x, y, 3));
+            }
+        }
+        // some text
+        for y in 10..15 {
+            for x in 10..40 {
+                pixels.push((x, y, ((x ^ y) & 1) * 2));
+            }
+        }
+        // a vertical line, a single dot and an overwritten pixel
+        for y in 20..30 {
+            pixels.push((50, y, 1));
+        }
+        pixels.push((60, 40, 2));
+        pixels.push((60, 40, 1));
+        pixels.push((61, 40, 3));
+        // new palette for a row in the middle of a run
+        pixels.push((20, 50, 2));
+        pixels.push((125, 50, 2));
+        pixels.push((21, 50, 2));
+
+        let (single, coalesced) = compare(&pixels);
+        assert!(coalesced * 10 < single);
+    }
+
+    #[test]
+    fn bios_boot_screen() {
+        let channel_f = RecordingChannelF {
+            pixels: RefCell::new(Vec::new()),
+        };
+        let (rom_0000, rom_0400) = bios::free_bios();
+        let mut cpu = Cpu::new(rom_0000, rom_0400, &[], &channel_f);
+        cpu.reset();
+
+        for _ in 0..300 {
+            cpu.run_frame();
+        }
+
+        let pixels = channel_f.pixels.borrow();
+        let (single, coalesced) = compare(&pixels);
+        std::println!(
+            "boot screen: {} pixels, {} bytes one by one, {} bytes coalesced",
+            pixels.len(),
+            single,
+            coalesced
+        );
+        assert!(coalesced * 4 < single);
+    }
+}
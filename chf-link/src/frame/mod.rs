@@ -0,0 +1,238 @@
+//! Full frame mode
+//!
+//! Instead of sending commands the main MCU keeps the VRAM of the emulator and sends all rows
+//! written during a frame as one block. The block is sent via DMA while the video MCU doesn't
+//! draw pixel lines (busy line low), so the video MCU can receive it via DMA, too.
+//!
+//! |Bytes|Contents|
+//! |---|---|
+//! |1|`BLOCK_START`|
+//! |1|number of rows|
+//! |33 per row|row number + the pixels packed like the emulator's VRAM: four 2 bit pixels per byte, first pixel in bits 0 - 1|
+//! |1|CRC-8 of everything before|
+//!
+//! Blocks are not acknowledged, a broken block is dropped. To repair that the main MCU adds one
+//! more row to every block, going through all rows one frame after the other.
+//!
+//! The video MCU keeps answering commands after acknowledging `FrameMode`, the first
+//! `BLOCK_START` switches it over - that block is dropped.
+
+use crate::{crc8, HEIGHT, WIDTH};
+
+/// First byte of a block
+pub const BLOCK_START: u8 = 0xb5;
+
+/// Bytes of a packed row
+pub const PACKED_ROW_LEN: usize = WIDTH / 4;
+
+/// A block with every row
+pub const MAX_BLOCK_LEN: usize = 2 + HEIGHT * (1 + PACKED_ROW_LEN) + 1;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BlockError {
+    /// The block doesn't start with `BLOCK_START` or has more rows than the screen
+    BadHeader,
+    /// The block is shorter than its header says
+    TooShort,
+    /// A row number is out of range
+    BadRow,
+    BadCrc,
+}
+
+/// Puts the rows set in `rows` (bit n = row n) into a block
+pub fn encode_block<'a>(
+    vram: &[u8; WIDTH * HEIGHT / 4],
+    rows: u64,
+    buffer: &'a mut [u8; MAX_BLOCK_LEN],
+) -> &'a [u8] {
+    buffer[0] = BLOCK_START;
+    buffer[1] = rows.count_ones() as u8;
+
+    let mut len = 2;
+    for y in (0..HEIGHT).filter(|y| rows & (1 << y) != 0) {
+        buffer[len] = y as u8;
+        buffer[len + 1..][..PACKED_ROW_LEN]
+            .copy_from_slice(&vram[y * PACKED_ROW_LEN..][..PACKED_ROW_LEN]);
+        len += 1 + PACKED_ROW_LEN;
+    }
+
+    buffer[len] = crc8(&buffer[..len]);
+    &buffer[..len + 1]
+}
+
+/// Length of the whole block, needs the first two bytes
+pub fn block_len(header: &[u8]) -> Result<usize, BlockError> {
+    if header.len() < 2 {
+        return Err(BlockError::TooShort);
+    }
+
+    if header[0] != BLOCK_START || header[1] as usize > HEIGHT {
+        return Err(BlockError::BadHeader);
+    }
+
+    Ok(2 + header[1] as usize * (1 + PACKED_ROW_LEN) + 1)
+}
+
+/// Checks a block and calls `row` with every row unpacked, returns the number of rows
+///
+/// Nothing is called if the block is broken.
+pub fn decode_block<F: FnMut(u8, &[u8; WIDTH])>(
+    block: &[u8],
+    mut row: F,
+) -> Result<usize, BlockError> {
+    let len = block_len(block)?;
+    if block.len() < len {
+        return Err(BlockError::TooShort);
+    }
+
+    if crc8(&block[..len - 1]) != block[len - 1] {
+        return Err(BlockError::BadCrc);
+    }
+
+    let rows = block[2..len - 1].chunks(1 + PACKED_ROW_LEN);
+    if rows.clone().any(|packed| packed[0] as usize >= HEIGHT) {
+        return Err(BlockError::BadRow);
+    }
+
+    let mut pixels = [0u8; WIDTH];
+    for packed in rows {
+        unpack_row(&packed[1..], &mut pixels);
+        row(packed[0], &pixels);
+    }
+
+    Ok(block[1] as usize)
+}
+
+/// Unpacks four pixels per byte, first pixel in bits 0 - 1
+pub fn unpack_row(packed: &[u8], pixels: &mut [u8; WIDTH]) {
+    for (x, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = (packed[x / 4] >> ((x % 4) * 2)) & 0b11;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::{
+        screen::{Screen, COLORS},
+        PALETTE_COLUMNS,
+    };
+    use chf_emulator::{
+        state::{SliceReader, SliceWriter, STATE_LEN},
+        test_util::NullChannelF,
+        Cpu,
+    };
+
+    fn no_rows(_y: u8, _pixels: &[u8; WIDTH]) {
+        unreachable!("broken blocks must not be applied");
+    }
+
+    // loads a state with every pixel set to something different, palette columns included
+    fn load_screen(cpu: &mut Cpu) {
+        let mut state = vec![0u8; STATE_LEN];
+        cpu.save_state(&mut SliceWriter::new(&mut state)).unwrap();
+
+        let vram_start = STATE_LEN - WIDTH * HEIGHT / 4;
+        for (idx, byte) in state[vram_start..].iter_mut().enumerate() {
+            *byte = (idx * 37 + idx / 32) as u8;
+        }
+
+        cpu.load_state(&mut SliceReader::new(&state)).unwrap();
+    }
+
+    #[test]
+    fn round_trip() {
+        let channel_f = NullChannelF;
+        let mut cpu = Cpu::new(&[], &[], &[], &channel_f);
+        load_screen(&mut cpu);
+
+        let rows = cpu.take_dirty_rows();
+        let mut buffer = [0u8; MAX_BLOCK_LEN];
+        let block = encode_block(cpu.vram(), rows, &mut buffer);
+        assert_eq!(MAX_BLOCK_LEN, block.len());
+        assert_eq!(Ok(block.len()), block_len(&block[..2]));
+
+        let mut vid_ram = [0u8; WIDTH * HEIGHT];
+        let mut indexed_pixels = [0u8; WIDTH * HEIGHT];
+        {
+            let mut screen = Screen::new(&mut vid_ram, &mut indexed_pixels);
+            let count = decode_block(block, |y, pixels| screen.apply_row(y, 0, pixels));
+            assert_eq!(Ok(HEIGHT), count);
+        }
+
+        let mut palettes = 0u8;
+        for y in 0..HEIGHT as u8 {
+            let palette = chf_emulator::palette(cpu.pixel(125, y), cpu.pixel(126, y));
+            palettes |= 1 << palette;
+
+            for x in 0..WIDTH as u8 {
+                let idx = y as usize * WIDTH + x as usize;
+                let pixel = cpu.pixel(x, y);
+                assert_eq!(pixel, indexed_pixels[idx]);
+
+                let color = if PALETTE_COLUMNS.contains(&x) {
+                    pixel
+                } else {
+                    COLORS[pixel as usize + palette * 4]
+                };
+                assert_eq!(color, vid_ram[idx], "x {} y {}", x, y);
+            }
+        }
+        // every palette was used
+        assert_eq!(0b1111, palettes);
+    }
+
+    #[test]
+    fn dirty_rows_only() {
+        let channel_f = NullChannelF;
+        let mut cpu = Cpu::new(&[], &[], &[], &channel_f);
+        load_screen(&mut cpu);
+
+        let mut buffer = [0u8; MAX_BLOCK_LEN];
+        let block = encode_block(cpu.vram(), 1 << 3 | 1 << 63, &mut buffer);
+        assert_eq!(2 + 2 * 33 + 1, block.len());
+
+        let mut rows = Vec::new();
+        decode_block(block, |y, pixels| {
+            for (x, pixel) in pixels.iter().enumerate() {
+                assert_eq!(cpu.pixel(x as u8, y), *pixel);
+            }
+            rows.push(y);
+        })
+        .unwrap();
+        assert_eq!(vec![3, 63], rows);
+
+        let block = encode_block(cpu.vram(), 0, &mut buffer);
+        assert_eq!(Ok(0), decode_block(block, no_rows));
+    }
+
+    #[test]
+    fn broken_blocks() {
+        let vram = [0x1bu8; WIDTH * HEIGHT / 4];
+        let mut buffer = [0u8; MAX_BLOCK_LEN];
+        let block = encode_block(&vram, 0b111, &mut buffer).to_vec();
+
+        for idx in 0..block.len() {
+            let mut broken = block.clone();
+            broken[idx] ^= 0x04;
+            assert!(decode_block(&broken, no_rows).is_err());
+        }
+
+        assert_eq!(
+            Err(BlockError::TooShort),
+            decode_block(&block[..block.len() - 1], no_rows)
+        );
+        assert_eq!(Err(BlockError::TooShort), block_len(&block[..1]));
+        assert_eq!(Err(BlockError::BadHeader), block_len(&[BLOCK_START, 65]));
+        assert_eq!(Err(BlockError::BadHeader), block_len(&[0x80, 1]));
+
+        let mut bad_row = block.clone();
+        bad_row[2] = 64;
+        let len = bad_row.len();
+        bad_row[len - 1] = crc8(&bad_row[..len - 1]);
+        assert_eq!(Err(BlockError::BadRow), decode_block(&bad_row, no_rows));
+    }
+}
@@ -0,0 +1,661 @@
+//! The protocol used between the main MCU and the video MCU
+//!
+//! The main MCU sends commands via SPI, each followed by its CRC-8 (polynomial 0x07) split into
+//! two bytes, high nibble first. The video MCU acknowledges every command, see `packet`.
+//!
+//! Every command starts with a byte which has bit 7 set, all following bytes of the command have
+//! bit 7 cleared. That way the receiver can always find the start of the next command after
+//! losing bytes.
+//!
+//! |Command|Bytes|
+//! |---|---|
+//! |set pixel|`1000 00cc` `0xxx xxxx` `00yy yyyy`|
+//! |set palette column|`1001 00vv` `0xxx xxxx` `00yy yyyy` (x is 125 or 126)|
+//! |clear|`1010 00cc`|
+//! |row|`1011 0000` `00yy yyyy` + 43 bytes, three 2 bit pixels per byte (first pixel in bits 0 - 1)|
+//! |fill rectangle|`1100 00cc` `0xxx xxxx` `00yy yyyy` `0www wwww` `00hh hhhh` (width - 1, height - 1)|
+//! |run of pixels|`1101 0000` `0xxx xxxx` `00yy yyyy` `0nnn nnnn` (length - 1) + pixels packed like a row|
+//! |frame mode|`1110 0000`|
+//!
+//! `encoder::Encoder` turns single pixels into the larger commands.
+//!
+//! With the `frame-dma` feature of the firmwares the main MCU sends `FrameMode` once the menu is
+//! done and continues with whole rows, see `frame`.
+
+#![no_std]
+
+pub mod encoder;
+pub mod frame;
+pub mod packet;
+pub mod screen;
+pub mod timing;
+
+pub const WIDTH: usize = 128;
+pub const HEIGHT: usize = 64;
+
+/// Columns 125 and 126 choose the palette of a row
+pub const PALETTE_COLUMNS: [u8; 2] = [125, 126];
+
+const OP_SET_PIXEL: u8 = 0;
+const OP_SET_PALETTE: u8 = 1;
+const OP_CLEAR: u8 = 2;
+const OP_ROW: u8 = 3;
+const OP_FILL_RECT: u8 = 4;
+const OP_RUN: u8 = 5;
+const OP_FRAME_MODE: u8 = 6;
+
+const START: u8 = 0x80;
+
+// three pixels per byte, the last byte holds the remaining two
+const ROW_BYTES: usize = WIDTH / 3 + 1;
+
+// the CRC follows the command, four bits per byte
+const CRC_LEN: usize = 2;
+
+/// Longest encoded command
+pub const MAX_FRAME_LEN: usize = 4 + ROW_BYTES + CRC_LEN;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Command {
+    SetPixel {
+        x: u8,
+        y: u8,
+        color: u8,
+    },
+    /// Sets column 125 or 126 of a row, the video MCU recolors the row
+    SetPalette {
+        x: u8,
+        y: u8,
+        value: u8,
+    },
+    /// Fills the whole screen, palette columns included
+    Clear {
+        color: u8,
+    },
+    /// All pixels of a row, palette columns included
+    Row {
+        y: u8,
+        pixels: [u8; WIDTH],
+    },
+    /// `width` and `height` are in `1..=WIDTH` and `1..=HEIGHT`
+    FillRect {
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        color: u8,
+    },
+    /// `len` pixels starting at x, the rest of `pixels` is zero - `len` is in `1..=WIDTH`
+    Run {
+        x: u8,
+        y: u8,
+        len: u8,
+        pixels: [u8; WIDTH],
+    },
+    /// Switches the video MCU to receiving blocks of rows, see `frame`
+    FrameMode,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DecodeError {
+    /// Got data without the start of a command, the byte is ignored
+    NotInSync,
+    UnknownCommand,
+    /// The command contained a coordinate or color which is out of range
+    BadValue,
+    BadCrc,
+}
+
+/// Pixels the video MCU shows, everything else except the palette columns is not transmitted
+pub fn is_visible(x: u8, y: u8) -> bool {
+    (4..=101).contains(&x) && (4..=62).contains(&y)
+}
+
+impl Command {
+    /// The command for a pixel written by the emulator, selects `SetPalette` for the palette columns
+    pub fn pixel(x: u8, y: u8, color: u8) -> Command {
+        if PALETTE_COLUMNS.contains(&x) {
+            Command::SetPalette { x, y, value: color }
+        } else {
+            Command::SetPixel { x, y, color }
+        }
+    }
+
+    /// Writes the command and its CRC to the buffer, returns the bytes to send - panics on an
+    /// empty `FillRect` or `Run`
+    pub fn encode<'a>(&self, buffer: &'a mut [u8; MAX_FRAME_LEN]) -> &'a [u8] {
+        let len = match *self {
+            Command::SetPixel { x, y, color } => {
+                buffer[0] = start(OP_SET_PIXEL, color);
+                buffer[1] = x & 0x7f;
+                buffer[2] = y & 0x3f;
+                3
+            }
+            Command::SetPalette { x, y, value } => {
+                buffer[0] = start(OP_SET_PALETTE, value);
+                buffer[1] = x & 0x7f;
+                buffer[2] = y & 0x3f;
+                3
+            }
+            Command::Clear { color } => {
+                buffer[0] = start(OP_CLEAR, color);
+                1
+            }
+            Command::Row { y, pixels } => {
+                buffer[0] = start(OP_ROW, 0);
+                buffer[1] = y & 0x3f;
+                2 + pack(&pixels, &mut buffer[2..])
+            }
+            Command::FillRect {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => {
+                assert!(width > 0 && height > 0, "empty rectangle");
+                buffer[0] = start(OP_FILL_RECT, color);
+                buffer[1] = x & 0x7f;
+                buffer[2] = y & 0x3f;
+                buffer[3] = (width - 1) & 0x7f;
+                buffer[4] = (height - 1) & 0x3f;
+                5
+            }
+            Command::Run { x, y, len, pixels } => {
+                assert!(len > 0, "empty run");
+                buffer[0] = start(OP_RUN, 0);
+                buffer[1] = x & 0x7f;
+                buffer[2] = y & 0x3f;
+                buffer[3] = (len - 1) & 0x7f;
+                4 + pack(&pixels[..len as usize], &mut buffer[4..])
+            }
+            Command::FrameMode => {
+                buffer[0] = start(OP_FRAME_MODE, 0);
+                1
+            }
+        };
+
+        let crc = crc8(&buffer[..len]);
+        buffer[len] = crc >> 4;
+        buffer[len + 1] = crc & 0x0f;
+
+        &buffer[..len + CRC_LEN]
+    }
+}
+
+/// Time in us the video MCU needs to execute a command after receiving it
+pub fn busy_time(command: &Command) -> u16 {
+    match *command {
+        Command::SetPixel { .. } => 0,
+        Command::SetPalette { .. } => 10, // will re-color
+        Command::Clear { .. } => 2000,
+        Command::Row { .. } => 30,
+        Command::FillRect { width, height, .. } => 10 + width as u16 * height as u16 / 4,
+        Command::Run { len, .. } => 10 + len as u16 / 4,
+        Command::FrameMode => 0,
+    }
+}
+
+/// CRC-8 with polynomial 0x07 and no reflection
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn start(op: u8, arg: u8) -> u8 {
+    START | (op << 4) | (arg & 0b11)
+}
+
+// three pixels per byte, returns the number of bytes written
+fn pack(pixels: &[u8], buffer: &mut [u8]) -> usize {
+    for (idx, chunk) in pixels.chunks(3).enumerate() {
+        let mut packed = 0u8;
+        for (i, pixel) in chunk.iter().enumerate() {
+            packed |= (pixel & 0b11) << (i * 2);
+        }
+        buffer[idx] = packed;
+    }
+    pixels.chunks(3).len()
+}
+
+fn unpack(data: &[u8], pixels: &mut [u8]) {
+    for (idx, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = (data[idx / 3] >> ((idx % 3) * 2)) & 0b11;
+    }
+}
+
+// length of the command starting with `frame`, a run needs its header to know
+fn frame_len(op: u8, frame: &[u8]) -> Option<usize> {
+    match op {
+        OP_SET_PIXEL | OP_SET_PALETTE => Some(3),
+        OP_CLEAR | OP_FRAME_MODE => Some(1),
+        OP_ROW => Some(2 + ROW_BYTES),
+        OP_FILL_RECT => Some(5),
+        OP_RUN if frame.len() < 4 => Some(4),
+        // length - 1 is sent, three pixels per byte
+        OP_RUN => Some(4 + frame[3] as usize / 3 + 1),
+        _ => None,
+    }
+}
+
+/// Collects received bytes into commands, checks their CRC
+pub struct Decoder {
+    buffer: [u8; MAX_FRAME_LEN],
+    len: usize,
+    interrupted: u32,
+}
+
+impl Decoder {
+    pub fn new() -> Decoder {
+        Decoder {
+            buffer: [0u8; MAX_FRAME_LEN],
+            len: 0,
+            interrupted: 0,
+        }
+    }
+
+    /// Number of incomplete commands dropped because the next command started
+    pub fn interrupted(&self) -> u32 {
+        self.interrupted
+    }
+
+    /// True if not in the middle of a command
+    pub fn is_idle(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Drops the command currently received
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    /// Feeds a received byte, returns the command once it is complete
+    pub fn push(&mut self, byte: u8) -> Result<Option<Command>, DecodeError> {
+        if byte & START != 0 {
+            if self.len != 0 {
+                self.interrupted += 1;
+            }
+            self.buffer[0] = byte;
+            self.len = 1;
+        } else if self.len == 0 {
+            return Err(DecodeError::NotInSync);
+        } else {
+            self.buffer[self.len] = byte;
+            self.len += 1;
+        }
+
+        let op = (self.buffer[0] >> 4) & 0b111;
+        let command_len = match frame_len(op, &self.buffer[..self.len]) {
+            Some(len) => len,
+            None => {
+                self.len = 0;
+                return Err(DecodeError::UnknownCommand);
+            }
+        };
+
+        if self.len < command_len + CRC_LEN {
+            return Ok(None);
+        }
+
+        self.len = 0;
+
+        let crc = &self.buffer[command_len..command_len + CRC_LEN];
+        if crc[0] > 0x0f || crc[1] > 0x0f {
+            return Err(DecodeError::BadCrc);
+        }
+        if (crc[0] << 4) | crc[1] != crc8(&self.buffer[..command_len]) {
+            return Err(DecodeError::BadCrc);
+        }
+
+        self.decode(op).map(Some)
+    }
+
+    fn decode(&self, op: u8) -> Result<Command, DecodeError> {
+        let frame = &self.buffer;
+        let arg = frame[0] & 0x0f;
+
+        if arg > 0b11 {
+            return Err(DecodeError::BadValue);
+        }
+
+        match op {
+            OP_SET_PIXEL | OP_SET_PALETTE => {
+                let x = frame[1];
+                let y = frame[2];
+                if y as usize >= HEIGHT {
+                    return Err(DecodeError::BadValue);
+                }
+
+                if op == OP_SET_PIXEL {
+                    Ok(Command::SetPixel { x, y, color: arg })
+                } else if PALETTE_COLUMNS.contains(&x) {
+                    Ok(Command::SetPalette { x, y, value: arg })
+                } else {
+                    Err(DecodeError::BadValue)
+                }
+            }
+            OP_CLEAR => Ok(Command::Clear { color: arg }),
+            OP_ROW => {
+                let y = frame[1];
+                if y as usize >= HEIGHT || arg != 0 {
+                    return Err(DecodeError::BadValue);
+                }
+
+                let mut pixels = [0u8; WIDTH];
+                unpack(&frame[2..], &mut pixels);
+                Ok(Command::Row { y, pixels })
+            }
+            OP_FILL_RECT => {
+                let (x, y) = (frame[1], frame[2]);
+                let (width, height) = (frame[3] + 1, frame[4] + 1);
+                if x as usize + width as usize > WIDTH || y as usize + height as usize > HEIGHT {
+                    return Err(DecodeError::BadValue);
+                }
+
+                Ok(Command::FillRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color: arg,
+                })
+            }
+            OP_RUN => {
+                let (x, y, len) = (frame[1], frame[2], frame[3] + 1);
+                if x as usize + len as usize > WIDTH || y as usize >= HEIGHT || arg != 0 {
+                    return Err(DecodeError::BadValue);
+                }
+
+                let mut pixels = [0u8; WIDTH];
+                unpack(&frame[4..], &mut pixels[..len as usize]);
+                Ok(Command::Run { x, y, len, pixels })
+            }
+            OP_FRAME_MODE if arg == 0 => Ok(Command::FrameMode),
+            OP_FRAME_MODE => Err(DecodeError::BadValue),
+            _ => Err(DecodeError::UnknownCommand),
+        }
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Decoder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    fn encode(command: &Command) -> Vec<u8> {
+        let mut buffer = [0u8; MAX_FRAME_LEN];
+        command.encode(&mut buffer).to_vec()
+    }
+
+    fn with_crc(data: &[u8]) -> Vec<u8> {
+        let mut data = data.to_vec();
+        let crc = crc8(&data);
+        data.push(crc >> 4);
+        data.push(crc & 0x0f);
+        data
+    }
+
+    fn decode_all(decoder: &mut Decoder, data: &[u8]) -> Vec<Result<Command, DecodeError>> {
+        data.iter()
+            .filter_map(|b| decoder.push(*b).transpose())
+            .collect()
+    }
+
+    fn commands() -> Vec<Command> {
+        let mut row = [0u8; WIDTH];
+        for (idx, pixel) in row.iter_mut().enumerate() {
+            *pixel = (idx * 7 % 4) as u8;
+        }
+
+        vec![
+            Command::SetPixel {
+                x: 4,
+                y: 62,
+                color: 3,
+            },
+            Command::SetPixel {
+                x: 127,
+                y: 0,
+                color: 0,
+            },
+            Command::SetPalette {
+                x: 125,
+                y: 10,
+                value: 2,
+            },
+            Command::SetPalette {
+                x: 126,
+                y: 63,
+                value: 1,
+            },
+            Command::Clear { color: 2 },
+            Command::Row { y: 33, pixels: row },
+            Command::FillRect {
+                x: 0,
+                y: 0,
+                width: 128,
+                height: 64,
+                color: 1,
+            },
+            Command::FillRect {
+                x: 100,
+                y: 60,
+                width: 1,
+                height: 4,
+                color: 3,
+            },
+            run(0, 10, &row),
+            run(28, 63, &row[..100]),
+            run(127, 1, &row[..1]),
+            run(40, 1, &row[..2]),
+            Command::FrameMode,
+        ]
+    }
+
+    fn run(x: u8, y: u8, run: &[u8]) -> Command {
+        let mut pixels = [0u8; WIDTH];
+        pixels[..run.len()].copy_from_slice(run);
+        Command::Run {
+            x,
+            y,
+            len: run.len() as u8,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut decoder = Decoder::new();
+
+        for command in commands() {
+            let data = encode(&command);
+            assert_eq!(data[0] & START, START);
+            assert!(data[1..].iter().all(|b| b & START == 0));
+
+            assert_eq!(vec![Ok(command)], decode_all(&mut decoder, &data));
+        }
+    }
+
+    // the video MCU leaves command mode when a block starts
+    #[test]
+    fn block_start_is_no_command() {
+        for command in commands().iter().chain([Command::FrameMode].iter()) {
+            assert_ne!(frame::BLOCK_START, encode(command)[0]);
+        }
+    }
+
+    #[test]
+    fn pixel_selects_palette_command() {
+        assert_eq!(
+            Command::SetPalette {
+                x: 126,
+                y: 5,
+                value: 1
+            },
+            Command::pixel(126, 5, 1)
+        );
+        assert_eq!(
+            Command::SetPixel {
+                x: 124,
+                y: 5,
+                color: 1
+            },
+            Command::pixel(124, 5, 1)
+        );
+    }
+
+    #[test]
+    fn resync() {
+        let mut decoder = Decoder::new();
+        let pixel = Command::SetPixel {
+            x: 50,
+            y: 20,
+            color: 1,
+        };
+        let clear = Command::Clear { color: 0 };
+
+        // starting in the middle of a command
+        let mut data = encode(&pixel)[1..].to_vec();
+        data.extend(encode(&pixel));
+        let mut expected = vec![Err(DecodeError::NotInSync); 4];
+        expected.push(Ok(pixel));
+        assert_eq!(expected, decode_all(&mut decoder, &data));
+
+        // losing the end of a command
+        let mut data = encode(&pixel)[..2].to_vec();
+        data.extend(encode(&clear));
+        data.extend(encode(&pixel));
+        assert_eq!(vec![Ok(clear), Ok(pixel)], decode_all(&mut decoder, &data));
+        assert_eq!(1, decoder.interrupted());
+
+        // a lost byte in the middle of a row
+        let row = commands()[5];
+        let mut data = encode(&row);
+        data.remove(20);
+        data.extend(encode(&pixel));
+        assert_eq!(vec![Ok(pixel)], decode_all(&mut decoder, &data));
+        assert_eq!(2, decoder.interrupted());
+    }
+
+    #[test]
+    fn bad_values() {
+        let mut decoder = Decoder::new();
+
+        assert_eq!(
+            vec![Err(DecodeError::UnknownCommand)],
+            decode_all(&mut decoder, &[0xf0])
+        );
+        assert_eq!(
+            vec![Err(DecodeError::BadValue)],
+            decode_all(&mut decoder, &with_crc(&[0x80, 10, 64]))
+        );
+        assert_eq!(
+            vec![Err(DecodeError::BadValue)],
+            decode_all(&mut decoder, &with_crc(&[0xe1]))
+        );
+        assert_eq!(
+            vec![Err(DecodeError::BadValue)],
+            decode_all(&mut decoder, &with_crc(&[0x84, 10, 10]))
+        );
+        assert_eq!(
+            vec![Err(DecodeError::BadValue)],
+            decode_all(&mut decoder, &with_crc(&[0x91, 100, 10]))
+        );
+
+        assert_eq!(
+            vec![Err(DecodeError::BadValue)],
+            decode_all(&mut decoder, &with_crc(&[0xc1, 100, 10, 28, 0]))
+        );
+        assert_eq!(
+            vec![Err(DecodeError::BadValue)],
+            decode_all(&mut decoder, &with_crc(&[0xc1, 0, 10, 0, 54]))
+        );
+        assert_eq!(
+            vec![Err(DecodeError::BadValue)],
+            decode_all(&mut decoder, &with_crc(&[0xd0, 127, 10, 1, 0]))
+        );
+
+        // still working afterwards
+        assert_eq!(
+            vec![Ok(Command::Clear { color: 3 })],
+            decode_all(&mut decoder, &with_crc(&[0xa3]))
+        );
+    }
+
+    #[test]
+    fn bad_crc() {
+        let mut decoder = Decoder::new();
+        let command = Command::FillRect {
+            x: 10,
+            y: 10,
+            width: 5,
+            height: 5,
+            color: 2,
+        };
+
+        for idx in 1..7 {
+            for bit in 0..7 {
+                let mut data = encode(&command);
+                data[idx] ^= 1 << bit;
+                assert_eq!(
+                    vec![Err(DecodeError::BadCrc)],
+                    decode_all(&mut decoder, &data),
+                    "byte {} bit {}",
+                    idx,
+                    bit
+                );
+            }
+        }
+
+        assert_eq!(
+            vec![Ok(command)],
+            decode_all(&mut decoder, &encode(&command))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "empty rectangle")]
+    fn empty_rectangle() {
+        encode(&Command::FillRect {
+            x: 4,
+            y: 4,
+            width: 0,
+            height: 1,
+            color: 1,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "empty run")]
+    fn empty_run() {
+        encode(&Command::Run {
+            x: 4,
+            y: 4,
+            len: 0,
+            pixels: [0; WIDTH],
+        });
+    }
+
+    #[test]
+    fn crc_check_value() {
+        assert_eq!(0xf4, crc8(b"123456789"));
+    }
+}
@@ -0,0 +1,366 @@
+//! Acknowledged transfer of commands
+//!
+//! SPI is full duplex: while the main MCU sends a byte it receives the reply the video MCU
+//! prepared after the byte before. Once a command is sent the main MCU sends `POLL` bytes until
+//! the video MCU replies with `ACK` or `NAK`. A `NAK` or no answer at all makes it send the
+//! command again.
+//!
+//! The CRC-8 finds every single bit error but only most of the multi bit errors in long commands.
+//! That's good enough for a few centimeters of wire between the two MCUs.
+//!
+//! An `ACK` which gets lost makes the main MCU send the command again, that's fine since
+//! executing a command twice doesn't change the result.
+//!
+//! While the video MCU is waiting for a command it answers `POLL` bytes with `NAK` once the reply
+//! to the last command went out, so a command which got lost completely is sent again, too.
+//! `POLL` is never the last byte of a command (that's the low nibble of the CRC), so a command
+//! which lost its first byte still ends in a `NAK`.
+
+use crate::{busy_time, Command, Decoder, MAX_FRAME_LEN};
+
+/// Reply of the video MCU: the last command was fine
+pub const ACK: u8 = 0x5a;
+/// Reply of the video MCU: the last command was broken or bytes were lost
+pub const NAK: u8 = 0xa5;
+/// Reply of the video MCU: in the middle of receiving a command
+pub const BUSY: u8 = 0x00;
+
+/// Sent by the main MCU to get the reply for the last command
+pub const POLL: u8 = 0x7f;
+
+/// Number of `POLL` bytes to wait for an answer before sending the command again
+pub const MAX_POLLS: usize = 4;
+/// Number of times a command is sent before giving up
+pub const MAX_TRIES: usize = 16;
+
+/// One byte transfer over SPI
+pub trait Link {
+    /// Sends a byte and returns the byte received at the same time
+    fn transfer(&mut self, byte: u8) -> u8;
+
+    /// Gives the other side time to execute a command
+    fn wait_us(&mut self, us: u16);
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LinkError {
+    /// The command was sent `MAX_TRIES` times without being acknowledged
+    NoAnswer,
+}
+
+/// Sends a command until it's acknowledged, returns the number of tries needed
+pub fn send<L: Link>(link: &mut L, command: &Command) -> Result<usize, LinkError> {
+    let mut buffer = [0u8; MAX_FRAME_LEN];
+    let packet = command.encode(&mut buffer);
+
+    for tries in 1..=MAX_TRIES {
+        for byte in packet.iter() {
+            link.transfer(*byte);
+        }
+        link.wait_us(busy_time(command));
+
+        for _ in 0..MAX_POLLS {
+            match link.transfer(POLL) {
+                ACK => return Ok(tries),
+                NAK => break,
+                _ => {}
+            }
+        }
+    }
+
+    Err(LinkError::NoAnswer)
+}
+
+/// The video MCU side
+pub struct Receiver {
+    decoder: Decoder,
+    reply: u8,
+}
+
+impl Receiver {
+    pub fn new() -> Receiver {
+        Receiver {
+            decoder: Decoder::new(),
+            reply: NAK,
+        }
+    }
+
+    /// The byte to send with the next transfer
+    pub fn reply(&self) -> u8 {
+        self.reply
+    }
+
+    /// True between commands
+    pub fn is_idle(&self) -> bool {
+        self.decoder.is_idle()
+    }
+
+    /// Feeds a received byte, returns the command once it's complete and correct
+    pub fn push(&mut self, byte: u8) -> Option<Command> {
+        // the reply went out with this byte, there's no command to answer for any more
+        if byte == POLL && self.decoder.is_idle() {
+            self.reply = NAK;
+            return None;
+        }
+
+        match self.decoder.push(byte) {
+            Ok(None) => {
+                self.reply = BUSY;
+                None
+            }
+            Ok(Some(command)) => {
+                self.reply = ACK;
+                Some(command)
+            }
+            Err(_) => {
+                self.reply = NAK;
+                None
+            }
+        }
+    }
+
+    /// A byte was lost, the command currently received is dropped
+    pub fn overrun(&mut self) {
+        self.decoder.reset();
+        self.reply = NAK;
+    }
+}
+
+impl Default for Receiver {
+    fn default() -> Self {
+        Receiver::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    // xorshift, good enough to inject errors
+    struct Random(u32);
+
+    impl Random {
+        fn chance(&mut self, one_in: u32) -> bool {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            one_in != 0 && self.0 < u32::MAX / one_in
+        }
+    }
+
+    /// The video MCU on a bad line
+    struct NoisyLink {
+        receiver: Receiver,
+        received: Vec<Command>,
+        random: Random,
+        // the next bytes sent are lost
+        drop_next: usize,
+        drop_one_in: u32,
+        overrun_one_in: u32,
+        corrupt_one_in: u32,
+        corrupt_reply_one_in: u32,
+    }
+
+    impl NoisyLink {
+        fn new(drop_one_in: u32, corrupt_one_in: u32) -> NoisyLink {
+            NoisyLink {
+                receiver: Receiver::new(),
+                received: Vec::new(),
+                random: Random(0x1234_5678),
+                drop_next: 0,
+                drop_one_in,
+                overrun_one_in: 0,
+                corrupt_one_in,
+                corrupt_reply_one_in: 0,
+            }
+        }
+    }
+
+    impl Link for NoisyLink {
+        fn transfer(&mut self, byte: u8) -> u8 {
+            let mut reply = self.receiver.reply();
+            if self.random.chance(self.corrupt_reply_one_in) {
+                reply ^= 0x18;
+            }
+
+            if self.drop_next > 0 {
+                self.drop_next -= 1;
+                return reply;
+            }
+            if self.random.chance(self.drop_one_in) {
+                return reply;
+            }
+            if self.random.chance(self.overrun_one_in) {
+                self.receiver.overrun();
+                return reply;
+            }
+
+            let byte = if self.random.chance(self.corrupt_one_in) {
+                byte ^ (1 << (self.random.0 % 8))
+            } else {
+                byte
+            };
+
+            if let Some(command) = self.receiver.push(byte) {
+                self.received.push(command);
+            }
+
+            reply
+        }
+
+        fn wait_us(&mut self, _us: u16) {}
+    }
+
+    fn commands() -> Vec<Command> {
+        let mut pixels = [0u8; crate::WIDTH];
+        for (idx, pixel) in pixels.iter_mut().enumerate() {
+            *pixel = (idx % 3) as u8;
+        }
+
+        (0..500u32)
+            .map(|i| match i % 5 {
+                0 => Command::SetPixel {
+                    x: (i % 128) as u8,
+                    y: (i % 64) as u8,
+                    color: (i % 4) as u8,
+                },
+                1 => Command::SetPalette {
+                    x: 125 + (i % 2) as u8,
+                    y: (i % 64) as u8,
+                    value: (i % 3) as u8,
+                },
+                2 => Command::Run {
+                    x: 10,
+                    y: (i % 64) as u8,
+                    len: 90,
+                    pixels: {
+                        let mut run = [0u8; crate::WIDTH];
+                        run[..90].copy_from_slice(&pixels[..90]);
+                        run
+                    },
+                },
+                3 => Command::FillRect {
+                    x: 4,
+                    y: 4,
+                    width: (i % 90 + 1) as u8,
+                    height: 3,
+                    color: 1,
+                },
+                _ => Command::Clear {
+                    color: (i % 4) as u8,
+                },
+            })
+            .collect()
+    }
+
+    // duplicates are fine, they don't change the picture
+    fn without_duplicates(commands: &[Command]) -> Vec<Command> {
+        let mut result: Vec<Command> = Vec::new();
+        for command in commands {
+            if result.last() != Some(command) {
+                result.push(*command);
+            }
+        }
+        result
+    }
+
+    fn send_all(link: &mut NoisyLink) -> usize {
+        let mut retries = 0;
+        for command in commands() {
+            retries += send(link, &command).unwrap() - 1;
+        }
+        retries
+    }
+
+    #[test]
+    fn clean_line() {
+        let mut link = NoisyLink::new(0, 0);
+        assert_eq!(0, send_all(&mut link));
+        assert_eq!(commands(), link.received);
+    }
+
+    #[test]
+    fn dropped_bytes() {
+        let mut link = NoisyLink::new(1000, 0);
+        link.overrun_one_in = 2000;
+        assert!(send_all(&mut link) > 0);
+        assert_eq!(commands(), without_duplicates(&link.received));
+    }
+
+    #[test]
+    fn corrupted_bytes() {
+        let mut link = NoisyLink::new(0, 1000);
+        link.corrupt_reply_one_in = 200;
+        assert!(send_all(&mut link) > 0);
+        assert_eq!(commands(), without_duplicates(&link.received));
+    }
+
+    #[test]
+    fn everything_at_once() {
+        let mut link = NoisyLink::new(2000, 2000);
+        link.overrun_one_in = 2000;
+        link.corrupt_reply_one_in = 500;
+        send_all(&mut link);
+        assert_eq!(commands(), without_duplicates(&link.received));
+    }
+
+    #[test]
+    fn lost_command() {
+        let mut link = NoisyLink::new(0, 0);
+        let first = Command::Clear { color: 1 };
+        let second = Command::SetPixel {
+            x: 10,
+            y: 20,
+            color: 2,
+        };
+        assert_eq!(Ok(1), send(&mut link, &first));
+
+        // the ACK of the command before doesn't answer for it
+        let mut buffer = [0u8; MAX_FRAME_LEN];
+        link.drop_next = second.encode(&mut buffer).len();
+        assert_eq!(Ok(2), send(&mut link, &second));
+        assert_eq!(vec![first, second], link.received);
+    }
+
+    #[test]
+    fn lost_first_byte_is_nak() {
+        let mut receiver = Receiver::new();
+        let mut buffer = [0u8; MAX_FRAME_LEN];
+        let packet = Command::Clear { color: 0 }.encode(&mut buffer);
+
+        for byte in packet[1..].iter() {
+            assert_eq!(None, receiver.push(*byte));
+        }
+        receiver.push(POLL);
+        assert_eq!(NAK, receiver.reply());
+
+        for byte in packet.iter() {
+            receiver.push(*byte);
+        }
+        assert_eq!(ACK, receiver.reply());
+        // sent with this poll
+        receiver.push(POLL);
+        assert_eq!(NAK, receiver.reply());
+    }
+
+    #[test]
+    fn dead_line() {
+        struct DeadLink;
+
+        impl Link for DeadLink {
+            fn transfer(&mut self, _byte: u8) -> u8 {
+                0xff
+            }
+
+            fn wait_us(&mut self, _us: u16) {}
+        }
+
+        assert_eq!(
+            Err(LinkError::NoAnswer),
+            send(&mut DeadLink, &Command::Clear { color: 0 })
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! The screen as the video MCU keeps it
+//!
+//! `vid_ram` holds what goes to the video pins: a 6 bit color `rrggbb` per pixel, except for the
+//! palette columns which keep their value. `indexed_pixels` holds the color index of every pixel,
+//! that's needed to recolor a row once its palette changes.
+
+use crate::{Command, HEIGHT, PALETTE_COLUMNS, WIDTH};
+
+// column 125 + 126 choose the palette to use
+pub const COLORS: [u8; 16] = [
+    0b000000, 0b111111, 0b111111, 0b111111, //
+    0b101011, 0b000011, 0b110000, 0b001000, //
+    0b010101, 0b000011, 0b110000, 0b001000, //
+    0b011101, 0b000011, 0b110000, 0b001000, //
+];
+
+/// The colors as grey levels 0 - 63 for monochrome composite
+pub const LUMA: [u8; 16] = luma(&COLORS);
+
+const fn luma(colors: &[u8; 16]) -> [u8; 16] {
+    let mut result = [0u8; 16];
+    let mut idx = 0;
+    while idx < colors.len() {
+        let (r, g, b) = (
+            colors[idx] >> 4,
+            (colors[idx] >> 2) & 0b11,
+            colors[idx] & 0b11,
+        );
+        let luma = 299 * r as u32 + 587 * g as u32 + 114 * b as u32;
+        result[idx] = ((luma * 63 + 1500) / 3000) as u8;
+        idx += 1;
+    }
+    result
+}
+
+pub struct Screen<'a> {
+    vid_ram: &'a mut [u8],
+    indexed_pixels: &'a mut [u8],
+    colors: &'static [u8; 16],
+}
+
+impl<'a> Screen<'a> {
+    /// Both buffers hold `WIDTH` * `HEIGHT` pixels, row by row
+    pub fn new(vid_ram: &'a mut [u8], indexed_pixels: &'a mut [u8]) -> Screen<'a> {
+        Screen::with_colors(vid_ram, indexed_pixels, &COLORS)
+    }
+
+    /// Uses other colors than `COLORS`, e.g. `LUMA`
+    pub fn with_colors(
+        vid_ram: &'a mut [u8],
+        indexed_pixels: &'a mut [u8],
+        colors: &'static [u8; 16],
+    ) -> Screen<'a> {
+        assert!(vid_ram.len() >= WIDTH * HEIGHT && indexed_pixels.len() >= WIDTH * HEIGHT);
+
+        Screen {
+            vid_ram,
+            indexed_pixels,
+            colors,
+        }
+    }
+
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::SetPixel { x, y, color } => self.apply_pixel(x, y, color),
+            Command::SetPalette { x, y, value } => self.apply_palette(x, y, value),
+            Command::Clear { color } => {
+                for y in 0..HEIGHT as u8 {
+                    self.apply_row(y, 0, &[color; WIDTH]);
+                }
+            }
+            Command::Row { y, pixels } => self.apply_row(y, 0, &pixels),
+            Command::FillRect {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => {
+                for yy in y..y + height {
+                    self.apply_row(yy, x, &[color; WIDTH][..width as usize]);
+                }
+            }
+            Command::Run { x, y, len, pixels } => self.apply_row(y, x, &pixels[..len as usize]),
+            // the video MCU's receive loop switches modes
+            Command::FrameMode => {}
+        }
+    }
+
+    /// Sets a pixel, a palette column recolors the row
+    pub fn apply_pixel(&mut self, x: u8, y: u8, color: u8) {
+        self.apply_row(y, x, &[color]);
+    }
+
+    /// Sets palette column 125 or 126 of a row and recolors the row
+    pub fn apply_palette(&mut self, x: u8, y: u8, value: u8) {
+        self.apply_row(y, x, &[value]);
+    }
+
+    /// Sets pixels starting at x, recolors the whole row if a palette column was written
+    pub fn apply_row(&mut self, y: u8, x: u8, pixels: &[u8]) {
+        let row = y as usize * WIDTH;
+        let (start, end) = (x as usize, x as usize + pixels.len());
+        self.indexed_pixels[row + start..row + end].copy_from_slice(pixels);
+
+        let palette_changed = PALETTE_COLUMNS
+            .iter()
+            .any(|column| (start..end).contains(&(*column as usize)));
+        let (start, end) = if palette_changed {
+            (0, WIDTH)
+        } else {
+            (start, end)
+        };
+
+        let palette = self.palette(y);
+        for xx in start..end {
+            let pixel = self.indexed_pixels[row + xx];
+            self.vid_ram[row + xx] = if PALETTE_COLUMNS.contains(&(xx as u8)) {
+                pixel
+            } else {
+                self.colors[pixel as usize + palette * 4]
+            };
+        }
+    }
+
+    /// The palette of a row, see `chf_emulator::palette`
+    pub fn palette(&self, y: u8) -> usize {
+        let row = &self.indexed_pixels[y as usize * WIDTH..][..WIDTH];
+        chf_emulator::palette(row[125], row[126])
+    }
+
+    /// What the video pins show for a row, as ARGB
+    pub fn rgb_row(&self, y: u8) -> [u32; WIDTH] {
+        let mut result = [0u32; WIDTH];
+        for (rgb_pixel, color) in result
+            .iter_mut()
+            .zip(self.vid_ram[y as usize * WIDTH..].iter())
+        {
+            *rgb_pixel = rgb(*color);
+        }
+        result
+    }
+}
+
+/// ARGB of a 6 bit color, every two bits of it drive one channel via resistors
+pub fn rgb(color: u8) -> u32 {
+    let channel = |shift: u8| ((color >> shift) & 0b11) as u32 * 0x55;
+    0xff000000 | channel(4) << 16 | channel(2) << 8 | channel(0)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    // desktop/src/render
+    const DESKTOP_COLORS: [u32; 16] = [
+        0xff000000, 0xffffffff, 0xffffffff, 0xffffffff, //
+        0xff7777ff, 0xff0000ff, 0xffff0000, 0xff008800, //
+        0xffcccccc, 0xff0000ff, 0xffff0000, 0xff008800, //
+        0xff77ff77, 0xff0000ff, 0xffff0000, 0xff008800, //
+    ];
+
+    fn desktop_color(row: &[u8], x: usize) -> u32 {
+        let palette = chf_emulator::palette(row[125], row[126]);
+        DESKTOP_COLORS[row[x] as usize + palette * 4]
+    }
+
+    // the colors are tuned differently for the desktop and the TV, but the same desktop color
+    // must always be the same video color
+    fn video_color(desktop_color: u32) -> u32 {
+        let mut result = None;
+        for (desktop, video) in DESKTOP_COLORS.iter().zip(COLORS.iter()) {
+            if *desktop == desktop_color {
+                assert!(result.is_none() || result == Some(rgb(*video)));
+                result = Some(rgb(*video));
+            }
+        }
+        result.unwrap()
+    }
+
+    fn assert_same_as_desktop(screen: &Screen, pixels: &[u8]) {
+        for y in 0..HEIGHT as u8 {
+            let row = &pixels[y as usize * WIDTH..][..WIDTH];
+            let rgb_row = screen.rgb_row(y);
+
+            for x in (0..WIDTH).filter(|x| !PALETTE_COLUMNS.contains(&(*x as u8))) {
+                assert_eq!(
+                    video_color(desktop_color(row, x)),
+                    rgb_row[x],
+                    "x {} y {} palette columns {} {}",
+                    x,
+                    y,
+                    row[125],
+                    row[126]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_palette_and_color() {
+        let mut vid_ram = [0u8; WIDTH * HEIGHT];
+        let mut indexed_pixels = [0u8; WIDTH * HEIGHT];
+        let mut screen = Screen::new(&mut vid_ram, &mut indexed_pixels);
+        let mut pixels = [0u8; WIDTH * HEIGHT];
+
+        // every row goes from one pair of palette values to another, palette set before and
+        // after the pixels
+        for y in 0..HEIGHT as u8 {
+            let (from, to) = (y & 0b1111, (y >> 2) ^ y);
+            let before = y & 0b10_0000 != 0;
+
+            let mut set = |x: u8, color: u8| {
+                pixels[y as usize * WIDTH + x as usize] = color;
+                screen.apply_pixel(x, y, color);
+            };
+
+            set(125, from & 0b11);
+            set(126, from >> 2);
+            if before {
+                set(125, to & 0b11);
+                set(126, (to >> 2) & 0b11);
+            }
+            for x in 0..WIDTH as u8 {
+                if !PALETTE_COLUMNS.contains(&x) {
+                    set(x, (x ^ y) & 0b11);
+                }
+            }
+            if !before {
+                set(126, (to >> 2) & 0b11);
+                set(125, to & 0b11);
+            }
+        }
+
+        assert_same_as_desktop(&screen, &pixels);
+    }
+
+    #[test]
+    fn palette_value_without_palette_change() {
+        let mut vid_ram = [0u8; WIDTH * HEIGHT];
+        let mut indexed_pixels = [0u8; WIDTH * HEIGHT];
+        let mut screen = Screen::new(&mut vid_ram, &mut indexed_pixels);
+
+        screen.apply_pixel(10, 5, 1);
+        screen.apply_palette(126, 5, 1);
+        // still palette 1, but column 125 must be remembered
+        screen.apply_palette(125, 5, 2);
+        screen.apply_palette(126, 5, 0);
+
+        assert_eq!(1, screen.palette(5));
+        assert_eq!(rgb(COLORS[4 + 1]), screen.rgb_row(5)[10]);
+    }
+
+    #[test]
+    fn commands() {
+        let mut vid_ram = [0u8; WIDTH * HEIGHT];
+        let mut indexed_pixels = [0u8; WIDTH * HEIGHT];
+        let mut screen = Screen::new(&mut vid_ram, &mut indexed_pixels);
+
+        let mut pixels = [0u8; WIDTH * HEIGHT];
+        let mut row = [0u8; WIDTH];
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = (x * 5 % 4) as u8;
+        }
+
+        screen.apply(Command::Clear { color: 2 });
+        for pixel in pixels.iter_mut() {
+            *pixel = 2;
+        }
+
+        screen.apply(Command::Row { y: 7, pixels: row });
+        pixels[7 * WIDTH..][..WIDTH].copy_from_slice(&row);
+
+        screen.apply(Command::FillRect {
+            x: 120,
+            y: 20,
+            width: 8,
+            height: 10,
+            color: 1,
+        });
+        screen.apply(Command::Run {
+            x: 3,
+            y: 40,
+            len: 125,
+            pixels: row,
+        });
+        for y in 20..30 {
+            for pixel in pixels[y * WIDTH + 120..][..8].iter_mut() {
+                *pixel = 1;
+            }
+        }
+        pixels[40 * WIDTH + 3..][..125].copy_from_slice(&row[..125]);
+
+        for y in 0..HEIGHT as u8 {
+            let expected = &pixels[y as usize * WIDTH..][..WIDTH];
+            assert_eq!(
+                expected,
+                &screen.indexed_pixels[y as usize * WIDTH..][..WIDTH]
+            );
+        }
+        assert_same_as_desktop(&screen, &pixels);
+    }
+}
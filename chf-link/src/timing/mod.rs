@@ -0,0 +1,618 @@
+//! Video timing of the video MCU
+//!
+//! TIM4 runs at 72 MHz in one pulse mode and is restarted for every entry of the table: the sync
+//! pin is low for `ccr` ticks and high until `arr`. A field isn't interlaced, every field is the
+//! same:
+//!
+//! |Entries|Contents|
+//! |---|---|
+//! |half lines|broad sync pulses|
+//! |half lines|equalizing pulses|
+//! |full lines|vertical sync on its own pin (VGA)|
+//! |full lines|blank|
+//! |full lines|with pixels, every row of the screen is drawn `lines_per_row` times|
+//! |full lines|blank (VGA)|
+//! |half lines|equalizing pulses before the next field|
+//!
+//! The table is generated from a `Standard` at compile time, the firmware chooses `PAL`, `NTSC`
+//! or `VGA` via its features. Monochrome composite uses the `PAL` or `NTSC` table, its sync pin
+//! is mixed with the luma.
+
+use crate::HEIGHT;
+
+/// Timer ticks per µs
+pub const TICKS_PER_US: u32 = 72;
+
+/// Entries of the longest table
+pub const MAX_LINES: usize = 525;
+
+/// The video MCU draws columns 0 - 103 of every row
+pub const DRAWN_COLUMNS: usize = 104;
+
+/// GPIOB pins of the six bits of a pixel
+pub const PIXEL_PINS: u32 = 0x3f << 3;
+
+/// GPIOB pin of the vertical sync (VGA)
+pub const V_SYNC_PIN: u32 = 1 << 10;
+
+/// What the drawing loop writes to the set / reset register of GPIOB for a pixel, the pins
+/// besides `PIXEL_PINS` keep their level
+pub const fn pixel_bsrr(color: u8) -> u32 {
+    let set = (color as u32) << 3 & PIXEL_PINS;
+    let reset = !(color as u32) << 3 & PIXEL_PINS;
+    set | reset << 16
+}
+
+/// Durations in timer ticks, counts in entries of the table
+pub struct Standard {
+    pub full_line: u16,
+    pub half_line: u16,
+    pub h_sync: u16,
+    /// Equalizing pulse
+    pub short_sync: u16,
+    pub broad_sync: u16,
+    pub broad_pulses: usize,
+    pub post_equalizing: usize,
+    pub v_sync_lines: usize,
+    pub blank_lines: usize,
+    pub pixel_lines: usize,
+    pub front_porch_lines: usize,
+    pub pre_equalizing: usize,
+    /// The pixel line showing the first row of the screen
+    pub first_drawn_line: usize,
+    pub lines_per_row: usize,
+    /// From the start of a line to the first pixel
+    pub pixel_start: u16,
+    /// One pass of the drawing loop
+    pub pixel_ticks: u16,
+}
+
+/// 312 lines, 50 Hz - tuned on a TV
+pub const PAL: Standard = Standard {
+    full_line: 2307 * 2, // 64
+    half_line: 2305,
+    h_sync: 344,      // 4.7
+    short_sync: 182,  // 2.35
+    broad_sync: 1966, // 27.3
+    broad_pulses: 5,
+    post_equalizing: 5,
+    v_sync_lines: 0,
+    blank_lines: 18,
+    pixel_lines: 286,
+    front_porch_lines: 0,
+    pre_equalizing: 6,
+    first_drawn_line: 52,
+    lines_per_row: 3,
+    pixel_start: 1380,
+    pixel_ticks: 22,
+};
+
+/// 262 lines, 60 Hz - the nominal values
+pub const NTSC: Standard = Standard {
+    full_line: 4576, // 63.556
+    half_line: 2288,
+    h_sync: 338,      // 4.7
+    short_sync: 166,  // 2.3
+    broad_sync: 1950, // 27.1
+    broad_pulses: 6,
+    post_equalizing: 6,
+    v_sync_lines: 0,
+    blank_lines: 11,
+    pixel_lines: 242,
+    front_porch_lines: 0,
+    pre_equalizing: 6,
+    first_drawn_line: 25,
+    lines_per_row: 3,
+    pixel_start: 1380,
+    pixel_ticks: 22,
+};
+
+/// 640 x 480, 31.5 kHz, 60 Hz - every line of a TV is drawn twice, with pixels half as long
+pub const VGA: Standard = Standard {
+    full_line: 2288, // 31.778
+    half_line: 1144,
+    h_sync: 275, // 3.813
+    short_sync: 0,
+    broad_sync: 0,
+    broad_pulses: 0,
+    post_equalizing: 0,
+    v_sync_lines: 2,
+    blank_lines: 33,
+    pixel_lines: 480,
+    front_porch_lines: 10,
+    pre_equalizing: 0,
+    first_drawn_line: 48,
+    lines_per_row: 6,
+    pixel_start: 724,
+    pixel_ticks: 11,
+};
+
+/// One entry of the table
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Line {
+    pub arr: u16,
+    pub ccr: u16,
+    pub has_pixels: bool,
+    /// The vertical sync pin is low
+    pub v_sync: bool,
+}
+
+pub struct Timing {
+    /// Only the first `len` entries are used
+    pub lines: [Line; MAX_LINES],
+    pub len: usize,
+    /// First entry drawing pixels
+    pub start_at: usize,
+    /// First entry after the pixels
+    pub stop_at: usize,
+    pub lines_per_row: usize,
+    /// Timer ticks after restarting the timer
+    pub pixel_start: u16,
+}
+
+/// Generates the table, `overhead` is subtracted from every duration: the ticks the ISR takes
+/// until the timer runs again
+pub const fn timing(standard: &Standard, overhead: u16) -> Timing {
+    let half_line = standard.half_line - overhead;
+    let full_line = standard.full_line - overhead;
+    let h_sync = standard.h_sync - overhead;
+
+    let broad = Line {
+        arr: half_line,
+        ccr: standard.broad_sync.saturating_sub(overhead),
+        has_pixels: false,
+        v_sync: false,
+    };
+    let equalizing = Line {
+        arr: half_line,
+        ccr: standard.short_sync.saturating_sub(overhead),
+        has_pixels: false,
+        v_sync: false,
+    };
+    let v_sync = Line {
+        arr: full_line,
+        ccr: h_sync,
+        has_pixels: false,
+        v_sync: true,
+    };
+    let blank = Line {
+        arr: full_line,
+        ccr: h_sync,
+        has_pixels: false,
+        v_sync: false,
+    };
+    let pixels = Line {
+        arr: full_line,
+        ccr: h_sync,
+        has_pixels: true,
+        v_sync: false,
+    };
+
+    let mut lines = [equalizing; MAX_LINES];
+    let mut len = 0;
+    let mut idx = 0;
+    while idx < standard.broad_pulses {
+        lines[len] = broad;
+        len += 1;
+        idx += 1;
+    }
+    // equalizing pulses are already in place
+    len += standard.post_equalizing;
+    idx = 0;
+    while idx < standard.v_sync_lines {
+        lines[len] = v_sync;
+        len += 1;
+        idx += 1;
+    }
+    idx = 0;
+    while idx < standard.blank_lines {
+        lines[len] = blank;
+        len += 1;
+        idx += 1;
+    }
+    let start_at = len + standard.first_drawn_line;
+    idx = 0;
+    while idx < standard.pixel_lines {
+        lines[len] = pixels;
+        len += 1;
+        idx += 1;
+    }
+    idx = 0;
+    while idx < standard.front_porch_lines {
+        lines[len] = blank;
+        len += 1;
+        idx += 1;
+    }
+    len += standard.pre_equalizing;
+
+    Timing {
+        lines,
+        len,
+        start_at,
+        stop_at: start_at + HEIGHT * standard.lines_per_row,
+        lines_per_row: standard.lines_per_row,
+        pixel_start: standard.pixel_start - overhead,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::screen::{Screen, LUMA};
+    use crate::WIDTH;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum Pulse {
+        Broad,
+        Equalizing,
+        VSync,
+        HSync,
+        Pixels,
+    }
+
+    struct Limits {
+        line_us: f64,
+        field_hz: f64,
+        h_sync_us: f64,
+        /// Equalizing and broad pulse
+        v_sync_us: Option<(f64, f64)>,
+        /// Lines of the field blanking, vertical sync included
+        blanking_lines: usize,
+        /// Active video of a line: after the back porch, before the front porch
+        active_us: (f64, f64),
+        sequence: &'static [(Pulse, usize)],
+    }
+
+    // ITU-R BT.470 for PAL B/G, SMPTE 170M for NTSC - without interlacing a field has a whole
+    // number of lines, so PAL has one more equalizing pulse
+    const PAL_LIMITS: Limits = Limits {
+        line_us: 64.0,
+        field_hz: 50.0,
+        h_sync_us: 4.7,
+        v_sync_us: Some((2.35, 27.3)),
+        blanking_lines: 25,
+        active_us: (10.5, 62.35),
+        sequence: &[
+            (Pulse::Broad, 5),
+            (Pulse::Equalizing, 5),
+            (Pulse::HSync, 18),
+            (Pulse::Pixels, 286),
+            (Pulse::Equalizing, 6),
+        ],
+    };
+
+    const NTSC_LIMITS: Limits = Limits {
+        line_us: 63.556,
+        field_hz: 59.94,
+        h_sync_us: 4.7,
+        v_sync_us: Some((2.3, 27.1)),
+        blanking_lines: 20,
+        active_us: (10.9, 62.06),
+        sequence: &[
+            (Pulse::Broad, 6),
+            (Pulse::Equalizing, 6),
+            (Pulse::HSync, 11),
+            (Pulse::Pixels, 242),
+            (Pulse::Equalizing, 6),
+        ],
+    };
+
+    // VESA 640 x 480 at 60 Hz
+    const VGA_LIMITS: Limits = Limits {
+        line_us: 31.778,
+        field_hz: 59.94,
+        h_sync_us: 3.813,
+        v_sync_us: None,
+        blanking_lines: 45,
+        active_us: (5.72, 31.14),
+        sequence: &[
+            (Pulse::VSync, 2),
+            (Pulse::HSync, 33),
+            (Pulse::Pixels, 480),
+            (Pulse::HSync, 10),
+        ],
+    };
+
+    fn us(ticks: u16) -> f64 {
+        ticks as f64 / TICKS_PER_US as f64
+    }
+
+    fn close(value: f64, nominal: f64, tolerance: f64) {
+        assert!((value - nominal).abs() <= tolerance);
+    }
+
+    fn pulse(standard: &Standard, line: &Line) -> Pulse {
+        if line.arr == standard.half_line {
+            if line.ccr == standard.broad_sync {
+                Pulse::Broad
+            } else {
+                assert_eq!(standard.short_sync, line.ccr);
+                Pulse::Equalizing
+            }
+        } else {
+            assert_eq!(standard.full_line, line.arr);
+            assert_eq!(standard.h_sync, line.ccr);
+            if line.has_pixels {
+                Pulse::Pixels
+            } else if line.v_sync {
+                Pulse::VSync
+            } else {
+                Pulse::HSync
+            }
+        }
+    }
+
+    fn check(standard: &Standard, limits: &Limits) {
+        let timing = timing(standard, 0);
+        let lines = &timing.lines[..timing.len];
+
+        let mut sequence: Vec<(Pulse, usize)> = Vec::new();
+        for line in lines {
+            let pulse = pulse(standard, line);
+            match sequence.last_mut() {
+                Some((last, count)) if *last == pulse => *count += 1,
+                _ => sequence.push((pulse, 1)),
+            }
+        }
+        assert_eq!(limits.sequence, &sequence[..]);
+
+        close(us(standard.full_line), limits.line_us, 0.1);
+        // the tuned pulses are a bit longer than nominal, TVs take that
+        close(us(standard.h_sync), limits.h_sync_us, 0.2);
+        if let Some((short_sync_us, broad_sync_us)) = limits.v_sync_us {
+            close(us(standard.half_line), limits.line_us / 2.0, 0.1);
+            close(us(standard.short_sync), short_sync_us, 0.2);
+            close(us(standard.broad_sync), broad_sync_us, 0.2);
+        }
+
+        let field_us: f64 = lines.iter().map(|line| us(line.arr)).sum();
+        close(1_000_000.0 / field_us, limits.field_hz, 0.12);
+
+        let half_lines = lines
+            .iter()
+            .filter(|line| line.arr == standard.half_line)
+            .count();
+        let blank_lines = lines
+            .iter()
+            .filter(|line| line.arr == standard.full_line && !line.has_pixels)
+            .count();
+        assert_eq!(0, half_lines % 2);
+        let blanking = half_lines / 2 + blank_lines;
+        assert!(blanking >= limits.blanking_lines);
+
+        assert!(lines[timing.start_at..timing.stop_at]
+            .iter()
+            .all(|line| line.has_pixels));
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum Signal {
+        Sync,
+        Blank,
+        Pixel { row: usize, column: usize },
+    }
+
+    // what a write to the set / reset register of GPIOB does to the output
+    fn bsrr(odr: u32, bits: u32) -> u32 {
+        (odr | bits & 0xffff) & !(bits >> 16)
+    }
+
+    // the timer drives the sync on PB9
+    const SYNC_PIN: u32 = 1 << 9;
+
+    /// One sample per timer tick: what the table says and the output of GPIOB - the sync pin of
+    /// the timer, the pixels the drawing loop writes from `vid_ram` and the vertical sync pin,
+    /// which the pixels written to the same port must leave alone
+    fn render(standard: &Standard, vid_ram: &[u8]) -> Vec<(Signal, u32)> {
+        let timing = timing(standard, 0);
+        let mut samples = Vec::new();
+        let mut odr = 0;
+
+        for (idx, line) in timing.lines[..timing.len].iter().enumerate() {
+            let drawn = (timing.start_at..timing.stop_at).contains(&idx);
+            let row = idx.wrapping_sub(timing.start_at) / timing.lines_per_row;
+            let first_pixel = timing.pixel_start as usize;
+            let pixels = DRAWN_COLUMNS * standard.pixel_ticks as usize;
+
+            // the interrupt sets the vertical sync when the line starts
+            odr = match line.v_sync {
+                true => bsrr(odr, V_SYNC_PIN << 16),
+                false => bsrr(odr, V_SYNC_PIN),
+            };
+
+            for tick in 0..line.arr as usize {
+                let signal = if tick < line.ccr as usize {
+                    Signal::Sync
+                } else if drawn && (first_pixel..first_pixel + pixels).contains(&tick) {
+                    let column = (tick - first_pixel) / standard.pixel_ticks as usize;
+                    odr = bsrr(odr, pixel_bsrr(vid_ram[row * WIDTH + column]));
+                    Signal::Pixel { row, column }
+                } else {
+                    if drawn && tick == first_pixel + pixels {
+                        odr = bsrr(odr, PIXEL_PINS << 16);
+                    }
+                    Signal::Blank
+                };
+                let sync = if signal == Signal::Sync { 0 } else { SYNC_PIN };
+                samples.push((signal, odr | sync));
+            }
+        }
+        samples
+    }
+
+    fn is_v_sync(pins: u32) -> bool {
+        pins & V_SYNC_PIN == 0
+    }
+
+    fn check_timeline(standard: &Standard, limits: &Limits) {
+        let samples = render(standard, &[0u8; WIDTH * HEIGHT]);
+        let ticks = |us: f64| (us * TICKS_PER_US as f64) as usize;
+
+        // every falling edge of the sync starts a (half) line
+        let mut line_starts = vec![0];
+        for (tick, pair) in samples.windows(2).enumerate() {
+            if pair[0].0 != Signal::Sync && pair[1].0 == Signal::Sync {
+                line_starts.push(tick + 1);
+            }
+        }
+        line_starts.push(samples.len());
+
+        let mut drawn_lines = vec![0; HEIGHT];
+        let mut rows = Vec::new();
+        for line in line_starts.windows(2) {
+            let line = &samples[line[0]..line[1]];
+            let pixels: Vec<(usize, usize, usize)> = line
+                .iter()
+                .enumerate()
+                .filter_map(|(tick, (signal, _))| match signal {
+                    Signal::Pixel { row, column } => Some((tick, *row, *column)),
+                    _ => None,
+                })
+                .collect();
+
+            if let (Some(first), Some(last)) = (pixels.first(), pixels.last()) {
+                assert_eq!(standard.full_line as usize, line.len());
+                assert!(first.0 >= ticks(limits.active_us.0));
+                assert!(last.0 < ticks(limits.active_us.1));
+
+                let mut columns: Vec<usize> = pixels.iter().map(|pixel| pixel.2).collect();
+                columns.dedup();
+                assert_eq!((0..DRAWN_COLUMNS).collect::<Vec<_>>(), columns);
+                assert!(pixels.iter().all(|pixel| pixel.1 == first.1));
+                assert!(line.iter().all(|(_, pins)| !is_v_sync(*pins)));
+
+                drawn_lines[first.1] += 1;
+                rows.push(first.1);
+            }
+        }
+
+        // every row on consecutive lines, one row after the other
+        assert!(drawn_lines
+            .iter()
+            .all(|count| *count == standard.lines_per_row));
+        assert!(rows.windows(2).all(|pair| pair[1] >= pair[0]));
+
+        // the vertical sync pin is low once per field
+        let v_sync_starts = samples
+            .windows(2)
+            .filter(|pair| !is_v_sync(pair[0].1) && is_v_sync(pair[1].1))
+            .count()
+            + is_v_sync(samples[0].1) as usize;
+        let v_sync_ticks = samples.iter().filter(|(_, pins)| is_v_sync(*pins)).count();
+        assert_eq!((standard.v_sync_lines != 0) as usize, v_sync_starts);
+        assert_eq!(
+            standard.v_sync_lines * standard.full_line as usize,
+            v_sync_ticks
+        );
+    }
+
+    #[test]
+    fn pal() {
+        check(&PAL, &PAL_LIMITS);
+        check_timeline(&PAL, &PAL_LIMITS);
+    }
+
+    #[test]
+    fn ntsc() {
+        check(&NTSC, &NTSC_LIMITS);
+        check_timeline(&NTSC, &NTSC_LIMITS);
+    }
+
+    #[test]
+    fn vga() {
+        check(&VGA, &VGA_LIMITS);
+        check_timeline(&VGA, &VGA_LIMITS);
+    }
+
+    #[test]
+    fn monochrome_composite() {
+        // every luma on every row, the palette columns choose the palette
+        let mut vid_ram = [0u8; WIDTH * HEIGHT];
+        let mut indexed_pixels = [0u8; WIDTH * HEIGHT];
+        let mut screen = Screen::with_colors(&mut vid_ram, &mut indexed_pixels, &LUMA);
+        for y in 0..HEIGHT as u8 {
+            let mut pixels = [0u8; WIDTH];
+            for (x, pixel) in pixels.iter_mut().enumerate() {
+                *pixel = (x as u8 + y) % 4;
+            }
+            pixels[125] = y % 4;
+            pixels[126] = y / 4 % 4;
+            screen.apply_row(y, 0, &pixels);
+        }
+
+        // a 300 mV sync pin and the luma pins summing up to 700 mV, in mV
+        let level = |pins: u32| {
+            let luma = (pins & PIXEL_PINS) >> 3;
+            (pins & SYNC_PIN != 0) as u32 * 300 + luma * 700 / 63
+        };
+
+        for (standard, limits) in [(PAL, PAL_LIMITS), (NTSC, NTSC_LIMITS)].iter() {
+            let samples = render(standard, &vid_ram);
+
+            for (signal, pins) in samples.iter() {
+                match *signal {
+                    // the pixel pins are off before the next sync pulse
+                    Signal::Sync => assert_eq!(0, level(*pins)),
+                    Signal::Blank => assert_eq!(300, level(*pins)),
+                    Signal::Pixel { row, column } => {
+                        let luma = vid_ram[row * WIDTH + column] as u32;
+                        assert_eq!(300 + luma * 700 / 63, level(*pins));
+                    }
+                }
+            }
+
+            // every pulse down to the sync level is a horizontal sync, an equalizing or a broad
+            // pulse
+            let mut pulses = Vec::new();
+            let mut len = 0;
+            for (_, pins) in samples.iter() {
+                if level(*pins) == 0 {
+                    len += 1;
+                } else if len > 0 {
+                    pulses.push(len as u16);
+                    len = 0;
+                }
+            }
+            let (short_sync_us, broad_sync_us) = limits.v_sync_us.unwrap();
+            for pulse in pulses.iter() {
+                let pulse_us = us(*pulse);
+                assert!([limits.h_sync_us, short_sync_us, broad_sync_us]
+                    .iter()
+                    .any(|nominal| (pulse_us - nominal).abs() <= 0.2));
+            }
+            assert_eq!(timing(standard, 0).len, pulses.len() + (len > 0) as usize);
+        }
+
+        // black and white
+        assert_eq!((0, 63), (LUMA[0], LUMA[1]));
+    }
+
+    #[test]
+    fn pal_as_before() {
+        // the hand written table of the video MCU
+        let timing = timing(&PAL, 110);
+        assert_eq!(320, timing.len);
+        assert_eq!((80, 272), (timing.start_at, timing.stop_at));
+        assert_eq!(1270, timing.pixel_start);
+
+        let line = |arr: u16, ccr: u16, has_pixels: bool| Line {
+            arr: arr - 110,
+            ccr: ccr - 110,
+            has_pixels,
+            v_sync: false,
+        };
+        for (idx, entry) in timing.lines[..timing.len].iter().enumerate() {
+            let expected = match idx {
+                0..=4 => line(2305, 1966, false),
+                5..=9 => line(2305, 182, false),
+                10..=27 => line(2307 * 2, 344, false),
+                28..=313 => line(2307 * 2, 344, true),
+                _ => line(2305, 182, false),
+            };
+            assert_eq!(expected, *entry, "{}", idx);
+        }
+    }
+}
@@ -0,0 +1,781 @@
+//! Choosing a cartridge
+//!
+//! The screen shows the current directory, a page of entries and the title of the selected
+//! cartridge once the selection rests on it:
+//!
+//! ```text
+//! /GAMES/
+//! >..
+//!  TIC-TAC-TOE
+//!  MORE/
+//!
+//!
+//! VIDEOCART 1
+//! ```
+//!
+//! Forward / back move the selection, left / right a page, holding them repeats. Releasing push
+//! opens a directory or chooses the cartridge, pull goes back to the parent directory. Names
+//! longer than a row scroll while selected, cartridges are shown without `.bin`.
+//!
+//! Entries are shown in directory order. Only the shown page is kept, the directory is listed
+//! again when the page changes.
+
+use crate::{Name, NAME_LEN};
+
+pub const COLUMNS: usize = 12;
+pub const ROWS: usize = 7;
+
+/// Rows showing entries
+pub const PAGE: usize = ROWS - 2;
+
+/// Deepest directory which can be opened
+pub const MAX_DEPTH: usize = 4;
+
+// all in calls of `update`
+pub(crate) const REPEAT_DELAY: u16 = 40;
+pub(crate) const REPEAT_RATE: u16 = 8;
+const TITLE_DELAY: u16 = 50;
+const SCROLL_RATE: u16 = 20;
+
+// in characters scrolled, a long name rests at its start and end
+const SCROLL_PAUSE: usize = 3;
+
+/// Characters of the screen, row by row
+pub type Text = [[u8; COLUMNS]; ROWS];
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Controller {
+    pub left: bool,
+    pub right: bool,
+    pub forward: bool,
+    pub back: bool,
+    pub push: bool,
+    pub pull: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Entry {
+    /// The 8.3 name the entry is opened by, e.g. `TICTAC.BIN`
+    pub short_name: Name,
+    pub is_dir: bool,
+    /// Whatever the listing needs to find the entry again
+    pub location: u32,
+}
+
+/// The directories of the SD-card
+///
+/// `path` holds the 8.3 names of the directories from the root directory.
+pub trait Listing {
+    type Error;
+
+    /// Calls `entry` with every entry of the directory
+    fn list(&mut self, path: &[Name], entry: &mut dyn FnMut(&Entry)) -> Result<(), Self::Error>;
+
+    /// The long file name of an entry if it has one
+    fn long_name(&mut self, path: &[Name], entry: &Entry) -> Result<Option<Name>, Self::Error>;
+
+    /// The title of a cartridge if the database has it
+    fn title(&mut self, path: &[Name], entry: &Entry) -> Result<Option<Name>, Self::Error>;
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Action {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Open,
+    Back,
+}
+
+pub struct Browser {
+    path: [Name; MAX_DEPTH],
+    depth: usize,
+    /// Entries of the directory, `..` included
+    count: usize,
+    selected: usize,
+    first: usize,
+    page: [Option<(Entry, Name)>; PAGE],
+    listed: bool,
+    /// Selected after listing the directory, the directory we came from
+    reselect: Option<Name>,
+    title: Option<Name>,
+    previous: Controller,
+    held: u16,
+    idle: u16,
+}
+
+impl Browser {
+    pub fn new() -> Browser {
+        Browser {
+            path: [Name::new(b""); MAX_DEPTH],
+            depth: 0,
+            count: 0,
+            selected: 0,
+            first: 0,
+            page: [None; PAGE],
+            listed: false,
+            reselect: None,
+            title: None,
+            previous: Controller::default(),
+            held: 0,
+            idle: 0,
+        }
+    }
+
+    /// Starts in the directory `path` with the entry `selected`, e.g. the cartridge chosen last
+    ///
+    /// The directory has to exist.
+    pub fn at(path: &[Name], selected: Option<Name>) -> Browser {
+        let mut browser = Browser::new();
+        browser.depth = path.len().min(MAX_DEPTH);
+        browser.path[..browser.depth].copy_from_slice(&path[..browser.depth]);
+        browser.reselect = selected;
+        browser
+    }
+
+    /// The directories leading to the current one
+    pub fn path(&self) -> &[Name] {
+        &self.path[..self.depth]
+    }
+
+    /// Call this periodically, returns the chosen cartridge - it's in the directory `path`
+    pub fn update<L: Listing>(
+        &mut self,
+        controller: Controller,
+        listing: &mut L,
+    ) -> Result<Option<Entry>, L::Error> {
+        if !self.listed {
+            self.list(listing)?;
+        }
+
+        let action = self.action(controller);
+        self.previous = controller;
+
+        let action = match action {
+            Some(action) => action,
+            None => {
+                self.idle = self.idle.saturating_add(1);
+                if self.idle == TITLE_DELAY {
+                    if let Some((entry, _)) =
+                        self.selected_entry().filter(|(entry, _)| !entry.is_dir)
+                    {
+                        self.title = listing.title(self.path(), &entry)?;
+                    }
+                }
+                return Ok(None);
+            }
+        };
+
+        self.idle = 0;
+        self.title = None;
+
+        let last = self.count.saturating_sub(1);
+        match action {
+            Action::Up => self.selected = self.selected.saturating_sub(1),
+            Action::Down => self.selected = (self.selected + 1).min(last),
+            Action::PageUp => self.selected = self.selected.saturating_sub(PAGE),
+            Action::PageDown => self.selected = (self.selected + PAGE).min(last),
+            Action::Back => {
+                if self.depth > 0 {
+                    self.leave_dir(listing)?;
+                }
+            }
+            Action::Open => match self.selected_entry() {
+                Some((entry, _)) if is_parent(&entry) => self.leave_dir(listing)?,
+                Some((entry, _)) if entry.is_dir => {
+                    self.enter_dir(entry.short_name, listing)?;
+                }
+                Some((entry, _)) => return Ok(Some(entry)),
+                None => {}
+            },
+        }
+
+        if self.selected < self.first {
+            self.first = self.selected;
+            self.list(listing)?;
+        } else if self.selected >= self.first + PAGE {
+            self.first = self.selected + 1 - PAGE;
+            self.list(listing)?;
+        }
+
+        Ok(None)
+    }
+
+    /// What to show
+    pub fn text(&self) -> Text {
+        let mut text = [[b' '; COLUMNS]; ROWS];
+
+        let mut path = [0u8; 1 + MAX_DEPTH * (NAME_LEN + 1)];
+        let mut len = 1;
+        path[0] = b'/';
+        for name in self.path() {
+            path[len..len + name.len()].copy_from_slice(name.as_bytes());
+            path[len + name.len()] = b'/';
+            len += name.len() + 1;
+        }
+        // the end of the path is the interesting part
+        put(&mut text[0], &path[len.saturating_sub(COLUMNS)..len]);
+
+        if self.count == 0 {
+            put(&mut text[1][1..], b"NO CARTS");
+        }
+
+        for (idx, shown) in self.page.iter().enumerate() {
+            if let Some((entry, name)) = shown {
+                let row = &mut text[1 + idx];
+                let mut shown = [0u8; NAME_LEN + 1];
+                let mut len = name.len();
+                shown[..len].copy_from_slice(name.as_bytes());
+                if !entry.is_dir && is_cartridge(name.as_bytes()) {
+                    len -= 4;
+                } else if entry.is_dir && !is_parent(entry) {
+                    shown[len] = b'/';
+                    len += 1;
+                }
+
+                let offset = if self.first + idx == self.selected {
+                    row[0] = b'>';
+                    scrolled(len, COLUMNS - 1, self.idle)
+                } else {
+                    0
+                };
+                put(&mut row[1..], &shown[offset..len]);
+            }
+        }
+
+        if let Some(title) = self.title {
+            let offset = scrolled(title.len(), COLUMNS, self.idle - TITLE_DELAY);
+            put(&mut text[ROWS - 1], &title.as_bytes()[offset..]);
+        }
+
+        text
+    }
+
+    fn selected_entry(&self) -> Option<(Entry, Name)> {
+        self.page
+            .get(self.selected.checked_sub(self.first)?)
+            .copied()?
+    }
+
+    fn action(&mut self, controller: Controller) -> Option<Action> {
+        // a cartridge starts on release, otherwise it would see push held
+        if self.previous.push && !controller.push {
+            return Some(Action::Open);
+        }
+        if controller.pull && !self.previous.pull {
+            return Some(Action::Back);
+        }
+
+        let direction = |controller: &Controller| {
+            if controller.forward {
+                Some(Action::Up)
+            } else if controller.back {
+                Some(Action::Down)
+            } else if controller.left {
+                Some(Action::PageUp)
+            } else if controller.right {
+                Some(Action::PageDown)
+            } else {
+                None
+            }
+        };
+
+        let now = direction(&controller);
+        if now.is_none() || now != direction(&self.previous) {
+            self.held = 0;
+            return now;
+        }
+
+        self.held += 1;
+        if self.held == REPEAT_DELAY {
+            self.held -= REPEAT_RATE;
+            now
+        } else {
+            None
+        }
+    }
+
+    fn enter_dir<L: Listing>(&mut self, name: Name, listing: &mut L) -> Result<(), L::Error> {
+        if self.depth == MAX_DEPTH {
+            return Ok(());
+        }
+
+        self.path[self.depth] = name;
+        self.depth += 1;
+        self.selected = 0;
+        self.first = 0;
+        self.list(listing)
+    }
+
+    fn leave_dir<L: Listing>(&mut self, listing: &mut L) -> Result<(), L::Error> {
+        self.depth -= 1;
+        self.reselect = Some(self.path[self.depth]);
+        self.selected = 0;
+        self.first = 0;
+        self.list(listing)
+    }
+
+    fn list<L: Listing>(&mut self, listing: &mut L) -> Result<(), L::Error> {
+        let path = &self.path[..self.depth];
+        let first = self.first;
+        let reselect = self.reselect.take();
+
+        let mut count = 0;
+        let mut page = [None; PAGE];
+        let mut found = None;
+        let mut add = |entry: &Entry| {
+            if reselect == Some(entry.short_name) {
+                found = Some(count);
+            }
+            if (first..first + PAGE).contains(&count) {
+                page[count - first] = Some(*entry);
+            }
+            count += 1;
+        };
+
+        if self.depth > 0 {
+            add(&Entry {
+                short_name: Name::new(b".."),
+                is_dir: true,
+                location: 0,
+            });
+        }
+        listing.list(path, &mut |entry| {
+            if is_shown(entry) {
+                add(entry);
+            }
+        })?;
+
+        self.count = count;
+        self.listed = true;
+
+        if let Some(found) = found {
+            self.selected = found;
+            if found >= PAGE {
+                self.first = found + 1 - PAGE;
+                return self.list(listing);
+            }
+        }
+
+        for (shown, entry) in self.page.iter_mut().zip(page.iter()) {
+            *shown = match entry {
+                Some(entry) if !is_parent(entry) => {
+                    let name = listing.long_name(path, entry)?;
+                    Some((*entry, name.unwrap_or(entry.short_name)))
+                }
+                Some(entry) => Some((*entry, entry.short_name)),
+                None => None,
+            };
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Browser {
+    fn default() -> Self {
+        Browser::new()
+    }
+}
+
+fn is_parent(entry: &Entry) -> bool {
+    entry.is_dir && entry.short_name.as_bytes() == b".."
+}
+
+// directories and cartridges, not the entries for the directory itself and its parent
+fn is_shown(entry: &Entry) -> bool {
+    let name = entry.short_name.as_bytes();
+    if entry.is_dir {
+        name != b"." && name != b".."
+    } else {
+        is_cartridge(name)
+    }
+}
+
+fn is_cartridge(name: &[u8]) -> bool {
+    name.len() > 4 && name[name.len() - 4..].eq_ignore_ascii_case(b".BIN")
+}
+
+// first character shown of a text scrolling through `width` characters
+fn scrolled(len: usize, width: usize, ticks: u16) -> usize {
+    if len <= width {
+        return 0;
+    }
+
+    let steps = (ticks / SCROLL_RATE) as usize;
+    let positions = len - width + 2 * SCROLL_PAUSE;
+    (steps % positions)
+        .saturating_sub(SCROLL_PAUSE)
+        .min(len - width)
+}
+
+fn put(row: &mut [u8], text: &[u8]) {
+    for (c, t) in row.iter_mut().zip(text.iter()) {
+        *c = *t;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use std::collections::HashMap;
+
+    struct File {
+        short_name: &'static str,
+        long_name: Option<&'static str>,
+        is_dir: bool,
+    }
+
+    fn file(short_name: &'static str, long_name: Option<&'static str>) -> File {
+        File {
+            short_name,
+            long_name,
+            is_dir: false,
+        }
+    }
+
+    fn dir(short_name: &'static str) -> File {
+        File {
+            short_name,
+            long_name: None,
+            is_dir: true,
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeListing {
+        dirs: HashMap<String, Vec<File>>,
+        titles: HashMap<&'static str, &'static str>,
+        lists: usize,
+    }
+
+    impl FakeListing {
+        fn files(&self, path: &[Name]) -> &Vec<File> {
+            let path: Vec<&str> = path.iter().map(|name| name.as_str()).collect();
+            &self.dirs[&path.join("/")]
+        }
+    }
+
+    impl Listing for FakeListing {
+        type Error = ();
+
+        fn list(&mut self, path: &[Name], entry: &mut dyn FnMut(&Entry)) -> Result<(), ()> {
+            self.lists += 1;
+            for (location, file) in self.files(path).iter().enumerate() {
+                entry(&Entry {
+                    short_name: Name::new(file.short_name.as_bytes()),
+                    is_dir: file.is_dir,
+                    location: location as u32,
+                });
+            }
+            Ok(())
+        }
+
+        fn long_name(&mut self, path: &[Name], entry: &Entry) -> Result<Option<Name>, ()> {
+            let file = &self.files(path)[entry.location as usize];
+            Ok(file.long_name.map(|name| Name::new(name.as_bytes())))
+        }
+
+        fn title(&mut self, _path: &[Name], entry: &Entry) -> Result<Option<Name>, ()> {
+            let title = self.titles.get(entry.short_name.as_str());
+            Ok(title.map(|title| Name::new(title.as_bytes())))
+        }
+    }
+
+    fn listing() -> FakeListing {
+        let mut listing = FakeListing::default();
+        listing.dirs.insert(
+            String::new(),
+            vec![
+                dir("."),
+                file("TICTAC.BIN", Some("Tic-Tac-Toe.bin")),
+                file("README.TXT", None),
+                dir("GAMES"),
+                file("HANGMAN.BIN", None),
+                file("DODGEI~1.BIN", Some("Dodge It (Videocart 16).bin")),
+                file("MAZE.BIN", None),
+                file("SHOOT.BIN", None),
+                file("BOWLING.BIN", None),
+            ],
+        );
+        listing.dirs.insert(
+            "GAMES".to_string(),
+            vec![dir("."), dir(".."), dir("MORE"), file("CHECKERS.BIN", None)],
+        );
+        listing
+            .dirs
+            .insert("GAMES/MORE".to_string(), vec![dir("."), dir("..")]);
+        listing
+            .titles
+            .insert("TICTAC.BIN", "Videocart 1 - Tic-Tac-Toe");
+        listing
+    }
+
+    fn lines(browser: &Browser) -> Vec<String> {
+        browser
+            .text()
+            .iter()
+            .map(|row| {
+                String::from_utf8(row.to_vec())
+                    .unwrap()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    const NOTHING: Controller = Controller {
+        left: false,
+        right: false,
+        forward: false,
+        back: false,
+        push: false,
+        pull: false,
+    };
+
+    const BACK: Controller = Controller {
+        back: true,
+        ..NOTHING
+    };
+
+    const PUSH: Controller = Controller {
+        push: true,
+        ..NOTHING
+    };
+
+    fn press(
+        browser: &mut Browser,
+        listing: &mut FakeListing,
+        controller: Controller,
+    ) -> Option<Entry> {
+        assert_eq!(None, browser.update(controller, listing).unwrap());
+        browser.update(NOTHING, listing).unwrap()
+    }
+
+    #[test]
+    fn cartridges_only() {
+        let mut listing = listing();
+        let mut browser = Browser::new();
+        browser.update(NOTHING, &mut listing).unwrap();
+
+        assert_eq!(
+            vec![
+                "/",
+                ">Tic-Tac-Toe",
+                " GAMES/",
+                " HANGMAN",
+                " Dodge It (V",
+                " MAZE",
+                ""
+            ],
+            lines(&browser)
+        );
+    }
+
+    #[test]
+    fn pages() {
+        let mut listing = listing();
+        let mut browser = Browser::new();
+        browser.update(NOTHING, &mut listing).unwrap();
+
+        for _ in 0..4 {
+            press(&mut browser, &mut listing, BACK);
+        }
+        assert_eq!(1, listing.lists);
+        assert_eq!(">MAZE", lines(&browser)[5]);
+
+        // the page scrolls by one entry
+        press(&mut browser, &mut listing, BACK);
+        assert_eq!(2, listing.lists);
+        assert_eq!(">SHOOT", lines(&browser)[5]);
+        assert_eq!(" GAMES/", lines(&browser)[1]);
+
+        press(&mut browser, &mut listing, BACK);
+        press(&mut browser, &mut listing, BACK);
+        assert_eq!(">BOWLING", lines(&browser)[5]);
+
+        let page_up = Controller {
+            left: true,
+            ..NOTHING
+        };
+        press(&mut browser, &mut listing, page_up);
+        assert_eq!(">GAMES/", lines(&browser)[1]);
+        press(&mut browser, &mut listing, page_up);
+        assert_eq!(">Tic-Tac-Toe", lines(&browser)[1]);
+
+        let page_down = Controller {
+            right: true,
+            ..NOTHING
+        };
+        press(&mut browser, &mut listing, page_down);
+        press(&mut browser, &mut listing, page_down);
+        assert_eq!(">BOWLING", lines(&browser)[5]);
+    }
+
+    #[test]
+    fn repeat() {
+        let mut listing = listing();
+        let mut browser = Browser::new();
+
+        for _ in 0..REPEAT_DELAY {
+            browser.update(BACK, &mut listing).unwrap();
+        }
+        assert_eq!(">GAMES/", lines(&browser)[2]);
+        browser.update(BACK, &mut listing).unwrap();
+        assert_eq!(">HANGMAN", lines(&browser)[3]);
+        for _ in 0..REPEAT_RATE {
+            browser.update(BACK, &mut listing).unwrap();
+        }
+        assert_eq!(">Dodge It (V", lines(&browser)[4]);
+    }
+
+    #[test]
+    fn directories() {
+        let mut listing = listing();
+        let mut browser = Browser::new();
+        browser.update(NOTHING, &mut listing).unwrap();
+
+        press(&mut browser, &mut listing, BACK);
+        press(&mut browser, &mut listing, PUSH);
+        assert_eq!(
+            vec!["/GAMES/", ">..", " MORE/", " CHECKERS", "", "", ""],
+            lines(&browser)
+        );
+
+        press(&mut browser, &mut listing, BACK);
+        press(&mut browser, &mut listing, PUSH);
+        assert_eq!(vec!["/GAMES/MORE/", ">.."], lines(&browser)[..2].to_vec());
+
+        // back via ".." selects the directory we came from
+        press(&mut browser, &mut listing, PUSH);
+        assert_eq!(">MORE/", lines(&browser)[2]);
+
+        let pull = Controller {
+            pull: true,
+            ..NOTHING
+        };
+        press(&mut browser, &mut listing, pull);
+        assert_eq!("/", lines(&browser)[0]);
+        assert_eq!(">GAMES/", lines(&browser)[2]);
+
+        // pull in the root directory does nothing
+        press(&mut browser, &mut listing, pull);
+        assert_eq!(">GAMES/", lines(&browser)[2]);
+    }
+
+    #[test]
+    fn reselect_on_a_later_page() {
+        let mut listing = listing();
+        listing.dirs.get_mut("").unwrap().push(dir("LAST"));
+        let mut browser = Browser::new();
+        browser.update(NOTHING, &mut listing).unwrap();
+
+        for _ in 0..8 {
+            press(&mut browser, &mut listing, BACK);
+        }
+        listing.dirs.insert("LAST".to_string(), vec![]);
+        press(&mut browser, &mut listing, PUSH);
+        assert_eq!(">..", lines(&browser)[1]);
+
+        press(&mut browser, &mut listing, PUSH);
+        assert_eq!(">LAST/", lines(&browser)[5]);
+    }
+
+    #[test]
+    fn start_at_the_last_cartridge() {
+        let mut listing = listing();
+        let mut browser = Browser::at(&[Name::new(b"GAMES")], Some(Name::new(b"CHECKERS.BIN")));
+        browser.update(NOTHING, &mut listing).unwrap();
+        assert_eq!(
+            vec!["/GAMES/", " ..", " MORE/", ">CHECKERS"],
+            lines(&browser)[..4].to_vec()
+        );
+
+        // an entry which is gone selects the first one
+        let mut browser = Browser::at(&[], Some(Name::new(b"GONE.BIN")));
+        browser.update(NOTHING, &mut listing).unwrap();
+        assert_eq!(">Tic-Tac-Toe", lines(&browser)[1]);
+    }
+
+    #[test]
+    fn long_path() {
+        // e.g. from the settings, not just 8.3 names
+        let name = Name::new(&[b'D'; NAME_LEN]);
+        let browser = Browser::at(&[name; MAX_DEPTH], None);
+        let mut expected = "D".repeat(COLUMNS - 1);
+        expected.push('/');
+        assert_eq!(expected, lines(&browser)[0]);
+    }
+
+    #[test]
+    fn choose() {
+        let mut listing = listing();
+        let mut browser = Browser::new();
+        browser.update(NOTHING, &mut listing).unwrap();
+
+        press(&mut browser, &mut listing, BACK);
+        press(&mut browser, &mut listing, PUSH);
+        press(&mut browser, &mut listing, BACK);
+        press(&mut browser, &mut listing, BACK);
+
+        // only on release
+        assert_eq!(None, browser.update(PUSH, &mut listing).unwrap());
+        assert_eq!(None, browser.update(PUSH, &mut listing).unwrap());
+        let chosen = browser.update(NOTHING, &mut listing).unwrap().unwrap();
+        assert_eq!("CHECKERS.BIN", chosen.short_name.as_str());
+        assert_eq!(
+            vec!["GAMES"],
+            browser
+                .path()
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn title_and_scrolling() {
+        let mut listing = listing();
+        let mut browser = Browser::new();
+
+        for _ in 0..TITLE_DELAY {
+            browser.update(NOTHING, &mut listing).unwrap();
+        }
+        assert_eq!("Videocart 1", lines(&browser)[6]);
+
+        for _ in 0..SCROLL_RATE * (SCROLL_PAUSE as u16 + 2) {
+            browser.update(NOTHING, &mut listing).unwrap();
+        }
+        assert_eq!("deocart 1 -", lines(&browser)[6]);
+        // the selected name is short enough
+        assert_eq!(">Tic-Tac-Toe", lines(&browser)[1]);
+
+        // no title in the database
+        press(&mut browser, &mut listing, BACK);
+        press(&mut browser, &mut listing, BACK);
+        press(&mut browser, &mut listing, BACK);
+        for _ in 0..SCROLL_RATE * (SCROLL_PAUSE as u16 + 1) {
+            browser.update(NOTHING, &mut listing).unwrap();
+        }
+        assert_eq!("", lines(&browser)[6]);
+        assert_eq!(">odge It (Vi", lines(&browser)[4]);
+    }
+
+    #[test]
+    fn empty() {
+        let mut listing = FakeListing::default();
+        listing
+            .dirs
+            .insert(String::new(), vec![file("NOTES.TXT", None)]);
+        let mut browser = Browser::new();
+
+        assert_eq!(None, browser.update(NOTHING, &mut listing).unwrap());
+        assert_eq!(None, press(&mut browser, &mut listing, PUSH));
+        assert_eq!(None, press(&mut browser, &mut listing, BACK));
+        assert_eq!(vec!["/", " NO CARTS"], lines(&browser)[..2].to_vec());
+    }
+}
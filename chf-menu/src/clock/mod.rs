@@ -0,0 +1,481 @@
+//! The date and time of the real-time clock and the screen setting it
+//!
+//! The clock counts the seconds since 1970-01-01 00:00, `DateTime` converts them to a date and
+//! to the timestamps of FAT directory entries.
+//!
+//! ```text
+//! SET CLOCK
+//!
+//!  2026-10-18
+//!  ----
+//!  12:34:56
+//!
+//! PUSH SETS IT
+//! ```
+//!
+//! Left / right choose a field, forward / back change it, holding them repeats. Releasing push
+//! sets the clock, releasing pull leaves it as it is.
+
+use crate::browser::{Controller, Text, COLUMNS, REPEAT_DELAY, REPEAT_RATE, ROWS};
+
+/// Years the clock can be set to
+pub const MIN_YEAR: u16 = 2000;
+pub const MAX_YEAR: u16 = 2099;
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DateTime {
+    pub year: u16,
+    /// 1 - 12
+    pub month: u8,
+    /// 1 - 31
+    pub day: u8,
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
+impl DateTime {
+    /// The time `seconds` after 1970-01-01 00:00
+    pub fn from_seconds(seconds: u32) -> DateTime {
+        let mut days = seconds / SECONDS_PER_DAY;
+        let time = seconds % SECONDS_PER_DAY;
+
+        let mut year = 1970;
+        while days >= days_in_year(year) {
+            days -= days_in_year(year);
+            year += 1;
+        }
+        let mut month = 1;
+        while days >= days_in_month(year, month) as u32 {
+            days -= days_in_month(year, month) as u32;
+            month += 1;
+        }
+
+        DateTime {
+            year,
+            month,
+            day: days as u8 + 1,
+            hours: (time / 3600) as u8,
+            minutes: (time / 60 % 60) as u8,
+            seconds: (time % 60) as u8,
+        }
+    }
+
+    /// The seconds since 1970-01-01 00:00
+    pub fn seconds(&self) -> u32 {
+        let mut days = (1970..self.year).map(days_in_year).sum::<u32>();
+        days += (1..self.month)
+            .map(|month| days_in_month(self.year, month) as u32)
+            .sum::<u32>();
+        days += self.day as u32 - 1;
+
+        days * SECONDS_PER_DAY
+            + self.hours as u32 * 3600
+            + self.minutes as u32 * 60
+            + self.seconds as u32
+    }
+
+    /// The date of a FAT directory entry: years since 1980, month and day
+    pub fn fat_date(&self) -> u16 {
+        (self.year.saturating_sub(1980) << 9) | (self.month as u16) << 5 | self.day as u16
+    }
+
+    /// The time of a FAT directory entry, in steps of two seconds
+    pub fn fat_time(&self) -> u16 {
+        (self.hours as u16) << 11 | (self.minutes as u16) << 5 | (self.seconds / 2) as u16
+    }
+}
+
+pub fn is_leap_year(year: u16) -> bool {
+    match (year % 4, year % 100, year % 400) {
+        (0, 0, 0) => true,
+        (0, 0, _) => false,
+        (0, _, _) => true,
+        _ => false,
+    }
+}
+
+/// Number of days of `month`, 1 - 12
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn days_in_year(year: u16) -> u32 {
+    if is_leap_year(year) {
+        366
+    } else {
+        365
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Edit {
+    Editing,
+    Set(DateTime),
+    Cancelled,
+}
+
+// year, month, day, hours, minutes, seconds
+const FIELDS: usize = 6;
+
+// where the fields are shown, row and column
+const FIELD_AT: [(usize, usize); FIELDS] = [(2, 1), (2, 6), (2, 9), (4, 1), (4, 4), (4, 7)];
+
+#[derive(Debug)]
+pub struct ClockEditor {
+    time: DateTime,
+    field: usize,
+    previous: Controller,
+    held: u16,
+}
+
+impl ClockEditor {
+    /// Starts with `time`, a clock never set starts at the first day of `MIN_YEAR`
+    pub fn new(time: DateTime) -> ClockEditor {
+        let time = if (MIN_YEAR..=MAX_YEAR).contains(&time.year) {
+            time
+        } else {
+            DateTime {
+                year: MIN_YEAR,
+                month: 1,
+                day: 1,
+                hours: 0,
+                minutes: 0,
+                seconds: 0,
+            }
+        };
+
+        ClockEditor {
+            time,
+            field: 0,
+            previous: Controller::default(),
+            held: 0,
+        }
+    }
+
+    /// Call this periodically, like `Browser::update`
+    pub fn update(&mut self, controller: Controller) -> Edit {
+        let previous = self.previous;
+        let step = self.step(controller);
+        self.previous = controller;
+
+        if previous.push && !controller.push {
+            return Edit::Set(self.time);
+        }
+        if previous.pull && !controller.pull {
+            return Edit::Cancelled;
+        }
+
+        match step {
+            Some(Step::Left) => self.field = self.field.saturating_sub(1),
+            Some(Step::Right) => self.field = (self.field + 1).min(FIELDS - 1),
+            Some(Step::Up) => self.change(true),
+            Some(Step::Down) => self.change(false),
+            None => {}
+        }
+        Edit::Editing
+    }
+
+    /// What to show
+    pub fn text(&self) -> Text {
+        let mut text = [[b' '; COLUMNS]; ROWS];
+        put(&mut text[0], b"SET CLOCK");
+        put(&mut text[ROWS - 1], b"PUSH SETS IT");
+
+        let time = &self.time;
+        let mut date = [b'-'; 10];
+        digits(&mut date[..4], time.year as u32);
+        digits(&mut date[5..7], time.month as u32);
+        digits(&mut date[8..], time.day as u32);
+        put(&mut text[2][1..], &date);
+
+        let mut clock = [b':'; 8];
+        digits(&mut clock[..2], time.hours as u32);
+        digits(&mut clock[3..5], time.minutes as u32);
+        digits(&mut clock[6..], time.seconds as u32);
+        put(&mut text[4][1..], &clock);
+
+        // the chosen field is underlined
+        let (row, column) = FIELD_AT[self.field];
+        let len = if self.field == 0 { 4 } else { 2 };
+        put(&mut text[row + 1][column..], &[b'-'; 4][..len]);
+
+        text
+    }
+
+    fn change(&mut self, up: bool) {
+        let time = &mut self.time;
+        let (value, first, last) = match self.field {
+            0 => (time.year as u32, MIN_YEAR as u32, MAX_YEAR as u32),
+            1 => (time.month as u32, 1, 12),
+            2 => (
+                time.day as u32,
+                1,
+                days_in_month(time.year, time.month) as u32,
+            ),
+            3 => (time.hours as u32, 0, 23),
+            4 => (time.minutes as u32, 0, 59),
+            _ => (time.seconds as u32, 0, 59),
+        };
+
+        let value = wrapped(value, first, last, up);
+        match self.field {
+            0 => time.year = value as u16,
+            1 => time.month = value as u8,
+            2 => time.day = value as u8,
+            3 => time.hours = value as u8,
+            4 => time.minutes = value as u8,
+            _ => time.seconds = value as u8,
+        }
+        // e.g. March 31st becomes February 28th
+        time.day = time.day.min(days_in_month(time.year, time.month));
+    }
+
+    // like the selection of the browser
+    fn step(&mut self, controller: Controller) -> Option<Step> {
+        let direction = |controller: &Controller| {
+            if controller.forward {
+                Some(Step::Up)
+            } else if controller.back {
+                Some(Step::Down)
+            } else if controller.left {
+                Some(Step::Left)
+            } else if controller.right {
+                Some(Step::Right)
+            } else {
+                None
+            }
+        };
+
+        let now = direction(&controller);
+        if now.is_none() || now != direction(&self.previous) {
+            self.held = 0;
+            return now;
+        }
+
+        self.held += 1;
+        if self.held == REPEAT_DELAY {
+            self.held -= REPEAT_RATE;
+            now
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Step {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+fn wrapped(value: u32, first: u32, last: u32, up: bool) -> u32 {
+    match (up, value) {
+        (true, value) if value >= last => first,
+        (true, value) => value + 1,
+        (false, value) if value <= first => last,
+        (false, value) => value - 1,
+    }
+}
+
+// `value` with leading zeros, as many digits as `out` is long
+fn digits(out: &mut [u8], mut value: u32) {
+    for c in out.iter_mut().rev() {
+        *c = b'0' + (value % 10) as u8;
+        value /= 10;
+    }
+}
+
+fn put(row: &mut [u8], text: &[u8]) {
+    for (c, t) in row.iter_mut().zip(text.iter()) {
+        *c = *t;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    const NOTHING: Controller = Controller {
+        left: false,
+        right: false,
+        forward: false,
+        back: false,
+        push: false,
+        pull: false,
+    };
+
+    fn date_time(year: u16, month: u8, day: u8, hours: u8, minutes: u8, seconds: u8) -> DateTime {
+        DateTime {
+            year,
+            month,
+            day,
+            hours,
+            minutes,
+            seconds,
+        }
+    }
+
+    fn press(editor: &mut ClockEditor, controller: Controller) -> Edit {
+        editor.update(controller);
+        editor.update(NOTHING)
+    }
+
+    fn lines(editor: &ClockEditor) -> Vec<String> {
+        editor
+            .text()
+            .iter()
+            .map(|row| {
+                String::from_utf8(row.to_vec())
+                    .unwrap()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(date_time(1970, 1, 1, 0, 0, 0), DateTime::from_seconds(0));
+        assert_eq!(
+            date_time(2000, 2, 29, 23, 59, 59),
+            DateTime::from_seconds(951_868_799)
+        );
+        assert_eq!(
+            date_time(2026, 10, 18, 12, 34, 56),
+            DateTime::from_seconds(1_792_326_896)
+        );
+        assert_eq!(
+            date_time(2106, 2, 7, 6, 28, 15),
+            DateTime::from_seconds(u32::MAX)
+        );
+
+        for seconds in [0, 68_169_600, 951_868_799, 951_868_800, 4_107_542_400].iter() {
+            assert_eq!(*seconds, DateTime::from_seconds(*seconds).seconds());
+        }
+
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(2100));
+        assert_eq!(29, days_in_month(2024, 2));
+        assert_eq!(28, days_in_month(2023, 2));
+        assert_eq!(30, days_in_month(2023, 11));
+    }
+
+    #[test]
+    fn fat() {
+        let time = date_time(2026, 10, 18, 12, 34, 57);
+        assert_eq!((46 << 9) | (10 << 5) | 18, time.fat_date());
+        assert_eq!((12 << 11) | (34 << 5) | 28, time.fat_time());
+
+        assert_eq!(0x21, date_time(1980, 1, 1, 0, 0, 0).fat_date());
+        assert_eq!(0xbf7d, date_time(2023, 11, 29, 23, 59, 58).fat_time());
+    }
+
+    #[test]
+    fn edit() {
+        let mut editor = ClockEditor::new(DateTime::from_seconds(0));
+        assert_eq!(
+            vec![
+                "SET CLOCK",
+                "",
+                " 2000-01-01",
+                " ----",
+                " 00:00:00",
+                "",
+                "PUSH SETS IT"
+            ],
+            lines(&editor)
+        );
+
+        let back = Controller {
+            back: true,
+            ..NOTHING
+        };
+        let right = Controller {
+            right: true,
+            ..NOTHING
+        };
+        let forward = Controller {
+            forward: true,
+            ..NOTHING
+        };
+
+        // the year wraps around, the day is kept in its month
+        press(&mut editor, back);
+        press(&mut editor, right);
+        press(&mut editor, back);
+        press(&mut editor, right);
+        press(&mut editor, back);
+        assert_eq!(" 2099-12-31", lines(&editor)[2]);
+        assert_eq!("         --", lines(&editor)[3]);
+        press(
+            &mut editor,
+            Controller {
+                left: true,
+                ..NOTHING
+            },
+        );
+        press(&mut editor, back);
+        press(&mut editor, back);
+        assert_eq!(" 2099-10-30", lines(&editor)[2]);
+        press(&mut editor, back);
+        assert_eq!(" 2099-09-30", lines(&editor)[2]);
+
+        // holding repeats
+        for _ in 0..3 {
+            press(&mut editor, right);
+        }
+        assert_eq!("    --", lines(&editor)[5]);
+        for _ in 0..=REPEAT_DELAY + REPEAT_RATE {
+            editor.update(forward);
+        }
+        assert_eq!(" 00:03:00", lines(&editor)[4]);
+
+        // set on releasing push, left as it is on releasing pull
+        assert_eq!(
+            Edit::Editing,
+            editor.update(Controller {
+                push: true,
+                ..NOTHING
+            })
+        );
+        assert_eq!(
+            Edit::Set(date_time(2099, 9, 30, 0, 3, 0)),
+            editor.update(NOTHING)
+        );
+        assert_eq!(
+            Edit::Cancelled,
+            press(
+                &mut editor,
+                Controller {
+                    pull: true,
+                    ..NOTHING
+                }
+            )
+        );
+
+        let time = date_time(2026, 10, 18, 12, 34, 56);
+        assert_eq!(
+            Edit::Set(time),
+            press(
+                &mut ClockEditor::new(time),
+                Controller {
+                    push: true,
+                    ..NOTHING
+                }
+            )
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Titles of the cartridges
+//!
+//! The database is a text file on the SD-card, one cartridge per line: the CRC-32 of the
+//! cartridge as eight hex digits, a space and the title. Lines starting with `#` are comments.
+//! A `|` after the title starts what the cartridge keeps in its save file, see
+//! `chf_emulator::save`.
+//!
+//! ```text
+//! # crc32   title
+//! 0123abcd  Videocart 1 - Tic-Tac-Toe
+//! 89abcdef  Videocart 18 - Hangman | @120 M 2800-280f
+//! ```
+//!
+//! The file is searched while it's read block by block, nothing is kept but the current line.
+
+use crate::{Name, NAME_LEN};
+
+// the CRC, spaces, the title and the save ranges - the rest of longer lines is ignored
+const LINE_LEN: usize = 8 + 2 + NAME_LEN + 3 + NAME_LEN;
+
+/// The line of a cartridge
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Record {
+    pub title: Name,
+    /// The ranges kept in its save file, the text after the `|`
+    pub save: Option<Name>,
+}
+
+pub struct Search {
+    crc: u32,
+    line: [u8; LINE_LEN],
+    len: usize,
+    found: Option<Record>,
+}
+
+impl Search {
+    pub fn new(crc: u32) -> Search {
+        Search {
+            crc,
+            line: [0u8; LINE_LEN],
+            len: 0,
+            found: None,
+        }
+    }
+
+    /// The next bytes of the file
+    pub fn push(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            match *byte {
+                b'\n' => {
+                    self.end_line();
+                }
+                b'\r' => {}
+                byte => {
+                    if self.len < LINE_LEN {
+                        self.line[self.len] = byte;
+                        self.len += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The line of the cartridge if the database has it
+    pub fn finish(mut self) -> Option<Record> {
+        self.end_line();
+        self.found
+    }
+
+    fn end_line(&mut self) {
+        let line = &self.line[..self.len];
+        self.len = 0;
+
+        if self.found.is_some() || line.len() < 9 || line[0] == b'#' {
+            return;
+        }
+
+        let crc = core::str::from_utf8(&line[..8])
+            .ok()
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+        if crc != Some(self.crc) || line[8] != b' ' {
+            return;
+        }
+
+        let rest = &line[9..];
+        let (title, save) = match rest.iter().position(|c| *c == b'|') {
+            Some(bar) => (&rest[..bar], Some(trimmed(&rest[bar + 1..]))),
+            None => (rest, None),
+        };
+        let title = trimmed(title);
+        if !title.is_empty() {
+            self.found = Some(Record {
+                title: Name::new(title),
+                save: save.filter(|save| !save.is_empty()).map(Name::new),
+            });
+        }
+    }
+}
+
+fn trimmed(text: &[u8]) -> &[u8] {
+    let start = text.iter().position(|c| *c != b' ').unwrap_or(text.len());
+    let end = text
+        .iter()
+        .rposition(|c| *c != b' ')
+        .map_or(0, |end| end + 1);
+    &text[start..end.max(start)]
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    const DATABASE: &[u8] = b"# crc32   title\r\n\
+        0123abcd  Videocart 1 - Tic-Tac-Toe\r\n\
+        \r\n\
+        DEADBEEF Hangman  \r\n\
+        0000002a  A title which is longer than the longest name\r\n\
+        00000001\r\n\
+        xyz12345 Not a CRC\r\n\
+        00000009 Hangman | @120 M 2800-280f \r\n\
+        0000000a Nothing kept |\r\n\
+        00000007 Last line without newline";
+
+    fn search(crc: u32, chunk_len: usize) -> Option<Record> {
+        let mut search = Search::new(crc);
+        for chunk in DATABASE.chunks(chunk_len) {
+            search.push(chunk);
+        }
+        search.finish()
+    }
+
+    #[test]
+    fn titles() {
+        for chunk_len in [1, 7, 512].iter() {
+            let title =
+                |crc: u32| search(crc, *chunk_len).map(|record| record.title.as_str().to_string());
+            assert_eq!(
+                Some("Videocart 1 - Tic-Tac-Toe".to_string()),
+                title(0x0123_abcd)
+            );
+            assert_eq!(Some("Hangman".to_string()), title(0xdead_beef));
+            assert_eq!(
+                Some("A title which is longer than the".to_string()),
+                title(0x2a)
+            );
+            assert_eq!(Some("Last line without newline".to_string()), title(0x07));
+            assert_eq!(None, title(0x01));
+            assert_eq!(None, title(0x1234_5678));
+        }
+    }
+
+    #[test]
+    fn save_ranges() {
+        let save = |crc: u32| {
+            search(crc, 5)
+                .unwrap()
+                .save
+                .map(|save| save.as_str().to_string())
+        };
+        assert_eq!(Some("@120 M 2800-280f".to_string()), save(0x09));
+        assert_eq!(
+            Some("Hangman".to_string()),
+            search(0x09, 5).map(|r| r.title.as_str().to_string())
+        );
+        assert_eq!(None, save(0x0a));
+        assert_eq!(None, save(0x0123_abcd));
+    }
+}
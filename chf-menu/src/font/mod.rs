@@ -0,0 +1,107 @@
+//! The 8 x 8 font of the menu
+//!
+//! Letters are upper case only, lower case letters are drawn upper case.
+
+/// Character followed by its eight rows, bit 7 is the leftmost pixel
+const CHARACTERS: &[u8] = &[
+    b'A', 0b00011000, 0b00100100, 0b01000010, 0b01000010, 0b01111110, 0b01000010, 0b01000010,
+    0b01000010, b'B', 0b11111000, 0b10000100, 0b10000100, 0b11111100, 0b10000010, 0b10000010,
+    0b10000010, 0b11111100, b'C', 0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b10000000,
+    0b10000000, 0b10000000, 0b11111110, b'D', 0b11111000, 0b10000100, 0b10000010, 0b10000010,
+    0b10000010, 0b10000010, 0b10000100, 0b11111000, b'E', 0b11111110, 0b10000000, 0b10000000,
+    0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b11111110, b'F', 0b11111110, 0b10000000,
+    0b10000000, 0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b10000000, b'G', 0b11111110,
+    0b10000000, 0b10000000, 0b10001110, 0b10000010, 0b10000010, 0b10000010, 0b11111110, b'H',
+    0b10000010, 0b10000010, 0b10000010, 0b11111110, 0b10000010, 0b10000010, 0b10000010, 0b10000010,
+    b'I', 0b00111000, 0b00010000, 0b00010000, 0b00010000, 0b00010000, 0b00010000, 0b00010000,
+    0b00111000, b'J', 0b00000100, 0b00000100, 0b00000100, 0b00000100, 0b00000100, 0b00000100,
+    0b01000100, 0b00111000, b'K', 0b10000010, 0b10000100, 0b10001000, 0b11110000, 0b10001000,
+    0b10000100, 0b10000010, 0b10000010, b'L', 0b10000000, 0b10000000, 0b10000000, 0b10000000,
+    0b10000000, 0b10000000, 0b10000000, 0b11111110, b'M', 0b10000010, 0b11000110, 0b10011010,
+    0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10000010, b'N', 0b10000010, 0b11000010,
+    0b10100010, 0b10010010, 0b10010010, 0b10001010, 0b10000110, 0b10000010, b'O', 0b01111100,
+    0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b01111100, b'P',
+    0b11111110, 0b10000010, 0b10000010, 0b11111100, 0b10000000, 0b10000000, 0b10000000, 0b10000000,
+    b'Q', 0b01111100, 0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10001010, 0b10000110,
+    0b01111110, b'R', 0b11111110, 0b10000010, 0b10000010, 0b11111100, 0b10000010, 0b10000010,
+    0b10000010, 0b10000010, b'S', 0b01111100, 0b10000010, 0b10000000, 0b10000000, 0b01111100,
+    0b00000010, 0b10000010, 0b01111000, b'T', 0b11111110, 0b00010000, 0b00010000, 0b00010000,
+    0b00010000, 0b00010000, 0b00010000, 0b00010000, b'U', 0b10000010, 0b10000010, 0b10000010,
+    0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b01111100, b'V', 0b10000010, 0b10000010,
+    0b10000010, 0b01000100, 0b01000100, 0b01000100, 0b00101000, 0b00010000, b'W', 0b10000010,
+    0b10000010, 0b10000010, 0b10000010, 0b10010010, 0b10101010, 0b11000110, 0b10000010, b'X',
+    0b10000010, 0b10000010, 0b01000100, 0b01000100, 0b00011000, 0b00100100, 0b00100100, 0b10000010,
+    b'Y', 0b10000010, 0b10000010, 0b01000100, 0b00101000, 0b00010000, 0b00010000, 0b00010000,
+    0b00010000, b'Z', 0b11111110, 0b00000100, 0b00000100, 0b00010000, 0b00100000, 0b01000000,
+    0b01000000, 0b11111110, b'0', 0b01111100, 0b10000010, 0b10000010, 0b10000010, 0b10000010,
+    0b10000010, 0b10000010, 0b01111100, b'1', 0b00010000, 0b00110000, 0b01010000, 0b00010000,
+    0b00010000, 0b00010000, 0b00010000, 0b00010000, b'2', 0b11111110, 0b00000010, 0b00000010,
+    0b00000010, 0b11111110, 0b10000000, 0b10000000, 0b11111110, b'3', 0b11111100, 0b00000010,
+    0b00000010, 0b11111100, 0b00000010, 0b00000010, 0b00000010, 0b11111100, b'4', 0b10000010,
+    0b10000010, 0b10000010, 0b11111110, 0b00000010, 0b00000010, 0b00000010, 0b00000010, b'5',
+    0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b11111110, 0b00000010, 0b00000010, 0b11111110,
+    b'6', 0b01111110, 0b10000000, 0b10000000, 0b11111100, 0b10000010, 0b10000010, 0b10000010,
+    0b11111100, b'7', 0b11111110, 0b00000010, 0b00000100, 0b00001000, 0b00010000, 0b00010000,
+    0b00010000, 0b00010000, b'8', 0b01111100, 0b10000010, 0b10000010, 0b01111100, 0b10000010,
+    0b10000010, 0b10000010, 0b01111100, b'9', 0b11111110, 0b10000010, 0b10000010, 0b11111110,
+    0b00000010, 0b00000010, 0b00000010, 0b00000010, b' ', 0, 0, 0, 0, 0, 0, 0, 0, b'.', 0, 0, 0, 0,
+    0, 0, 0b00110000, 0b00110000, b',', 0, 0, 0, 0, 0, 0b00110000, 0b00010000, 0b00100000, b'-', 0,
+    0, 0, 0b01111100, 0, 0, 0, 0, b'_', 0, 0, 0, 0, 0, 0, 0, 0b11111110, b'/', 0b00000010,
+    0b00000100, 0b00001000, 0b00010000, 0b00100000, 0b01000000, 0b10000000, 0, b'>', 0b01000000,
+    0b00100000, 0b00010000, 0b00001000, 0b00010000, 0b00100000, 0b01000000, 0, b':', 0, 0b00110000,
+    0b00110000, 0, 0, 0b00110000, 0b00110000, 0, b'!', 0b00010000, 0b00010000, 0b00010000,
+    0b00010000, 0b00010000, 0, 0b00010000, 0, b'?', 0b01111100, 0b10000010, 0b00000100, 0b00001000,
+    0b00010000, 0, 0b00010000, 0, b'(', 0b00001000, 0b00010000, 0b00100000, 0b00100000, 0b00100000,
+    0b00010000, 0b00001000, 0, b')', 0b00100000, 0b00010000, 0b00001000, 0b00001000, 0b00001000,
+    0b00010000, 0b00100000, 0, b'\'', 0b00010000, 0b00010000, 0b00100000, 0, 0, 0, 0, 0, b'&',
+    0b00110000, 0b01001000, 0b00110000, 0b01010000, 0b10001010, 0b10000100, 0b01111010, 0,
+];
+
+/// The rows of a character, characters not in the font are empty
+pub fn glyph(c: u8) -> [u8; 8] {
+    let c = c.to_ascii_uppercase();
+    let mut result = [0u8; 8];
+    for character in CHARACTERS.chunks(9) {
+        if character[0] == c {
+            result.copy_from_slice(&character[1..]);
+        }
+    }
+    result
+}
+
+/// Column 0 stays empty, columns 1 - 7 are bits 7 - 1 of a row
+pub fn is_set(glyph: &[u8; 8], x: u8, y: u8) -> bool {
+    x != 0 && glyph[y as usize] >> (8 - x) & 1 != 0
+}
+
+/// True if the font can draw the character
+pub fn has_glyph(c: u8) -> bool {
+    let c = c.to_ascii_uppercase();
+    CHARACTERS.chunks(9).any(|character| character[0] == c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters() {
+        assert_eq!(0, CHARACTERS.len() % 9);
+        for c in b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ .,-_/>:!?()'&".iter() {
+            assert!(has_glyph(*c));
+            // bit 0 would be drawn in column 0
+            assert!(glyph(*c).iter().all(|row| row & 1 == 0));
+        }
+        assert_eq!(glyph(b'A'), glyph(b'a'));
+        assert_eq!([0u8; 8], glyph(b'~'));
+    }
+
+    #[test]
+    fn columns() {
+        let one = glyph(b'1');
+        assert!(!is_set(&one, 0, 0));
+        assert!(is_set(&one, 4, 0));
+        assert!(is_set(&one, 3, 1) && is_set(&one, 4, 1));
+        assert!(!is_set(&one, 1, 7));
+    }
+}
@@ -0,0 +1,162 @@
+//! VFAT long file names
+//!
+//! A long name is stored in extra directory entries right in front of the 8.3 entry, last part
+//! first. Each holds 13 UCS-2 characters:
+//!
+//! |Offset|Contents|
+//! |---|---|
+//! |0|sequence number, bit 6 set on the last part|
+//! |1 - 10|characters 1 - 5|
+//! |11|attributes, always `0x0f`|
+//! |13|checksum of the 8.3 name|
+//! |14 - 25|characters 6 - 11|
+//! |28 - 31|characters 12 - 13|
+//!
+//! Characters which aren't ASCII become `?`.
+
+use crate::{Name, NAME_LEN};
+
+/// Bytes of a directory entry
+pub const ENTRY_LEN: usize = 32;
+
+const ATTR_LFN: u8 = 0x0f;
+const LAST_PART: u8 = 0x40;
+const DELETED: u8 = 0xe5;
+const CHARS_PER_ENTRY: usize = 13;
+const CHAR_OFFSETS: [usize; CHARS_PER_ENTRY] = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+
+/// Checksum of the 11 bytes of an 8.3 name as stored in the directory entry
+pub fn checksum(short_name: &[u8]) -> u8 {
+    short_name[..11]
+        .iter()
+        .fold(0u8, |sum, c| sum.rotate_right(1).wrapping_add(*c))
+}
+
+/// Finds the long name of the 8.3 entry at `offset` in `entries`, raw directory entries
+///
+/// `entries` should hold the entries in front of the 8.3 entry, e.g. the directory block before
+/// the one holding it. `None` if the entry has no long name or it's broken.
+pub fn long_name(entries: &[u8], offset: usize) -> Option<Name> {
+    let short = entries.get(offset..offset + ENTRY_LEN)?;
+    let checksum = checksum(short);
+
+    let mut name = [0u8; NAME_LEN];
+    let mut len = 0;
+    let mut sequence = 1;
+    let mut position = offset;
+
+    loop {
+        position = position.checked_sub(ENTRY_LEN)?;
+        let entry = &entries[position..position + ENTRY_LEN];
+
+        if entry[11] != ATTR_LFN
+            || entry[0] == DELETED
+            || entry[0] & !LAST_PART != sequence
+            || entry[13] != checksum
+        {
+            return None;
+        }
+
+        for (idx, char_offset) in CHAR_OFFSETS.iter().enumerate() {
+            let c = u16::from_le_bytes([entry[*char_offset], entry[char_offset + 1]]);
+            let idx = (sequence as usize - 1) * CHARS_PER_ENTRY + idx;
+            if c == 0 || c == 0xffff {
+                break;
+            }
+
+            if idx < NAME_LEN {
+                name[idx] = if c < 0x80 { c as u8 } else { b'?' };
+                len = len.max(idx + 1);
+            }
+        }
+
+        if entry[0] & LAST_PART != 0 {
+            return Some(Name::new(&name[..len]));
+        }
+        sequence += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    // the entries of `name` in front of its 8.3 entry, like a FAT driver writes them
+    fn entries(name: &str, short_name: &[u8; 11]) -> Vec<u8> {
+        let chars: Vec<u16> = name.encode_utf16().collect();
+        let parts = (chars.len() - 1) / CHARS_PER_ENTRY + 1;
+        let mut result = Vec::new();
+
+        for sequence in (1..=parts).rev() {
+            let mut entry = [0u8; ENTRY_LEN];
+            entry[0] = sequence as u8 | if sequence == parts { LAST_PART } else { 0 };
+            entry[11] = ATTR_LFN;
+            entry[13] = checksum(short_name);
+
+            for (idx, offset) in CHAR_OFFSETS.iter().enumerate() {
+                let char_idx = (sequence - 1) * CHARS_PER_ENTRY + idx;
+                let c = match char_idx {
+                    idx if idx < chars.len() => chars[idx],
+                    idx if idx == chars.len() => 0,
+                    _ => 0xffff,
+                };
+                entry[*offset..offset + 2].copy_from_slice(&c.to_le_bytes());
+            }
+            result.extend_from_slice(&entry);
+        }
+
+        let mut entry = [0u8; ENTRY_LEN];
+        entry[..11].copy_from_slice(short_name);
+        entry[11] = 0x20;
+        result.extend_from_slice(&entry);
+        result
+    }
+
+    #[test]
+    fn checksum_of_short_name() {
+        // the loop of the FAT specification: ((sum & 1) << 7) + (sum >> 1) + c
+        assert_eq!(0x59, checksum(b"VIDEOC~1BIN"));
+    }
+
+    #[test]
+    fn names() {
+        for name in ["Tic-Tac-Toe.bin", "Videocart 12.bin", "a", "exactly13char"].iter() {
+            let raw = entries(name, b"VIDEOC~1BIN");
+            let found = long_name(&raw, raw.len() - ENTRY_LEN).unwrap();
+            assert_eq!(name.as_bytes(), found.as_bytes());
+        }
+
+        let raw = entries(
+            "Dodge It (Videocart 16) - longer than we keep.bin",
+            b"DODGEI~1BIN",
+        );
+        let found = long_name(&raw, raw.len() - ENTRY_LEN).unwrap();
+        assert_eq!(&b"Dodge It (Videocart 16) - longer"[..], found.as_bytes());
+
+        let raw = entries("Schach \u{e4}\u{f6}.bin", b"SCHACH~1BIN");
+        let found = long_name(&raw, raw.len() - ENTRY_LEN).unwrap();
+        assert_eq!(&b"Schach ??.bin"[..], found.as_bytes());
+    }
+
+    #[test]
+    fn no_long_name() {
+        let mut raw = vec![0u8; ENTRY_LEN * 2];
+        raw[ENTRY_LEN..ENTRY_LEN + 11].copy_from_slice(b"GAME    BIN");
+        assert!(long_name(&raw, ENTRY_LEN).is_none());
+        assert!(long_name(&raw, 0).is_none());
+
+        // the long name of another entry
+        let mut raw = entries("Hangman.bin", b"HANGMAN BIN");
+        let len = raw.len();
+        raw[len - ENTRY_LEN..len - ENTRY_LEN + 11].copy_from_slice(b"MAZE    BIN");
+        assert!(long_name(&raw, len - ENTRY_LEN).is_none());
+
+        // a part is missing
+        let mut raw = entries("A name longer than thirteen.bin", b"ANAMEL~1BIN");
+        raw.drain(ENTRY_LEN..ENTRY_LEN * 2);
+        assert!(long_name(&raw, raw.len() - ENTRY_LEN).is_none());
+    }
+}
@@ -0,0 +1,69 @@
+//! The menu of the main MCU
+//!
+//! `browser::Browser` lists the cartridges on the SD-card and lets the player choose one. It
+//! doesn't know about the SD-card, it gets the entries of a directory via `browser::Listing` and
+//! renders into a grid of characters which the firmware draws with `font`.
+//!
+//! `lfn` puts the long file names together from the raw directory entries, `database` finds the
+//! title of a cartridge by its CRC-32. `settings` keeps the preferences of the menu, `clock` sets the
+//! real-time clock.
+
+#![no_std]
+
+pub mod browser;
+pub mod clock;
+pub mod database;
+pub mod font;
+pub mod lfn;
+pub mod settings;
+
+/// Longest name kept, longer names are cut
+pub const NAME_LEN: usize = 32;
+
+/// A file name or title, ASCII
+#[derive(Clone, Copy)]
+pub struct Name {
+    bytes: [u8; NAME_LEN],
+    len: u8,
+}
+
+impl Name {
+    pub fn new(name: &[u8]) -> Name {
+        let len = name.len().min(NAME_LEN);
+        let mut bytes = [0u8; NAME_LEN];
+        bytes[..len].copy_from_slice(&name[..len]);
+
+        Name {
+            bytes,
+            len: len as u8,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(self.as_bytes()).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Name) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl core::fmt::Debug for Name {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
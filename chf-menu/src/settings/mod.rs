@@ -0,0 +1,164 @@
+//! Preferences of the menu, kept in a text file on the SD-card
+//!
+//! One setting per line, the name, a space and the value. Lines starting with `#`, unknown
+//! settings and bad values are ignored.
+//!
+//! ```text
+//! dir GAMES/MORE
+//! cart CHECKERS.BIN
+//! saves on
+//! ```
+//!
+//! `dir` and `cart` are the directory and the 8.3 name of the cartridge chosen last, the menu
+//! starts there. `saves off` turns the save files off.
+
+use crate::browser::MAX_DEPTH;
+use crate::Name;
+
+/// Longest settings file read
+pub const MAX_LEN: usize = 256;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Settings {
+    path: [Name; MAX_DEPTH],
+    depth: usize,
+    pub cartridge: Option<Name>,
+    pub saves: bool,
+}
+
+impl Settings {
+    pub fn new() -> Settings {
+        Settings {
+            path: [Name::new(b""); MAX_DEPTH],
+            depth: 0,
+            cartridge: None,
+            saves: true,
+        }
+    }
+
+    /// The settings of a file, missing ones keep their defaults
+    pub fn parse(text: &[u8]) -> Settings {
+        let mut settings = Settings::new();
+        for line in text.split(|c| *c == b'\n') {
+            let line = match line.last() {
+                Some(b'\r') => &line[..line.len() - 1],
+                _ => line,
+            };
+            if line.first() == Some(&b'#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, |c| *c == b' ');
+            let name = parts.next().unwrap_or_default();
+            let value = parts.next().unwrap_or_default();
+            match name {
+                b"dir" => settings.set_path_text(value),
+                b"cart" if !value.is_empty() => settings.cartridge = Some(Name::new(value)),
+                b"saves" if value == b"on" || value == b"off" => settings.saves = value == b"on",
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    /// The directories leading to the one of the cartridge chosen last
+    pub fn path(&self) -> &[Name] {
+        &self.path[..self.depth]
+    }
+
+    pub fn set_path(&mut self, path: &[Name]) {
+        self.depth = path.len().min(MAX_DEPTH);
+        self.path[..self.depth].copy_from_slice(&path[..self.depth]);
+    }
+
+    /// Calls `out` with the contents of the file, piece by piece
+    pub fn write(&self, out: &mut dyn FnMut(&[u8])) {
+        out(b"dir ");
+        for (idx, name) in self.path().iter().enumerate() {
+            if idx > 0 {
+                out(b"/");
+            }
+            out(name.as_bytes());
+        }
+        out(b"\n");
+
+        if let Some(cartridge) = self.cartridge {
+            out(b"cart ");
+            out(cartridge.as_bytes());
+            out(b"\n");
+        }
+
+        out(if self.saves {
+            b"saves on\n"
+        } else {
+            b"saves off\n"
+        });
+    }
+
+    fn set_path_text(&mut self, text: &[u8]) {
+        self.depth = 0;
+        for name in text.split(|c| *c == b'/').filter(|name| !name.is_empty()) {
+            if self.depth == MAX_DEPTH {
+                break;
+            }
+            self.path[self.depth] = Name::new(name);
+            self.depth += 1;
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::prelude::v1::*;
+
+    use super::*;
+
+    fn written(settings: &Settings) -> String {
+        let mut text = Vec::new();
+        settings.write(&mut |bytes| text.extend_from_slice(bytes));
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let settings =
+            Settings::parse(b"# menu\r\ndir /GAMES/MORE/\r\ncart CHECKERS.BIN\r\nsaves off");
+        assert_eq!(
+            vec!["GAMES", "MORE"],
+            settings
+                .path()
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(Name::new(b"CHECKERS.BIN")), settings.cartridge);
+        assert!(!settings.saves);
+
+        let settings = Settings::parse(b"saves maybe\nvolume 11\ndir A/B/C/D/E\ncart\n");
+        assert_eq!(MAX_DEPTH, settings.path().len());
+        assert_eq!(None, settings.cartridge);
+        assert!(settings.saves);
+
+        assert_eq!(Settings::new(), Settings::parse(b""));
+    }
+
+    #[test]
+    fn write() {
+        let mut settings = Settings::new();
+        assert_eq!("dir \nsaves on\n", written(&settings));
+
+        settings.set_path(&[Name::new(b"GAMES"), Name::new(b"MORE")]);
+        settings.cartridge = Some(Name::new(b"CHECKERS.BIN"));
+        settings.saves = false;
+        let text = written(&settings);
+        assert_eq!("dir GAMES/MORE\ncart CHECKERS.BIN\nsaves off\n", text);
+        assert_eq!(settings, Settings::parse(text.as_bytes()));
+    }
+}
@@ -0,0 +1,67 @@
+use chf_emulator::{cheat::CheatTarget, Cpu};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Compare {
+    Equal,
+    Changed,
+    Increased,
+    Decreased,
+}
+
+/// Narrows down the memory location of e.g. the number of lives
+///
+/// Start a search, play until the value changes, keep the locations which changed the same way,
+/// repeat until only a few candidates are left.
+pub struct Search {
+    snapshot: Vec<u8>,
+    candidates: Vec<bool>,
+}
+
+impl Search {
+    pub fn new(cpu: &Cpu) -> Search {
+        Search {
+            snapshot: snapshot(cpu),
+            candidates: vec![true; CheatTarget::COUNT],
+        }
+    }
+
+    /// Keeps the candidates whose value compares to the last snapshot as given and takes a new snapshot
+    pub fn filter(&mut self, cpu: &Cpu, compare: Compare) {
+        let current = snapshot(cpu);
+
+        for (idx, candidate) in self.candidates.iter_mut().enumerate() {
+            let old = self.snapshot[idx];
+            let new = current[idx];
+            *candidate &= match compare {
+                Compare::Equal => new == old,
+                Compare::Changed => new != old,
+                Compare::Increased => new > old,
+                Compare::Decreased => new < old,
+            };
+        }
+
+        self.snapshot = current;
+    }
+
+    pub fn count(&self) -> usize {
+        self.candidates.iter().filter(|c| **c).count()
+    }
+
+    /// The remaining candidates with their current value
+    pub fn candidates(&self) -> impl Iterator<Item = (CheatTarget, u8)> + '_ {
+        self.candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| **candidate)
+            .filter_map(move |(idx, _)| {
+                CheatTarget::from_index(idx).map(|target| (target, self.snapshot[idx]))
+            })
+    }
+}
+
+fn snapshot(cpu: &Cpu) -> Vec<u8> {
+    (0..CheatTarget::COUNT)
+        .filter_map(CheatTarget::from_index)
+        .map(|target| cpu.cheat_target_value(target))
+        .collect()
+}
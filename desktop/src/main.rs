@@ -1,24 +1,111 @@
-use std::{cell::RefCell, env, fs};
-
-use minifb::{Key, Scale, ScaleMode, Window, WindowOptions};
-
-use chf_emulator::Cpu;
-
-const WIDTH: usize = 128 * 2;
-const HEIGHT: usize = 64 * 2;
-
-const ROM_0000: &'static [u8] = include_bytes!("../../chf-emulator/roms/SL31253.bin");
-const ROM_0400: &'static [u8] = include_bytes!("../../chf-emulator/roms/SL31254.bin");
+use std::{
+    cell::RefCell,
+    env, fs,
+    io::{self, Write},
+};
+
+use minifb::{Key, KeyRepeat, Scale, ScaleMode, Window, WindowOptions};
+
+use chf_emulator::{
+    bios,
+    crc::crc32,
+    movie::{self, MovieHeader, MovieReader},
+    state::{self, SliceReader, SliceWriter, STATE_LEN},
+    Cpu, Input,
+};
+
+use cheats::{Compare, Search};
+use render::Renderer;
+use rewind::Rewind;
+use viewers::Viewers;
+
+mod cheats;
+mod render;
+mod rewind;
+mod viewers;
+
+const ROMS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../chf-emulator/roms");
+
+// hold backspace to rewind up to ten seconds
+const REWIND_KEY: Key = Key::Backspace;
+const REWIND_FRAMES: usize = 600;
+
+// opens / closes the VRAM, memory and SRAM viewers
+const VIEWERS_KEY: Key = Key::F9;
+
+// cycles through sharp, scanlines and CRT, toggles 4:3 / square pixels
+const FILTER_KEY: Key = Key::F10;
+const ASPECT_KEY: Key = Key::F11;
+
+// asks for another cartridge in the terminal, minifb doesn't tell about dropped files
+const OPEN_KEY: Key = Key::F7;
+
+const CARTRIDGE_LEN: usize = 1024 * 8;
+
+// switches the console off and on, R is the RESET button
+const POWER_KEY: Key = Key::F12;
+
+// save states in `STATES_DIR`, named like the ones of the SD-card so they can be copied over
+const SAVE_STATE_KEY: Key = Key::Insert;
+const LOAD_STATE_KEY: Key = Key::Home;
+const NEXT_SLOT_KEY: Key = Key::PageUp;
+const PREVIOUS_SLOT_KEY: Key = Key::PageDown;
+const STATES_DIR: &str = "states";
+
+const KEYS: [(Key, chf_emulator::Key); 21] = [
+    (Key::R, chf_emulator::Key::Reset),
+    (Key::Key1, chf_emulator::Key::Start),
+    (Key::Key2, chf_emulator::Key::Hold),
+    (Key::Key3, chf_emulator::Key::Mode),
+    (Key::Key4, chf_emulator::Key::Time),
+    (Key::A, chf_emulator::Key::Left0),
+    (Key::D, chf_emulator::Key::Right0),
+    (Key::W, chf_emulator::Key::Forward0),
+    (Key::S, chf_emulator::Key::Back0),
+    (Key::Q, chf_emulator::Key::CounterClockwise0),
+    (Key::E, chf_emulator::Key::Clockwise0),
+    (Key::Y, chf_emulator::Key::Pull0),
+    (Key::Z, chf_emulator::Key::Push0),
+    (Key::NumPad4, chf_emulator::Key::Left1),
+    (Key::NumPad6, chf_emulator::Key::Right1),
+    (Key::NumPad8, chf_emulator::Key::Forward1),
+    (Key::NumPad5, chf_emulator::Key::Back1),
+    (Key::NumPad7, chf_emulator::Key::CounterClockwise1),
+    (Key::NumPad9, chf_emulator::Key::Clockwise1),
+    (Key::NumPad1, chf_emulator::Key::Pull1),
+    (Key::NumPad2, chf_emulator::Key::Push1),
+];
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    let mut cartridge = [0u8; 1024 * 8];
+    let (rom_0000, rom_0400) = bios::load_or_free(ROMS_DIR);
+    let mut cartridge = [0u8; CARTRIDGE_LEN];
+    let mut cartridge_len = 0;
 
     if args.len() > 1 {
-        let data = fs::read(&args[1]).unwrap();
-        for (i, b) in data.iter().enumerate() {
-            cartridge[i] = *b;
+        cartridge_len = read_cartridge(&args[1], &mut cartridge).unwrap();
+    }
+
+    // desktop <cartridge> [--record <movie> | --play <movie>]
+    let movie_header = MovieHeader::new(&rom_0000, &rom_0400, &cartridge[..cartridge_len]);
+    let mut record_to = None;
+    let mut movie_mode = Movie::None;
+    if args.len() > 3 {
+        match args[2].as_str() {
+            "--record" => {
+                record_to = Some(args[3].clone());
+                movie_mode = Movie::Record(Vec::new());
+            }
+            "--play" => {
+                let data = fs::read(&args[3]).unwrap();
+                let reader = MovieReader::new(&data).unwrap();
+                if !reader.header.matches(&movie_header) {
+                    println!("Movie was recorded with a different BIOS or cartridge");
+                }
+                movie_mode = Movie::Play(reader.collect(), 0);
+            }
+            _ => println!("Unknown option {}", args[2]),
         }
     }
 
@@ -34,41 +121,21 @@ fn main() {
 
         pixels: RefCell::new([0u8; 128 * 64]),
 
-        key_1: RefCell::new(false),
-        key_2: RefCell::new(false),
-        key_3: RefCell::new(false),
-        key_4: RefCell::new(false),
-        r0: RefCell::new(false),
-        l0: RefCell::new(false),
-        u0: RefCell::new(false),
-        d0: RefCell::new(false),
-        ccw0: RefCell::new(false),
-        cw0: RefCell::new(false),
-        pull0: RefCell::new(false),
-        push0: RefCell::new(false),
-        r1: RefCell::new(false),
-        l1: RefCell::new(false),
-        u1: RefCell::new(false),
-        d1: RefCell::new(false),
-        ccw1: RefCell::new(false),
-        cw1: RefCell::new(false),
-        pull1: RefCell::new(false),
-        push1: RefCell::new(false),
+        keys: RefCell::new(Input::default()),
+        movie: RefCell::new(movie_mode),
     };
 
-    let cartridge = &cartridge;
-    let mut cpu = Cpu::new(ROM_0000, ROM_0400, cartridge, &channel_f);
-    cpu.reset();
+    let mut viewers: Option<Viewers> = None;
 
-    let mut buffer: Vec<u32> = vec![0; WIDTH * HEIGHT];
+    let mut renderer = Renderer::new();
 
     let mut window = Window::new(
         "Channel F - ESC to exit",
-        WIDTH,
-        HEIGHT,
+        renderer.width(),
+        renderer.height(),
         WindowOptions {
             resize: true,
-            scale: Scale::X2,
+            scale: Scale::X1,
             scale_mode: ScaleMode::AspectRatioStretch,
             ..WindowOptions::default()
         },
@@ -80,188 +147,341 @@ fn main() {
 
     window.set_background_color(0, 0, 20);
 
-    let mut cntr = 0u32;
-    let mut exe_cntr = 0u32;
-
-    let mut p_is_down = false;
-    let mut o_is_down = false;
-    let mut l_is_down = false;
-
-    let mut show_info = false;
-    let mut pc_low = u16::MAX;
-    let mut pc_high = u16::MIN;
-
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        *channel_f.key_1.borrow_mut() = window.is_key_down(Key::Key1);
-        *channel_f.key_2.borrow_mut() = window.is_key_down(Key::Key2);
-        *channel_f.key_3.borrow_mut() = window.is_key_down(Key::Key3);
-        *channel_f.key_4.borrow_mut() = window.is_key_down(Key::Key4);
-
-        *channel_f.l0.borrow_mut() = window.is_key_down(Key::A);
-        *channel_f.r0.borrow_mut() = window.is_key_down(Key::D);
-        *channel_f.u0.borrow_mut() = window.is_key_down(Key::W);
-        *channel_f.d0.borrow_mut() = window.is_key_down(Key::S);
-        *channel_f.ccw0.borrow_mut() = window.is_key_down(Key::Q);
-        *channel_f.cw0.borrow_mut() = window.is_key_down(Key::E);
-        *channel_f.pull0.borrow_mut() = window.is_key_down(Key::Y);
-        *channel_f.push0.borrow_mut() = window.is_key_down(Key::Z);
-
-        *channel_f.l1.borrow_mut() = window.is_key_down(Key::NumPad4);
-        *channel_f.r1.borrow_mut() = window.is_key_down(Key::NumPad6);
-        *channel_f.u1.borrow_mut() = window.is_key_down(Key::NumPad8);
-        *channel_f.d1.borrow_mut() = window.is_key_down(Key::NumPad5);
-        *channel_f.ccw1.borrow_mut() = window.is_key_down(Key::NumPad7);
-        *channel_f.cw1.borrow_mut() = window.is_key_down(Key::NumPad9);
-        *channel_f.pull1.borrow_mut() = window.is_key_down(Key::NumPad1);
-        *channel_f.push1.borrow_mut() = window.is_key_down(Key::NumPad2);
-
-        if window.is_key_down(Key::L) {
-            l_is_down = true;
-        }
+    loop {
+        // a new `Cpu` for every cartridge, nothing of the game before is left
+        let mut cpu = Cpu::new(&rom_0000, &rom_0400, &cartridge, &channel_f);
+        cpu.reset();
+
+        let crc = crc32(&cartridge[..cartridge_len]);
+        let cheat_file = format!("cheats/{:08x}.txt", crc);
+        let mut slot = 0;
+        load_cheats(&mut cpu, &cheat_file);
+        let mut search: Option<Search> = None;
+
+        let mut cntr = 0u32;
+        let mut exe_cntr = 0u32;
+
+        let mut p_is_down = false;
+        let mut o_is_down = false;
+        let mut l_is_down = false;
+
+        let mut rewind = Rewind::new(REWIND_FRAMES);
+        let mut state = vec![0u8; STATE_LEN];
+        // rewinding would make recorded inputs useless
+        let rewind_enabled = matches!(*channel_f.movie.borrow(), Movie::None);
+
+        let mut show_info = false;
+        let mut pc_low = u16::MAX;
+        let mut pc_high = u16::MIN;
+
+        let mut open_cartridge = false;
+        while window.is_open() && !window.is_key_down(Key::Escape) {
+            {
+                let mut keys = channel_f.keys.borrow_mut();
+                for (key, channel_f_key) in KEYS.iter() {
+                    keys.set(*channel_f_key, window.is_key_down(*key));
+                }
+            }
 
-        if window.is_key_released(Key::L) && l_is_down {
-            l_is_down = false;
-            show_info = !show_info;
-        }
+            if window.is_key_down(Key::L) {
+                l_is_down = true;
+            }
 
-        if window.is_key_down(Key::O) {
-            o_is_down = true;
-        }
+            if window.is_key_released(Key::L) && l_is_down {
+                l_is_down = false;
+                show_info = !show_info;
+            }
 
-        if window.is_key_released(Key::O) && o_is_down {
-            o_is_down = false;
-            for i in 0..=255 {
-                seen_opcodes[i] = false;
+            if window.is_key_down(Key::O) {
+                o_is_down = true;
             }
-        }
 
-        if window.is_key_down(Key::P) {
-            p_is_down = true;
-        }
+            if window.is_key_released(Key::O) && o_is_down {
+                o_is_down = false;
+                for i in 0..=255 {
+                    seen_opcodes[i] = false;
+                }
+            }
 
-        if window.is_key_released(Key::P) && p_is_down {
-            p_is_down = false;
-            for i in 0..=255 {
-                println!("{:x} {}", i, seen_opcodes[i]);
+            if window.is_key_down(Key::P) {
+                p_is_down = true;
             }
-            println!();
-        }
 
-        exe_cntr += 1;
-        if exe_cntr >= 2 {
-            exe_cntr = 0;
+            if window.is_key_released(Key::P) && p_is_down {
+                p_is_down = false;
+                for i in 0..=255 {
+                    println!("{:x} {}", i, seen_opcodes[i]);
+                }
+                println!();
+            }
 
-            let pc = cpu.pc0;
+            exe_cntr += 1;
+            if exe_cntr >= 2 {
+                exe_cntr = 0;
 
-            cpu.cycles = 0;
-            let opcode = cpu.fetch();
+                let pc = cpu.pc0;
+                let opcode = cpu.peek(pc);
 
-            seen_opcodes[opcode as usize] = true;
+                seen_opcodes[opcode as usize] = true;
 
-            if show_info {
-                if pc >= pc_high {
-                    pc_high = pc;
+                if show_info {
+                    if pc >= pc_high {
+                        pc_high = pc;
+                    }
+
+                    if pc <= pc_low {
+                        pc_low = pc;
+                    }
+                    println!("{:x} {:x} .... {:x} - {:x}", pc, opcode, pc_low, pc_high);
                 }
 
-                if pc <= pc_low {
-                    pc_low = pc;
+                if cpu.step() && rewind_enabled {
+                    if window.is_key_down(REWIND_KEY) {
+                        if let Some(previous) = rewind.pop() {
+                            cpu.load_state(&mut SliceReader::new(previous)).unwrap();
+                            cpu.repaint();
+                        }
+                    } else {
+                        cpu.save_state(&mut SliceWriter::new(&mut state)).unwrap();
+                        rewind.push(&state);
+                    }
                 }
-                println!("{:x} {:x} .... {:x} - {:x}", pc, opcode, pc_low, pc_high);
             }
 
-            cpu.execute(opcode);
-        }
+            if current_sound != *channel_f.sound.borrow() {
+                current_sound = *channel_f.sound.borrow();
+                sink.stop();
+                sink = rodio::Sink::try_new(&stream_handle).unwrap();
+
+                match current_sound {
+                    Sound::Silence => {}
+                    Sound::Frequency1Khz => {
+                        let sound = SineWave::new(1000);
+                        sink.append(sound);
+                    }
+                    Sound::Frequency500Hz => {
+                        let sound = SineWave::new(500);
+                        sink.append(sound);
+                    }
+                    Sound::Frequency120Hz => {
+                        let sound = SineWave::new(120);
+                        sink.append(sound);
+                    }
+                }
+            }
+
+            cntr += 1;
+            if cntr >= 42000 {
+                cntr = 0;
+
+                let pixels = channel_f.pixels.borrow();
+                let (width, height) = (renderer.width(), renderer.height());
+                let frame = renderer.render(&pixels);
+
+                // We unwrap here as we want this code to exit if it fails
+                window.update_with_buffer(frame, width, height).unwrap();
+
+                if window.is_key_pressed(FILTER_KEY, KeyRepeat::No) {
+                    renderer.filter = renderer.filter.next();
+                }
+                if window.is_key_pressed(ASPECT_KEY, KeyRepeat::No) {
+                    renderer.aspect_4_3 = !renderer.aspect_4_3;
+                }
+
+                handle_cheat_keys(&window, &mut cpu, &mut search, &cheat_file);
 
-        if current_sound != *channel_f.sound.borrow() {
-            current_sound = *channel_f.sound.borrow();
-            sink.stop();
-            sink = rodio::Sink::try_new(&stream_handle).unwrap();
+                // like rewinding, loading a state would make recorded inputs useless
+                if rewind_enabled {
+                    handle_state_keys(&window, &mut cpu, &mut slot, crc);
+                }
+
+                // a movie only records the inputs, it couldn't be played back
+                if window.is_key_pressed(POWER_KEY, KeyRepeat::No) && rewind_enabled {
+                    cpu.power_on();
+                }
 
-            match current_sound {
-                Sound::Silence => {}
-                Sound::Frequency1Khz => {
-                    let sound = SineWave::new(1000);
-                    sink.append(sound);
+                if window.is_key_pressed(OPEN_KEY, KeyRepeat::No) {
+                    // recorded or played inputs belong to the cartridge of the movie
+                    if rewind_enabled {
+                        open_cartridge = true;
+                        break;
+                    }
+                    println!("Can't change the cartridge of a movie");
                 }
-                Sound::Frequency500Hz => {
-                    let sound = SineWave::new(500);
-                    sink.append(sound);
+
+                if window.is_key_pressed(VIEWERS_KEY, KeyRepeat::No) {
+                    viewers = match viewers {
+                        Some(_) => None,
+                        None => Some(Viewers::new()),
+                    };
                 }
-                Sound::Frequency120Hz => {
-                    let sound = SineWave::new(120);
-                    sink.append(sound);
+                if let Some(open) = &mut viewers {
+                    if open.is_open() {
+                        open.update(&cpu, &pixels);
+                    } else {
+                        viewers = None;
+                    }
                 }
             }
         }
 
-        cntr += 1;
-        if cntr >= 42000 {
-            cntr = 0;
-
-            let pixels = channel_f.pixels.borrow();
-            // column 125 + 126 choose the palette to use
-            const COLORS: [u32; 16] = [
-                0xff000000, 0xffffffff, 0xffffffff, 0xffffffff, //
-                0xff7777ff, 0xff0000ff, 0xffff0000, 0xff008800, //
-                0xffcccccc, 0xff0000ff, 0xffff0000, 0xff008800, //
-                0xff77ff77, 0xff0000ff, 0xffff0000, 0xff008800, //
-            ];
-
-            for y in 0..64 {
-                // The last three columns in the video buffer are special.
-                // 127 - unknown
-                // 126 - bit 1 = palette bit 1
-                // 125 - bit 1 = palette bit 0 (or with 126 bit 0)
-                // (palette is shifted by two and added to 'color'
-                //  to find palette index which holds the color's index)
-                let palette = ((pixels[y * 128 + 125] & 2 >> 1) | (pixels[y * 128 + 126])) & 0b11;
-
-                for x in 0..128 {
-                    let pixel = pixels[y * 128 + x];
-
-                    let color = COLORS[(pixel + palette * 4) as usize];
-
-                    let addr = (y * 128 * 2 * 2) + (x * 2);
-                    buffer[addr + 0] = color;
-                    buffer[addr + 1] = color;
-                    buffer[addr + 256] = color;
-                    buffer[addr + 257] = color;
+        if !open_cartridge {
+            break;
+        }
+
+        match ask_for_cartridge(&mut cartridge) {
+            Ok(len) => cartridge_len = len,
+            Err(e) => println!("{}", e),
+        }
+        *channel_f.pixels.borrow_mut() = [0u8; 128 * 64];
+        *channel_f.sound.borrow_mut() = Sound::Silence;
+    }
+
+    let movie_mode = channel_f.movie.borrow();
+    if let (Some(path), Movie::Record(frames)) = (record_to, &*movie_mode) {
+        let mut header = movie_header;
+        header.frames = frames.len() as u32;
+
+        let mut data = header.to_bytes().to_vec();
+        for input in frames {
+            data.extend_from_slice(&movie::encode_frame(*input));
+        }
+        fs::write(&path, &data).unwrap();
+        println!("Recorded {} frames to {}", frames.len(), path);
+    }
+}
+
+fn read_cartridge(path: &str, cartridge: &mut [u8; CARTRIDGE_LEN]) -> io::Result<usize> {
+    let data = fs::read(path)?;
+    if data.len() > CARTRIDGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "cartridges have 8K at most",
+        ));
+    }
+
+    *cartridge = [0u8; CARTRIDGE_LEN];
+    cartridge[..data.len()].copy_from_slice(&data);
+    Ok(data.len())
+}
+
+// the window doesn't update while waiting for the path
+fn ask_for_cartridge(cartridge: &mut [u8; CARTRIDGE_LEN]) -> io::Result<usize> {
+    print!("Cartridge to load: ");
+    io::stdout().flush()?;
+
+    let mut path = String::new();
+    io::stdin().read_line(&mut path)?;
+    read_cartridge(path.trim(), cartridge)
+}
+
+fn load_cheats(cpu: &mut Cpu, path: &str) {
+    cpu.cheats.clear();
+
+    if let Ok(list) = fs::read_to_string(path) {
+        match cpu.cheats.load(&list) {
+            Ok(()) => println!("Loaded {} cheats from {}", cpu.cheats.iter().count(), path),
+            Err((line, e)) => println!("{}:{} {:?}", path, line, e),
+        }
+    }
+}
+
+fn handle_state_keys(window: &Window, cpu: &mut Cpu, slot: &mut u8, crc: u32) {
+    if window.is_key_pressed(NEXT_SLOT_KEY, KeyRepeat::No) {
+        *slot = (*slot + 1) % state::SLOTS;
+        println!("Save state slot {}", slot);
+    }
+    if window.is_key_pressed(PREVIOUS_SLOT_KEY, KeyRepeat::No) {
+        *slot = (*slot + state::SLOTS - 1) % state::SLOTS;
+        println!("Save state slot {}", slot);
+    }
+
+    let name = state::slot_file_name(crc, *slot);
+    let path = format!("{}/{}", STATES_DIR, String::from_utf8_lossy(&name));
+
+    if window.is_key_pressed(SAVE_STATE_KEY, KeyRepeat::No) {
+        let mut data = vec![0u8; STATE_LEN];
+        cpu.save_state(&mut SliceWriter::new(&mut data)).unwrap();
+        match fs::create_dir_all(STATES_DIR).and_then(|_| fs::write(&path, &data)) {
+            Ok(()) => println!("Saved the state to {}", path),
+            Err(e) => println!("{}: {}", path, e),
+        }
+    }
+
+    if window.is_key_pressed(LOAD_STATE_KEY, KeyRepeat::No) {
+        match fs::read(&path) {
+            Ok(data) => match cpu.load_state(&mut SliceReader::new(&data)) {
+                Ok(()) => {
+                    cpu.repaint();
+                    println!("Loaded the state from {}", path);
                 }
-            }
+                Err(e) => println!("{}: {:?}", path, e),
+            },
+            Err(e) => println!("{}: {}", path, e),
+        }
+    }
+}
+
+// F1 starts a search, F2 - F5 keep the locations which are equal, changed, increased or decreased
+// since the last key press, F6 prints the candidates, F8 reloads the cheat file
+fn handle_cheat_keys(
+    window: &Window,
+    cpu: &mut Cpu,
+    search: &mut Option<Search>,
+    cheat_file: &str,
+) {
+    if window.is_key_pressed(Key::F1, KeyRepeat::No) {
+        *search = Some(Search::new(cpu));
+        println!("Cheat search started");
+    }
+
+    if window.is_key_pressed(Key::F8, KeyRepeat::No) {
+        load_cheats(cpu, cheat_file);
+    }
+
+    let search = match search {
+        Some(search) => search,
+        None => return,
+    };
+
+    let compare = if window.is_key_pressed(Key::F2, KeyRepeat::No) {
+        Some(Compare::Equal)
+    } else if window.is_key_pressed(Key::F3, KeyRepeat::No) {
+        Some(Compare::Changed)
+    } else if window.is_key_pressed(Key::F4, KeyRepeat::No) {
+        Some(Compare::Increased)
+    } else if window.is_key_pressed(Key::F5, KeyRepeat::No) {
+        Some(Compare::Decreased)
+    } else {
+        None
+    };
+
+    if let Some(compare) = compare {
+        search.filter(cpu, compare);
+        println!("{:?}: {} candidates left", compare, search.count());
+    }
 
-            // We unwrap here as we want this code to exit if it fails
-            window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
+    if window.is_key_pressed(Key::F6, KeyRepeat::No) {
+        for (target, value) in search.candidates().take(32) {
+            println!("{} {:02x}", target, value);
         }
+        println!("{} candidates", search.count());
     }
 }
 
+enum Movie {
+    None,
+    Record(Vec<Input>),
+    Play(Vec<Input>, usize),
+}
+
 struct DesktopChannelF {
     sound: RefCell<Sound>,
 
     pixels: RefCell<[u8; 128 * 64]>,
 
-    key_1: RefCell<bool>,
-    key_2: RefCell<bool>,
-    key_3: RefCell<bool>,
-    key_4: RefCell<bool>,
-
-    r0: RefCell<bool>,
-    l0: RefCell<bool>,
-    u0: RefCell<bool>,
-    d0: RefCell<bool>,
-    ccw0: RefCell<bool>,
-    cw0: RefCell<bool>,
-    pull0: RefCell<bool>,
-    push0: RefCell<bool>,
-
-    r1: RefCell<bool>,
-    l1: RefCell<bool>,
-    u1: RefCell<bool>,
-    d1: RefCell<bool>,
-    ccw1: RefCell<bool>,
-    cw1: RefCell<bool>,
-    pull1: RefCell<bool>,
-    push1: RefCell<bool>,
+    keys: RefCell<Input>,
+    movie: RefCell<Movie>,
 }
 
 use chf_emulator::ChannelF;
@@ -280,31 +500,30 @@ impl<'a> ChannelF for DesktopChannelF {
     }
 
     fn key_pressed(&self, key: chf_emulator::Key) -> bool {
-        match key {
-            chf_emulator::Key::Start => *self.key_1.borrow(),
-            chf_emulator::Key::Hold => *self.key_2.borrow(),
-            chf_emulator::Key::Mode => *self.key_3.borrow(),
-            chf_emulator::Key::Time => *self.key_4.borrow(),
-
-            chf_emulator::Key::Right0 => *self.r0.borrow(),
-            chf_emulator::Key::Left0 => *self.l0.borrow(),
-            chf_emulator::Key::Forward0 => *self.u0.borrow(),
-            chf_emulator::Key::Back0 => *self.d0.borrow(),
-            chf_emulator::Key::CounterClockwise0 => *self.ccw0.borrow(),
-            chf_emulator::Key::Clockwise0 => *self.cw0.borrow(),
-            chf_emulator::Key::Pull0 => *self.pull0.borrow(),
-            chf_emulator::Key::Push0 => *self.push0.borrow(),
-
-            chf_emulator::Key::Right1 => *self.r1.borrow(),
-            chf_emulator::Key::Left1 => *self.l1.borrow(),
-            chf_emulator::Key::Forward1 => *self.u1.borrow(),
-            chf_emulator::Key::Back1 => *self.d1.borrow(),
-            chf_emulator::Key::CounterClockwise1 => *self.ccw1.borrow(),
-            chf_emulator::Key::Clockwise1 => *self.cw1.borrow(),
-            chf_emulator::Key::Pull1 => *self.pull1.borrow(),
-            chf_emulator::Key::Push1 => *self.push1.borrow(),
-
-            _ => false,
+        self.keys.borrow().is_pressed(key)
+    }
+
+    fn input(&self) -> Input {
+        let keys = *self.keys.borrow();
+
+        match &mut *self.movie.borrow_mut() {
+            Movie::None => keys,
+            Movie::Record(frames) => {
+                frames.push(keys);
+                keys
+            }
+            Movie::Play(frames, idx) => {
+                if *idx < frames.len() {
+                    *idx += 1;
+                    frames[*idx - 1]
+                } else {
+                    if *idx == frames.len() {
+                        *idx += 1;
+                        println!("Movie finished");
+                    }
+                    keys
+                }
+            }
         }
     }
 }
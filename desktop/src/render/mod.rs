@@ -0,0 +1,144 @@
+// visible area of the Channel F screen - same as the main MCU sends to the video MCU
+const VISIBLE_X: usize = 4;
+const VISIBLE_Y: usize = 4;
+const VISIBLE_WIDTH: usize = 101 - 4 + 1;
+const VISIBLE_HEIGHT: usize = 62 - 4 + 1;
+
+// every line of the Channel F becomes this many lines, leaves room for scanlines
+const LINE_HEIGHT: usize = 6;
+
+pub const HEIGHT: usize = VISIBLE_HEIGHT * LINE_HEIGHT;
+// a TV picture is 4:3, so the Channel F pixels are a bit taller than wide
+pub const WIDTH_4_3: usize = HEIGHT * 4 / 3;
+pub const WIDTH_SQUARE: usize = VISIBLE_WIDTH * LINE_HEIGHT;
+
+// column 125 + 126 choose the palette to use
+const COLORS: [u32; 16] = [
+    0xff000000, 0xffffffff, 0xffffffff, 0xffffffff, //
+    0xff7777ff, 0xff0000ff, 0xffff0000, 0xff008800, //
+    0xffcccccc, 0xff0000ff, 0xffff0000, 0xff008800, //
+    0xff77ff77, 0xff0000ff, 0xffff0000, 0xff008800, //
+];
+
+// brightness of the lines making up one Channel F line (256 = full)
+const SCANLINE: [u32; LINE_HEIGHT] = [224, 256, 256, 224, 144, 64];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Sharp,
+    Scanlines,
+    /// Scanlines, a bit of horizontal blur and an RGB shadow mask
+    Crt,
+}
+
+impl Filter {
+    pub fn next(self) -> Filter {
+        match self {
+            Filter::Sharp => Filter::Scanlines,
+            Filter::Scanlines => Filter::Crt,
+            Filter::Crt => Filter::Sharp,
+        }
+    }
+}
+
+pub struct Renderer {
+    pub filter: Filter,
+    pub aspect_4_3: bool,
+    buffer: Vec<u32>,
+    line: Vec<u32>,
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer {
+            filter: Filter::Sharp,
+            aspect_4_3: true,
+            buffer: vec![0; WIDTH_SQUARE * HEIGHT],
+            line: vec![0; WIDTH_SQUARE],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        if self.aspect_4_3 {
+            WIDTH_4_3
+        } else {
+            WIDTH_SQUARE
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        HEIGHT
+    }
+
+    /// Renders the visible part of the screen, the result is `width()` x `height()` pixels
+    pub fn render(&mut self, pixels: &[u8; 128 * 64]) -> &[u32] {
+        let width = self.width();
+
+        for y in 0..VISIBLE_HEIGHT {
+            let row = &pixels[(y + VISIBLE_Y) * 128..][..128];
+
+            let palette = chf_emulator::palette(row[125], row[126]);
+
+            for x in 0..width {
+                let pixel = row[VISIBLE_X + x * VISIBLE_WIDTH / width];
+                self.line[x] = COLORS[pixel as usize + palette * 4];
+            }
+
+            if self.filter == Filter::Crt {
+                blur(&mut self.line[..width]);
+            }
+
+            for (yy, brightness) in SCANLINE.iter().enumerate() {
+                let out = &mut self.buffer[(y * LINE_HEIGHT + yy) * width..][..width];
+
+                match self.filter {
+                    Filter::Sharp => out.copy_from_slice(&self.line[..width]),
+                    Filter::Scanlines => {
+                        for (out, color) in out.iter_mut().zip(self.line.iter()) {
+                            *out = scale(*color, [*brightness; 3]);
+                        }
+                    }
+                    Filter::Crt => {
+                        for (x, (out, color)) in out.iter_mut().zip(self.line.iter()).enumerate() {
+                            let mut factors = [brightness * 3 / 4; 3];
+                            factors[x % 3] = *brightness;
+                            *out = scale(*color, factors);
+                        }
+                    }
+                }
+            }
+        }
+
+        &self.buffer[..width * HEIGHT]
+    }
+}
+
+// 1-2-1 blur of a line, softens the edges between pixels like a TV does
+fn blur(line: &mut [u32]) {
+    let mut previous = line[0];
+    for x in 0..line.len() {
+        let current = line[x];
+        let next = if x + 1 < line.len() {
+            line[x + 1]
+        } else {
+            current
+        };
+
+        let mut result = 0xff000000;
+        for shift in [0, 8, 16].iter() {
+            let channel = |c: u32| (c >> shift) & 0xff;
+            let value = (channel(previous) + 2 * channel(current) + channel(next)) / 4;
+            result |= value << shift;
+        }
+        line[x] = result;
+        previous = current;
+    }
+}
+
+// scales the red, green and blue channels of an ARGB color, 256 = unchanged
+fn scale(color: u32, factors: [u32; 3]) -> u32 {
+    let r = ((color >> 16) & 0xff) * factors[0] / 256;
+    let g = ((color >> 8) & 0xff) * factors[1] / 256;
+    let b = (color & 0xff) * factors[2] / 256;
+    0xff000000 | (r << 16) | (g << 8) | b
+}
@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+/// Ring buffer of machine states for rewinding
+///
+/// Only the newest state is kept as is. Every older state is stored as the XOR difference to
+/// its successor, run length encoded. From frame to frame most of the state stays the same so
+/// these differences are mostly zeros and compress well.
+pub struct Rewind {
+    capacity: usize,
+    newest: Option<Vec<u8>>,
+    deltas: VecDeque<Vec<u8>>,
+}
+
+impl Rewind {
+    pub fn new(capacity: usize) -> Rewind {
+        Rewind {
+            capacity,
+            newest: None,
+            deltas: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, state: &[u8]) {
+        if let Some(newest) = &self.newest {
+            if self.deltas.len() >= self.capacity {
+                self.deltas.pop_front();
+            }
+            self.deltas.push_back(compress_delta(newest, state));
+        }
+
+        self.newest = Some(state.to_vec());
+    }
+
+    /// Removes the newest state and returns the one before it
+    pub fn pop(&mut self) -> Option<&[u8]> {
+        let delta = self.deltas.pop_back()?;
+        let newest = self.newest.as_mut()?;
+        apply_delta(newest, &delta);
+        Some(newest)
+    }
+}
+
+// The difference is stored as pairs of (number of unchanged bytes, number of changed bytes)
+// followed by the changed bytes XORed. Counts are at most 255, longer runs are split.
+fn compress_delta(old: &[u8], new: &[u8]) -> Vec<u8> {
+    let mut res = Vec::new();
+    let mut i = 0;
+
+    while i < new.len() {
+        let mut same = 0;
+        while i < new.len() && same < 255 && old[i] == new[i] {
+            same += 1;
+            i += 1;
+        }
+
+        let start = i;
+        while i < new.len() && i - start < 255 && old[i] != new[i] {
+            i += 1;
+        }
+
+        res.push(same as u8);
+        res.push((i - start) as u8);
+        for j in start..i {
+            res.push(old[j] ^ new[j]);
+        }
+    }
+
+    res
+}
+
+fn apply_delta(data: &mut [u8], delta: &[u8]) {
+    let mut i = 0;
+    let mut idx = 0;
+
+    while idx + 1 < delta.len() {
+        i += delta[idx] as usize;
+        let changed = delta[idx + 1] as usize;
+        idx += 2;
+
+        for _ in 0..changed {
+            data[i] ^= delta[idx];
+            i += 1;
+            idx += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // states of different lengths of runs, the first byte tells them apart
+    fn state(n: u8) -> Vec<u8> {
+        let mut state = vec![0u8; 600];
+        state[0] = n;
+        for (idx, byte) in state.iter_mut().enumerate().skip(1) {
+            if idx % (n as usize + 2) == 0 {
+                *byte = n.wrapping_mul(31) ^ idx as u8;
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn pop_newest_first() {
+        let mut rewind = Rewind::new(3);
+        assert_eq!(None, rewind.pop());
+
+        for n in 0..6 {
+            rewind.push(&state(n));
+        }
+
+        // the newest state is the current one, the oldest ones were dropped
+        assert_eq!(Some(&state(4)[..]), rewind.pop());
+        assert_eq!(Some(&state(3)[..]), rewind.pop());
+        assert_eq!(Some(&state(2)[..]), rewind.pop());
+        assert_eq!(None, rewind.pop());
+
+        // pushing goes on from the state popped last
+        rewind.push(&state(7));
+        assert_eq!(Some(&state(2)[..]), rewind.pop());
+    }
+}
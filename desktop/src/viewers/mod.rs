@@ -0,0 +1,199 @@
+use minifb::{Scale, Window, WindowOptions};
+
+use chf_emulator::Cpu;
+
+const VRAM_SCALE: usize = 4;
+const VRAM_WIDTH: usize = 128 * VRAM_SCALE;
+const VRAM_HEIGHT: usize = 64 * VRAM_SCALE;
+
+// 3x5 pixel font in 4x6 pixel cells
+const CHAR_WIDTH: usize = 4;
+const CHAR_HEIGHT: usize = 6;
+// "2800 00 01 ... 0f" - 16 bytes per line
+const MEMORY_COLUMNS: usize = 4 + 1 + 16 * 3;
+const MEMORY_ROWS: usize = 64 / 16 + 1 + 0x800 / 16;
+const MEMORY_WIDTH: usize = MEMORY_COLUMNS * CHAR_WIDTH;
+const MEMORY_HEIGHT: usize = MEMORY_ROWS * CHAR_HEIGHT;
+
+const SRAM_SCALE: usize = 8;
+const SRAM_WIDTH: usize = 32 * SRAM_SCALE;
+const SRAM_HEIGHT: usize = 32 * SRAM_SCALE;
+
+// raw color indices - not what the palette makes of them
+const INDEX_COLORS: [u32; 4] = [0xff000000, 0xff5555ff, 0xffff5555, 0xff55ff55];
+// columns 125 - 127 select the palette of the row
+const PALETTE_COLUMN_BORDER: u32 = 0xffffff00;
+
+const TEXT: u32 = 0xffaaaaaa;
+const TEXT_CHANGED: u32 = 0xffffff00;
+const TEXT_ADDRESS: u32 = 0xff5555ff;
+
+const HEX_FONT: [[u8; 5]; 16] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b111, 0b101, 0b111, 0b101, 0b101],
+    [0b110, 0b101, 0b110, 0b101, 0b110],
+    [0b111, 0b100, 0b100, 0b100, 0b111],
+    [0b110, 0b101, 0b101, 0b101, 0b110],
+    [0b111, 0b100, 0b111, 0b100, 0b111],
+    [0b111, 0b100, 0b111, 0b100, 0b100],
+];
+
+/// Debug windows showing the raw VRAM, scratchpad + RAM and the 2102 SRAM
+pub struct Viewers {
+    vram: Window,
+    vram_buffer: Vec<u32>,
+
+    memory: Window,
+    memory_buffer: Vec<u32>,
+    last_memory: Vec<u8>,
+
+    sram: Window,
+    sram_buffer: Vec<u32>,
+}
+
+impl Viewers {
+    pub fn new() -> Viewers {
+        Viewers {
+            vram: open_window("VRAM - color indices", VRAM_WIDTH, VRAM_HEIGHT),
+            vram_buffer: vec![0; VRAM_WIDTH * VRAM_HEIGHT],
+
+            memory: open_window("Scratchpad + RAM", MEMORY_WIDTH, MEMORY_HEIGHT),
+            memory_buffer: vec![0; MEMORY_WIDTH * MEMORY_HEIGHT],
+            last_memory: Vec::new(),
+
+            sram: open_window("2102 SRAM", SRAM_WIDTH, SRAM_HEIGHT),
+            sram_buffer: vec![0; SRAM_WIDTH * SRAM_HEIGHT],
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.vram.is_open() && self.memory.is_open() && self.sram.is_open()
+    }
+
+    pub fn update(&mut self, cpu: &Cpu, pixels: &[u8; 128 * 64]) {
+        self.update_vram(pixels);
+        self.update_memory(cpu);
+        self.update_sram(cpu);
+    }
+
+    fn update_vram(&mut self, pixels: &[u8; 128 * 64]) {
+        for y in 0..64 {
+            for x in 0..128 {
+                let color = INDEX_COLORS[(pixels[y * 128 + x] & 0b11) as usize];
+
+                for yy in 0..VRAM_SCALE {
+                    for xx in 0..VRAM_SCALE {
+                        let border =
+                            yy == 0 || yy == VRAM_SCALE - 1 || xx == 0 || xx == VRAM_SCALE - 1;
+                        let addr = (y * VRAM_SCALE + yy) * VRAM_WIDTH + x * VRAM_SCALE + xx;
+                        self.vram_buffer[addr] = if x >= 125 && border {
+                            PALETTE_COLUMN_BORDER
+                        } else {
+                            color
+                        };
+                    }
+                }
+            }
+        }
+
+        self.vram
+            .update_with_buffer(&self.vram_buffer, VRAM_WIDTH, VRAM_HEIGHT)
+            .unwrap();
+    }
+
+    fn update_memory(&mut self, cpu: &Cpu) {
+        let mut memory = cpu.scratchpad.to_vec();
+        memory.extend_from_slice(cpu.ram());
+        if self.last_memory.len() != memory.len() {
+            self.last_memory = memory.clone();
+        }
+
+        for pixel in self.memory_buffer.iter_mut() {
+            *pixel = 0xff000000;
+        }
+
+        // scratchpad at rows 0 - 3, an empty row, then the RAM
+        for (idx, value) in memory.iter().enumerate() {
+            let (address, row) = if idx < 64 {
+                (idx, idx / 16)
+            } else {
+                (0x2800 + idx - 64, (idx - 64) / 16 + 64 / 16 + 1)
+            };
+
+            if idx % 16 == 0 {
+                self.draw_hex(0, row, address as u16 as u32, 4, TEXT_ADDRESS);
+            }
+
+            let color = if self.last_memory[idx] != *value {
+                TEXT_CHANGED
+            } else {
+                TEXT
+            };
+            self.draw_hex(5 + (idx % 16) * 3, row, *value as u32, 2, color);
+        }
+
+        self.last_memory = memory;
+
+        self.memory
+            .update_with_buffer(&self.memory_buffer, MEMORY_WIDTH, MEMORY_HEIGHT)
+            .unwrap();
+    }
+
+    fn draw_hex(&mut self, column: usize, row: usize, value: u32, digits: usize, color: u32) {
+        for digit in 0..digits {
+            let nibble = (value >> ((digits - 1 - digit) * 4)) & 0xf;
+            let glyph = &HEX_FONT[nibble as usize];
+
+            for (y, bits) in glyph.iter().enumerate() {
+                for x in 0..3 {
+                    if bits & (0b100 >> x) != 0 {
+                        let addr = (row * CHAR_HEIGHT + y) * MEMORY_WIDTH
+                            + (column + digit) * CHAR_WIDTH
+                            + x;
+                        self.memory_buffer[addr] = color;
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_sram(&mut self, cpu: &Cpu) {
+        for (bit, value) in cpu.xmemory.iter().enumerate() {
+            let color = if *value != 0 { 0xffffffff } else { 0xff000040 };
+
+            for yy in 0..SRAM_SCALE - 1 {
+                for xx in 0..SRAM_SCALE - 1 {
+                    let addr =
+                        ((bit / 32) * SRAM_SCALE + yy) * SRAM_WIDTH + (bit % 32) * SRAM_SCALE + xx;
+                    self.sram_buffer[addr] = color;
+                }
+            }
+        }
+
+        self.sram
+            .update_with_buffer(&self.sram_buffer, SRAM_WIDTH, SRAM_HEIGHT)
+            .unwrap();
+    }
+}
+
+fn open_window(title: &str, width: usize, height: usize) -> Window {
+    Window::new(
+        title,
+        width,
+        height,
+        WindowOptions {
+            scale: Scale::X1,
+            ..WindowOptions::default()
+        },
+    )
+    .expect("Unable to Open Window")
+}
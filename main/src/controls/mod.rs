@@ -0,0 +1,110 @@
+//! The console buttons and both hand controllers, scanned by the TIM3 interrupt
+//!
+//! Both input data registers are read in one go, `chf_controls::scanner` decodes and debounces
+//! them. The select line of the controller read next is switched right after a sample, it has a
+//! whole timer period to settle.
+
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use chf_controls::scanner::{pa, pb, Scanner, Wiring};
+use chf_emulator::{Input, Key};
+use stm32f1xx_hal::pac::{self, interrupt, Interrupt};
+use stm32f1xx_hal::{
+    gpio::{
+        gpioa::{PA1, PA2},
+        Output, PushPull,
+    },
+    prelude::*,
+    timer::{CountDownTimer, Event, Timer},
+};
+
+/// Samples per second, a scan of both controllers takes two
+const SAMPLE_RATE: u32 = 2000;
+
+/// How the buttons and the controllers are connected, see the schematic
+static WIRING: Wiring = Wiring {
+    direct: &[(Key::Reset, pb(11))],
+    selected: [
+        &[
+            (Key::Start, pb(9)),
+            (Key::Hold, pb(8)),
+            (Key::Left0, pa(3)),
+            (Key::Right0, pa(4)),
+            (Key::Forward0, pa(5)),
+            (Key::Back0, pa(6)),
+            (Key::CounterClockwise0, pa(7)),
+            (Key::Clockwise0, pb(0)),
+            (Key::Pull0, pb(1)),
+            (Key::Push0, pb(10)),
+        ],
+        &[
+            (Key::Mode, pb(7)),
+            (Key::Time, pb(6)),
+            (Key::Left1, pa(3)),
+            (Key::Right1, pa(4)),
+            (Key::Forward1, pa(5)),
+            (Key::Back1, pa(6)),
+            (Key::CounterClockwise1, pa(7)),
+            (Key::Clockwise1, pb(0)),
+            (Key::Pull1, pb(1)),
+            (Key::Push1, pb(10)),
+        ],
+    ],
+};
+
+static mut TIMER: MaybeUninit<CountDownTimer<pac::TIM3>> = MaybeUninit::uninit();
+static mut SELECT: MaybeUninit<(PA1<Output<PushPull>>, PA2<Output<PushPull>>)> =
+    MaybeUninit::uninit();
+static mut SCANNER: Scanner = Scanner::new(&WIRING);
+
+// the debounced keys, written by the interrupt
+static INPUT: AtomicU32 = AtomicU32::new(0);
+
+/// Starts scanning, the input pins have to be configured as pull down inputs
+pub fn init(
+    timer: Timer<pac::TIM3>,
+    mut select_0: PA1<Output<PushPull>>,
+    mut select_1: PA2<Output<PushPull>>,
+) {
+    select_0.set_high().unwrap_or_default();
+    select_1.set_low().unwrap_or_default();
+
+    let mut timer = timer.start_count_down(SAMPLE_RATE.hz());
+    timer.listen(Event::Update);
+
+    // make them accessible from the isr
+    unsafe {
+        *(TIMER.as_mut_ptr()) = timer;
+        *(SELECT.as_mut_ptr()) = (select_0, select_1);
+
+        pac::NVIC::unmask(Interrupt::TIM3);
+    }
+}
+
+/// The debounced state of the buttons and the controllers
+pub fn input() -> Input {
+    Input(INPUT.load(Ordering::Relaxed))
+}
+
+#[interrupt]
+fn TIM3() {
+    unsafe {
+        (*TIMER.as_mut_ptr()).clear_update_interrupt_flag();
+
+        let pins = ((*pac::GPIOB::ptr()).idr.read().bits() << 16)
+            | ((*pac::GPIOA::ptr()).idr.read().bits() & 0xffff);
+        let select = SCANNER.sample(pins);
+
+        let (select_0, select_1) = &mut *SELECT.as_mut_ptr();
+        if select == 0 {
+            select_1.set_low().unwrap_or_default();
+            select_0.set_high().unwrap_or_default();
+        } else {
+            select_0.set_low().unwrap_or_default();
+            select_1.set_high().unwrap_or_default();
+        }
+
+        INPUT.store(SCANNER.input().0, Ordering::Relaxed);
+    }
+}
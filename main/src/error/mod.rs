@@ -0,0 +1,233 @@
+//! Stopping with a message on the screen and an error code on the LED
+//!
+//! Whatever main owns is stolen: SPI2 and the busy line of the video MCU are used via their
+//! registers, the way main set them up. If SPI2 isn't enabled yet only the LED tells the error.
+//!
+//! The LED blinks the code, pauses and starts again.
+
+use core::fmt::Write;
+use core::panic::PanicInfo;
+
+#[cfg(feature = "frame-dma")]
+use chf_link::frame;
+use chf_link::packet::Link;
+#[cfg(not(feature = "frame-dma"))]
+use chf_link::{encoder::Encoder, packet, Command};
+use chf_menu::browser::{Text, COLUMNS, ROWS};
+use rtt_target::rprintln;
+use stm32f1xx_hal::pac;
+
+// at 72 MHz
+const CYCLES_PER_US: u32 = 72;
+
+// tries to see the busy line of the video MCU change, about 100 ms
+const MAX_BUSY_POLLS: u32 = 1_000_000;
+
+/// Why main stopped, the number is blinked by the LED
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fatal {
+    Panic = 1,
+    NoSdCard = 2,
+    NoVolume = 3,
+    NoBios = 4,
+    FileTooBig = 5,
+    SdCard = 6,
+}
+
+impl Fatal {
+    /// The message shown for the error
+    pub fn text(self) -> Text {
+        let lines: [&[u8]; 2] = match self {
+            Fatal::Panic => [b"PANIC", b""],
+            Fatal::NoSdCard => [b"NO SD CARD", b""],
+            Fatal::NoVolume => [b"NO FAT", b"PARTITION"],
+            Fatal::NoBios => [b"NO BIOS", b""],
+            Fatal::FileTooBig => [b"FILE TOO BIG", b"MAX 4K"],
+            Fatal::SdCard => [b"SD CARD", b"ERROR"],
+        };
+        let mut text = [[b' '; COLUMNS]; ROWS];
+        for (row, line) in text.iter_mut().zip(lines.iter()) {
+            row[..line.len()].copy_from_slice(line);
+        }
+        text
+    }
+}
+
+/// Shows the message of `fatal`
+pub fn stop(fatal: Fatal) -> ! {
+    show(fatal, &fatal.text())
+}
+
+/// Shows `text` and blinks the code of `fatal` forever
+pub fn show(fatal: Fatal, text: &Text) -> ! {
+    rprintln!("stopped: {:?}", fatal);
+
+    let dp = unsafe { pac::Peripherals::steal() };
+    if dp.SPI2.cr1.read().spe().bit_is_set() {
+        draw(&dp, text);
+    }
+
+    loop {
+        for _ in 0..fatal as u8 {
+            dp.GPIOC.bsrr.write(|w| w.br13().set_bit()); // on
+            delay_ms(200);
+            dp.GPIOC.bsrr.write(|w| w.bs13().set_bit());
+            delay_ms(200);
+        }
+        delay_ms(1000);
+    }
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    rprintln!("{}", info);
+
+    // the file name is cut at the front, the message wraps over the rows left
+    let mut text = Fatal::Panic.text();
+    let mut writer = TextWriter {
+        text: &mut text,
+        row: 1,
+        column: 0,
+    };
+    if let Some(location) = info.location() {
+        let file = location.file().as_bytes();
+        let file = &file[file.len().saturating_sub(COLUMNS)..];
+        writer.write_bytes(file);
+        writer.new_line();
+        write!(writer, "LINE {}", location.line()).unwrap_or_default();
+        writer.new_line();
+    }
+    if let Some(message) = info.message() {
+        write!(writer, "{}", message).unwrap_or_default();
+    }
+
+    show(Fatal::Panic, &text)
+}
+
+// fills the rows of a `Text`, what doesn't fit is dropped
+struct TextWriter<'a> {
+    text: &'a mut Text,
+    row: usize,
+    column: usize,
+}
+
+impl<'a> TextWriter<'a> {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for c in bytes {
+            if self.column == COLUMNS {
+                self.new_line();
+            }
+            if self.row < ROWS {
+                self.text[self.row][self.column] = *c;
+                self.column += 1;
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.row += 1;
+        self.column = 0;
+    }
+}
+
+impl<'a> Write for TextWriter<'a> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+// the screen is cleared, the text drawn like the menu
+#[cfg(not(feature = "frame-dma"))]
+fn draw(dp: &pac::Peripherals, text: &Text) {
+    let mut link = RawLink { dp };
+    link.start();
+
+    let mut send = |command: &Command| {
+        packet::send(&mut link, command).ok();
+    };
+    send(&Command::Clear { color: 0 });
+    let mut encoder = Encoder::new();
+    crate::draw_text(text, &[[b' '; COLUMNS]; ROWS], &mut |x, y, color| {
+        encoder.pixel(x, y, color, &mut send)
+    });
+    encoder.flush(&mut send);
+}
+
+// the video MCU is in frame mode, the whole screen is sent as one block
+#[cfg(feature = "frame-dma")]
+fn draw(dp: &pac::Peripherals, text: &Text) {
+    let mut link = RawLink { dp };
+    link.start();
+
+    let mut vram = [0u8; crate::VRAM_LEN];
+    crate::draw_text(text, &[[b' '; COLUMNS]; ROWS], &mut |x, y, color| {
+        crate::store_pixel(&mut vram, x, y, color)
+    });
+    let mut block = [0u8; frame::MAX_BLOCK_LEN];
+    let block = frame::encode_block(&vram, u64::MAX, &mut block);
+
+    link.wait_busy(true);
+    link.wait_busy(false);
+    for byte in block {
+        link.write(*byte);
+    }
+}
+
+// SPI2 via its registers, works like `SpiLink`
+struct RawLink<'a> {
+    dp: &'a pac::Peripherals,
+}
+
+impl<'a> RawLink<'a> {
+    // DMA off, a byte received before is dropped
+    fn start(&mut self) {
+        let spi = &self.dp.SPI2;
+        spi.cr2.modify(|_, w| w.txdmaen().clear_bit());
+        while spi.sr.read().bsy().bit_is_set() {}
+        spi.dr.read();
+        spi.sr.read();
+    }
+
+    fn is_busy(&self) -> bool {
+        self.dp.GPIOA.idr.read().idr9().bit_is_set()
+    }
+
+    // as fast as DMA, the replies are dropped
+    #[cfg(feature = "frame-dma")]
+    fn write(&mut self, byte: u8) {
+        let spi = &self.dp.SPI2;
+        while spi.sr.read().txe().bit_is_clear() {}
+        spi.dr.write(|w| unsafe { w.bits(byte as u32) });
+    }
+
+    #[cfg(feature = "frame-dma")]
+    fn wait_busy(&self, busy: bool) {
+        for _ in 0..MAX_BUSY_POLLS {
+            if self.is_busy() == busy {
+                break;
+            }
+        }
+    }
+}
+
+impl<'a> Link for RawLink<'a> {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        let spi = &self.dp.SPI2;
+        while spi.sr.read().txe().bit_is_clear() {}
+        spi.dr.write(|w| unsafe { w.bits(byte as u32) });
+
+        self.wait_us(if self.is_busy() { 65 } else { 15 });
+
+        while spi.sr.read().rxne().bit_is_clear() {}
+        spi.dr.read().bits() as u8
+    }
+
+    fn wait_us(&mut self, us: u16) {
+        cortex_m::asm::delay(us as u32 * CYCLES_PER_US);
+    }
+}
+
+fn delay_ms(ms: u32) {
+    cortex_m::asm::delay(ms * 1000 * CYCLES_PER_US);
+}
@@ -1,15 +1,45 @@
 #![no_std]
 #![no_main]
-#![feature(asm)]
 #![feature(llvm_asm)]
 #![feature(fmt_internals)]
-
-use core::cell::RefCell;
-
-use chf_emulator::{ChannelF, Cpu};
-use embedded_sdmmc::{SdMmcSpi, TimeSource, VolumeIdx};
+#![feature(panic_info_message)]
+
+mod controls;
+mod error;
+mod sound;
+
+use core::cell::{Cell, RefCell};
+
+use chf_controls::chords::{Chord, Chords};
+use chf_emulator::{
+    audio::{self, Resampler, Synth},
+    bios::{self, Location},
+    crc::Crc32,
+    save::{self, SaveError, SaveRanges},
+    state::{self, StateError, StateReader, StateWriter},
+    ChannelF, Cpu, Input, Key,
+};
+use chf_link::encoder::Encoder;
+#[cfg(feature = "frame-dma")]
+use chf_link::frame;
+use chf_link::{
+    packet::{self, Link, LinkError},
+    Command,
+};
+use chf_menu::{
+    browser::{self, Browser, Entry, Listing, Text, COLUMNS, ROWS},
+    clock::{ClockEditor, DateTime, Edit},
+    database::{self, Record},
+    font, lfn,
+    settings::{self, Settings},
+    Name,
+};
+use embedded_sdmmc::{
+    Block, BlockDevice, BlockIdx, DirEntry, Directory, File, SdMmcSpi, TimeSource, Timestamp,
+    Volume, VolumeIdx,
+};
+use error::Fatal;
 use nb::block;
-use panic_halt as _;
 use rtt_target::{rprintln, rtt_init_print};
 
 use cortex_m_rt::entry;
@@ -25,9 +55,15 @@ use stm32f1xx_hal::{
     pac::{self, SPI2},
     prelude::*,
     pwm::Channel,
+    rtc::Rtc,
     spi::{Spi, Spi2NoRemap},
     timer::{Tim2NoRemap, Timer},
 };
+#[cfg(feature = "frame-dma")]
+use stm32f1xx_hal::{
+    dma::{dma1, WriteDma},
+    spi::SpiTxDma,
+};
 
 use embedded_hal::{
     digital::v2::{InputPin, OutputPin},
@@ -56,6 +92,11 @@ fn main() -> ! {
         .pclk2(72.mhz())
         .freeze(&mut flash.acr);
 
+    // the real-time clock runs from the 32.768 kHz crystal, on VBAT while switched off
+    let mut pwr = dp.PWR;
+    let mut backup_domain = rcc.bkp.constrain(dp.BKP, &mut rcc.apb1, &mut pwr);
+    let rtc = RefCell::new(Rtc::rtc(dp.RTC, &mut backup_domain));
+
     let mut afio = dp.AFIO.constrain(&mut rcc.apb2);
     let mut gpioa = dp.GPIOA.split(&mut rcc.apb2);
     let mut gpiob = dp.GPIOB.split(&mut rcc.apb2);
@@ -67,41 +108,46 @@ fn main() -> ! {
 
     led.set_high().unwrap_or_default(); // on board LED off
 
-    let button_1 = gpiob.pb9.into_pull_down_input(&mut gpiob.crh);
-    let button_2 = gpiob.pb8.into_pull_down_input(&mut gpiob.crh);
-    let button_3 = gpiob.pb7.into_pull_down_input(&mut gpiob.crl);
-    let button_4 = gpiob.pb6.into_pull_down_input(&mut gpiob.crl);
-
-    let mut select_controller_0 = gpioa.pa1.into_push_pull_output(&mut gpioa.crl);
-    let mut select_controller_1 = gpioa.pa2.into_push_pull_output(&mut gpioa.crl);
-    let left = gpioa.pa3.into_pull_down_input(&mut gpioa.crl);
-    let right = gpioa.pa4.into_pull_down_input(&mut gpioa.crl);
-    let up = gpioa.pa5.into_pull_down_input(&mut gpioa.crl);
-    let down = gpioa.pa6.into_pull_down_input(&mut gpioa.crl);
-    let ccw = gpioa.pa7.into_pull_down_input(&mut gpioa.crl);
-    let cw = gpiob.pb0.into_pull_down_input(&mut gpiob.crl);
-    let pull = gpiob.pb1.into_pull_down_input(&mut gpiob.crl);
-    let push = gpiob.pb10.into_pull_down_input(&mut gpiob.crh);
+    // the buttons and the controllers, `controls` reads them via the input data registers
+    gpiob.pb9.into_pull_down_input(&mut gpiob.crh); // start
+    gpiob.pb8.into_pull_down_input(&mut gpiob.crh); // hold
+    gpiob.pb7.into_pull_down_input(&mut gpiob.crl); // mode
+    gpiob.pb6.into_pull_down_input(&mut gpiob.crl); // time
+    gpiob.pb11.into_pull_down_input(&mut gpiob.crh); // reset
+
+    let select_controller_0 = gpioa.pa1.into_push_pull_output(&mut gpioa.crl);
+    let select_controller_1 = gpioa.pa2.into_push_pull_output(&mut gpioa.crl);
+    gpioa.pa3.into_pull_down_input(&mut gpioa.crl); // left
+    gpioa.pa4.into_pull_down_input(&mut gpioa.crl); // right
+    gpioa.pa5.into_pull_down_input(&mut gpioa.crl); // forward
+    gpioa.pa6.into_pull_down_input(&mut gpioa.crl); // back
+    gpioa.pa7.into_pull_down_input(&mut gpioa.crl); // counter clockwise
+    gpiob.pb0.into_pull_down_input(&mut gpiob.crl); // clockwise
+    gpiob.pb1.into_pull_down_input(&mut gpiob.crl); // pull
+    gpiob.pb10.into_pull_down_input(&mut gpiob.crh); // push
+
+    controls::init(
+        Timer::tim3(dp.TIM3, &clocks, &mut rcc.apb1),
+        select_controller_0,
+        select_controller_1,
+    );
 
     let peer_bsy = gpioa.pa9.into_pull_down_input(&mut gpioa.crh);
 
     let mut delay = Delay::new(cp.SYST, clocks);
 
-    // TIM2
+    // TIM2: the PWM DAC, DMA sets the duty of every sample
     let c1 = gpioa.pa0.into_alternate_push_pull(&mut gpioa.crl);
-    // If you don't want to use all channels, just leave some out
-    // let c4 = gpioa.pa3.into_alternate_push_pull(&mut gpioa.crl);
-    let pins = c1;
-
     let mut pwm = Timer::tim2(dp.TIM2, &clocks, &mut rcc.apb1).pwm::<Tim2NoRemap, _, _, _>(
-        pins,
+        c1,
         &mut afio.mapr,
-        120.hz(),
+        sound::PWM_RATE.hz(),
     );
-
-    let max = pwm.get_max_duty();
-    pwm.set_duty(Channel::C1, max / 2);
-    pwm.disable(Channel::C1);
+    pwm.set_duty(Channel::C1, audio::SILENCE as u16);
+    pwm.enable(Channel::C1);
+    // channel 7 plays the samples, with `frame-dma` channel 5 sends the rows
+    let dma1 = dp.DMA1.split(&mut rcc.ahb);
+    sound::init(dp.TIM4, dma1.7);
 
     // SPI2: other MCU
     let pins = (
@@ -137,64 +183,9 @@ fn main() -> ! {
     let pa15 = pa15.into_push_pull_output(&mut gpioa.crh);
     let sd = SdMmcSpi::new(spi_sd, pa15);
 
-    let time_source = FakeTimeSource {};
-    let mut controller = embedded_sdmmc::Controller::new(sd, time_source);
-    controller.device().init().unwrap();
-
-    let volume = controller.get_volume(VolumeIdx(0));
-    let mut volume = volume.unwrap();
-    let dir = controller.open_root_dir(&volume);
-    let dir = dir.unwrap();
-
-    let mut files = [0u8; 8 * 128]; // 64 files supported at max
-    let mut idx = 0;
-    let mut file_count = 0;
-
-    controller
-        .iterate_dir(&volume, &dir, |entry| {
-            struct WriteBuffer {
-                buffer: [u8; 12],
-                idx: usize,
-            }
-
-            impl core::fmt::Write for WriteBuffer {
-                fn write_str(&mut self, s: &str) -> core::fmt::Result {
-                    for c in s.chars() {
-                        self.buffer[self.idx] = c as u8;
-                        self.idx += 1;
-                    }
-                    Ok(())
-                }
-            }
-
-            let mut write_buffer = WriteBuffer {
-                buffer: [0u8; 12],
-                idx: 0usize,
-            };
-
-            let fname = entry.name.clone();
-            core::fmt::write(&mut write_buffer, format_args!("{}", fname)).unwrap_or_default();
-
-            if write_buffer.buffer[9] == b'B' {
-                for i in 0..8 {
-                    files[idx + i] = write_buffer.buffer[i];
-                }
-
-                idx += 8;
-                file_count += 1;
-            }
-        })
-        .unwrap();
-
     let mut pb12 = gpiob.pb12.into_push_pull_output(&mut gpiob.crh);
     pb12.set_high().unwrap();
 
-    let mut x: u8;
-    let mut y: u8;
-    let mut c: u8;
-
-    let mut playing_sound = chf_emulator::Sound::Silence;
-
     let channel_f = StmChannelF {
         should_set_pixel: RefCell::from(false),
         x: RefCell::from(0),
@@ -202,203 +193,464 @@ fn main() -> ! {
         color: RefCell::from(0),
 
         current_sound: RefCell::from(chf_emulator::Sound::Silence),
-
-        key_1: RefCell::new(false),
-        key_2: RefCell::new(false),
-        key_3: RefCell::new(false),
-        key_4: RefCell::new(false),
-        r0: RefCell::new(false),
-        l0: RefCell::new(false),
-        u0: RefCell::new(false),
-        d0: RefCell::new(false),
-        ccw0: RefCell::new(false),
-        cw0: RefCell::new(false),
-        pull0: RefCell::new(false),
-        push0: RefCell::new(false),
-        r1: RefCell::new(false),
-        l1: RefCell::new(false),
-        u1: RefCell::new(false),
-        d1: RefCell::new(false),
-        ccw1: RefCell::new(false),
-        cw1: RefCell::new(false),
-        pull1: RefCell::new(false),
-        push1: RefCell::new(false),
+        chords: RefCell::from(Chords::new()),
     };
 
     pb12.set_low().unwrap(); // keep NSS low all the time
 
-    // clear screen
-    for y in 0..64 {
-        for x in 0..127 {
-            set_pixel(x, y, 0, &peer_bsy, &mut spi, &mut delay, &mut led);
-            delay.delay_us(85u16);
-        }
+    // the menu and the errors are drawn via rows, too - the video MCU doesn't leave full frame
+    // mode
+    #[cfg(feature = "frame-dma")]
+    let mut spi_dma = {
+        // the video MCU only understands the rows once it acknowledged the mode, it answers until
+        // the first block starts
+        while send_command(
+            &Command::FrameMode,
+            &peer_bsy,
+            &mut spi,
+            &mut delay,
+            &mut led,
+        )
+        .is_err()
+        {}
+
+        spi.with_tx_dma(dma1.5)
+    };
+
+    // the errors of the SD-card are shown on the screen, files get the time of the clock
+    let time_source = RtcTimeSource { rtc: &rtc };
+    let mut controller = embedded_sdmmc::Controller::new(sd, time_source);
+    if controller.device().init().is_err() {
+        error::stop(Fatal::NoSdCard);
     }
 
-    select_controller_0.set_high().unwrap_or_default();
-    delay.delay_us(300u16);
+    let mut volume = match controller.get_volume(VolumeIdx(0)) {
+        Ok(volume) => volume,
+        Err(_) => error::stop(Fatal::NoVolume),
+    };
 
-    let mut fileindex = 0;
-    let mut shown_fileindex = 999;
-    let mut left_pressed = false;
-    let mut right_pressed = false;
-    let mut push_pressed = false;
-    loop {
-        if shown_fileindex != fileindex {
-            let idx = fileindex * 8;
-            let fname = core::str::from_utf8(&files[idx..(idx + 8)]).unwrap_or_default();
-            draw_str(10, 10, fname, &peer_bsy, &mut spi, &mut delay, &mut led);
-            shown_fileindex = fileindex;
+    // the BIOS comes from the SD-card, with `embedded-bios` the built in one fills in, with
+    // `free-bios` the free one replaces an incomplete one
+    let mut listing = SdListing {
+        controller: &mut controller,
+        volume: &mut volume,
+    };
+    let found = unsafe { listing.load_bios(&mut BIOS_0000, &mut BIOS_0400) };
+    let found = found.unwrap_or((None, None));
+    #[cfg(feature = "embedded-bios")]
+    let found = {
+        let (mut rom_0000, mut rom_0400) = found;
+        if rom_0000.is_none() {
+            unsafe { BIOS_0000.copy_from_slice(EMBEDDED_0000) };
+            rom_0000 = Some("embedded");
         }
-
-        if left_pressed && left.is_low().unwrap_or_default() && fileindex > 0 {
-            fileindex -= 1;
+        if rom_0400.is_none() {
+            unsafe { BIOS_0400.copy_from_slice(EMBEDDED_0400) };
+            rom_0400 = Some("embedded");
         }
-
-        if right_pressed && right.is_low().unwrap_or_default() && fileindex < file_count {
-            fileindex += 1;
+        (rom_0000, rom_0400)
+    };
+    // the halves of the free BIOS only work together
+    #[cfg(feature = "free-bios")]
+    let found = match found {
+        (Some(rom_0000), Some(rom_0400)) => (Some(rom_0000), Some(rom_0400)),
+        _ => {
+            let (free_0000, free_0400) = bios::free_bios();
+            unsafe {
+                BIOS_0000.copy_from_slice(free_0000);
+                BIOS_0400.copy_from_slice(free_0400);
+            }
+            (Some("free"), Some("free"))
         }
+    };
+    rprintln!("BIOS {:?}", found);
+    // nothing can be played without a BIOS
+    if let Some(report) = bios_report(found) {
+        error::show(Fatal::NoBios, &report);
+    }
 
-        if push_pressed && push.is_low().unwrap_or_default() {
-            break;
+    // kept while playing, the menu comes back with the directory and cartridge chosen last -
+    // after power on that's the one of the settings
+    let mut listing = SdListing {
+        controller: &mut controller,
+        volume: &mut volume,
+    };
+    let mut settings = listing.read_settings().unwrap_or_default();
+    let mut browser = match listing.open_dir(settings.path()) {
+        Ok(dir) => {
+            controller.close_dir(&volume, dir);
+            Browser::at(settings.path(), settings.cartridge)
         }
+        Err(_) => Browser::new(),
+    };
+
+    loop {
+        // the menu starts silent on a cleared screen, the samples queued run out
+        channel_f
+            .current_sound
+            .replace(chf_emulator::Sound::Silence);
+
+        // the menu's buffers are gone while the game runs
+        let chosen = {
+            // a command the video MCU didn't acknowledge clears the screen and redraws the menu
+            #[cfg(not(feature = "frame-dma"))]
+            let lost = Cell::new(
+                send_command(
+                    &Command::Clear { color: 0 },
+                    &peer_bsy,
+                    &mut spi,
+                    &mut delay,
+                    &mut led,
+                )
+                .is_err(),
+            );
+            #[cfg(feature = "frame-dma")]
+            let mut menu_vram = [0u8; VRAM_LEN];
+            #[cfg(feature = "frame-dma")]
+            {
+                spi_dma = send_block(spi_dma, &menu_vram, u64::MAX, &peer_bsy);
+            }
 
-        left_pressed = left.is_high().unwrap_or_default();
-        right_pressed = right.is_high().unwrap_or_default();
-        push_pressed = push.is_high().unwrap_or_default();
+            let mut shown: Text = [[b' '; COLUMNS]; ROWS];
+            #[cfg(not(feature = "frame-dma"))]
+            let mut menu_encoder = Encoder::new();
+            // time opens the screen setting the clock
+            let mut clock: Option<ClockEditor> = None;
+            let mut time_pressed = true;
+            loop {
+                let keys = controls::input();
+                let input = browser::Controller {
+                    left: keys.is_pressed(Key::Left0),
+                    right: keys.is_pressed(Key::Right0),
+                    forward: keys.is_pressed(Key::Forward0),
+                    back: keys.is_pressed(Key::Back0),
+                    push: keys.is_pressed(Key::Push0),
+                    pull: keys.is_pressed(Key::Pull0),
+                };
+
+                let time = keys.is_pressed(Key::Time) && !time_pressed;
+                time_pressed = keys.is_pressed(Key::Time);
+
+                if let Some(editor) = clock.as_mut() {
+                    match editor.update(input) {
+                        Edit::Editing => {}
+                        Edit::Set(time) => {
+                            rtc.borrow_mut().set_time(time.seconds());
+                            clock = None;
+                        }
+                        Edit::Cancelled => clock = None,
+                    }
+                } else if time {
+                    let now = DateTime::from_seconds(rtc.borrow().current_time());
+                    clock = Some(ClockEditor::new(now));
+                } else {
+                    let mut listing = SdListing {
+                        controller: &mut controller,
+                        volume: &mut volume,
+                    };
+                    match browser.update(input, &mut listing) {
+                        Ok(Some(entry)) => break entry,
+                        Ok(None) => {}
+                        Err(e) => {
+                            rprintln!("menu err {:?}", e);
+                            error::stop(Fatal::SdCard);
+                        }
+                    }
+                }
+                let text = match &clock {
+                    Some(editor) => editor.text(),
+                    None => browser.text(),
+                };
+
+                // only the characters which changed are sent
+
+                #[cfg(not(feature = "frame-dma"))]
+                {
+                    let mut send = |command: &Command| {
+                        if send_command(command, &peer_bsy, &mut spi, &mut delay, &mut led).is_err()
+                        {
+                            lost.set(true);
+                        }
+                    };
+                    draw_text(&text, &shown, &mut |x, y, color| {
+                        menu_encoder.pixel(x, y, color, &mut send)
+                    });
+                    menu_encoder.flush(&mut send);
+                }
 
-        delay.delay_us(300u16);
-    }
+                #[cfg(feature = "frame-dma")]
+                {
+                    let mut rows = 0u64;
+                    draw_text(&text, &shown, &mut |x, y, color| {
+                        store_pixel(&mut menu_vram, x, y, color);
+                        rows |= 1 << y;
+                    });
+                    if rows != 0 {
+                        spi_dma = send_block(spi_dma, &menu_vram, rows, &peer_bsy);
+                    }
+                }
 
-    select_controller_0.set_low().unwrap_or_default();
+                shown = text;
 
-    // load the cartridge
-    let idx = fileindex * 8;
-    let mut full_fname = [0u8; 12];
-    for i in 0..8 {
-        full_fname[i] = files[idx + i];
-    }
-    full_fname[8] = b'.';
-    full_fname[9] = b'B';
-    full_fname[10] = b'I';
-    full_fname[11] = b'N';
+                #[cfg(not(feature = "frame-dma"))]
+                if lost.take() {
+                    let clear = &Command::Clear { color: 0 };
+                    lost.set(
+                        send_command(clear, &peer_bsy, &mut spi, &mut delay, &mut led).is_err(),
+                    );
+                    shown = [[b' '; COLUMNS]; ROWS];
+                }
+
+                delay.delay_ms(10u16);
+            }
+        };
 
-    let file_to_load = core::str::from_utf8(&full_fname).unwrap_or_default();
+        let mut listing = SdListing {
+            controller: &mut controller,
+            volume: &mut volume,
+        };
+        let mut chosen_settings = settings;
+        chosen_settings.set_path(browser.path());
+        chosen_settings.cartridge = Some(chosen.short_name);
+        if chosen_settings != settings {
+            settings = chosen_settings;
+            if let Err(e) = listing.write_settings(&settings) {
+                rprintln!("settings err {:?}", e);
+            }
+        }
 
-    let mut file = controller
-        .open_file_in_dir(
+        // load the cartridge, a smaller one must not see the rest of the one before
+        let dir = match listing.open_dir(browser.path()) {
+            Ok(dir) => dir,
+            Err(_) => error::stop(Fatal::SdCard),
+        };
+        let mut file = match controller.open_file_in_dir(
             &mut volume,
             &dir,
-            file_to_load,
+            chosen.short_name.as_str(),
             embedded_sdmmc::Mode::ReadOnly,
-        )
-        .unwrap();
-    unsafe {
-        controller
-            .read(&volume, &mut file, &mut CARTRIDGE)
-            .unwrap_or_default();
-    }
-
-    let catridge = unsafe { CARTRIDGE };
-    let mut cpu = Cpu::new(ROM_0000, ROM_0400, &catridge, &channel_f);
-    cpu.reset();
-
-    let mut cnt = 0;
-    loop {
-        cnt += 1;
-        if cnt > 10000 {
-            cnt = 0;
-
-            // checking keys is quite slow - better use complete reads of the GPIO registers
-            handle_keys(
-                &mut select_controller_0,
-                &mut select_controller_1,
-                &button_1,
-                &button_2,
-                &button_3,
-                &button_4,
-                &right,
-                &left,
-                &up,
-                &down,
-                &ccw,
-                &cw,
-                &pull,
-                &push,
-                &channel_f,
-            )
+        ) {
+            Ok(file) => file,
+            Err(_) => error::stop(Fatal::SdCard),
+        };
+        if file.length() as usize > unsafe { CARTRIDGE.len() } {
+            error::stop(Fatal::FileTooBig);
         }
+        let len = unsafe {
+            CARTRIDGE = [0u8; 4096];
+            controller
+                .read(&volume, &mut file, &mut CARTRIDGE)
+                .unwrap_or_default()
+        };
+        controller.close_file(&volume, file).unwrap_or_default();
+        controller.close_dir(&volume, dir);
+
+        // a new `Cpu` for every cartridge, nothing of the game before is left
+        let catridge = unsafe { &CARTRIDGE };
+        let (rom_0000, rom_0400) = unsafe { (&BIOS_0000, &BIOS_0400) };
+        let mut cpu = Cpu::new(rom_0000, rom_0400, catridge, &channel_f);
+        cpu.reset();
+
+        // what the database says the cartridge keeps in its save file
+        let mut crc = Crc32::new();
+        crc.update(&catridge[..len]);
+        let crc = crc.finish();
+        let mut listing = SdListing {
+            controller: &mut controller,
+            volume: &mut volume,
+        };
+        let save_ranges = match listing.record(crc) {
+            Ok(Some(Record {
+                save: Some(save), ..
+            })) if settings.saves => SaveRanges::parse(save.as_str())
+                .map_err(|e| rprintln!("save ranges err {:?}", e))
+                .ok(),
+            _ => None,
+        };
+        let mut save_loaded = false;
+        channel_f.chords.replace(Chords::new());
+
+        // pixels are coalesced into runs and rectangles, sent at the latest when the frame ends
+        #[cfg(not(feature = "frame-dma"))]
+        let mut encoder = Encoder::new();
+        // a command the video MCU didn't acknowledge repaints the whole screen after the frame
+        #[cfg(not(feature = "frame-dma"))]
+        let lost = Cell::new(false);
+        #[cfg(not(feature = "frame-dma"))]
+        let mut send = |command: &Command| {
+            if send_command(command, &peer_bsy, &mut spi, &mut delay, &mut led).is_err() {
+                lost.set(true);
+            }
+        };
 
-        cpu.cycles = 0;
-        let opcode = cpu.fetch();
-        cpu.execute(opcode);
-
-        let should_set_pixel = channel_f.should_set_pixel.replace(false);
-
-        if should_set_pixel {
-            x = channel_f.x.take();
-            y = channel_f.y.take();
-            c = channel_f.color.take();
-            set_pixel(x, y, c, &peer_bsy, &mut spi, &mut delay, &mut led);
+        // the game starts on a cleared screen, with `frame-dma` the rows written during a frame
+        // are sent via DMA
+        #[cfg(not(feature = "frame-dma"))]
+        send(&Command::Clear { color: 0 });
+        #[cfg(feature = "frame-dma")]
+        {
+            spi_dma = send_block(spi_dma, cpu.vram(), u64::MAX, &peer_bsy);
         }
 
-        if playing_sound != *(channel_f.current_sound.borrow()) {
-            playing_sound = *(channel_f.current_sound.borrow());
+        // the samples of the core, at the rate of the PWM DAC - when the queue is full the
+        // emulation runs too fast and they are dropped
+        let mut synth = Synth::new();
+        let mut resampler = Resampler::new(audio::CORE_RATE, sound::SAMPLE_RATE);
+        loop {
+            let frame_done = cpu.step();
+
+            synth.run(cpu.cycles as u32, &mut |sample| {
+                resampler.push(sample, &mut |sample| {
+                    sound::QUEUE.push(sample);
+                })
+            });
+            synth.set_sound(*channel_f.current_sound.borrow());
+
+            let should_set_pixel = channel_f.should_set_pixel.replace(false);
+
+            #[cfg(not(feature = "frame-dma"))]
+            {
+                if should_set_pixel {
+                    let x = channel_f.x.take();
+                    let y = channel_f.y.take();
+                    let c = channel_f.color.take();
+                    encoder.pixel(x, y, c, &mut send);
+                }
 
-            match playing_sound {
-                chf_emulator::Sound::Silence => {
-                    pwm.disable(Channel::C1);
+                if frame_done {
+                    encoder.flush(&mut send);
+                    if lost.take() {
+                        repaint(&cpu, &mut encoder, &mut send);
+                    }
                 }
-                chf_emulator::Sound::Frequency1Khz => {
-                    pwm.set_period(1000.hz());
-                    pwm.enable(Channel::C1);
+            }
+
+            #[cfg(feature = "frame-dma")]
+            {
+                // the core keeps the VRAM itself
+                let _ = should_set_pixel;
+
+                if frame_done {
+                    spi_dma = send_rows(spi_dma, &mut cpu, &peer_bsy);
                 }
-                chf_emulator::Sound::Frequency500Hz => {
-                    pwm.set_period(500.hz());
-                    pwm.enable(Channel::C1);
+            }
+
+            if frame_done {
+                if let Some(ranges) =
+                    save_ranges.filter(|ranges| !save_loaded && cpu.frame >= ranges.load_frame)
+                {
+                    save_loaded = true;
+                    let mut listing = SdListing {
+                        controller: &mut controller,
+                        volume: &mut volume,
+                    };
+                    match listing.load_save(&mut cpu, &ranges, crc) {
+                        Ok(()) => {}
+                        // played for the first time
+                        Err(SaveError::Io(embedded_sdmmc::Error::FileNotFound)) => {}
+                        Err(e) => rprintln!("load save err {:?}", e),
+                    }
                 }
-                chf_emulator::Sound::Frequency120Hz => {
-                    pwm.set_period(120.hz());
-                    pwm.enable(Channel::C1);
+
+                // the game didn't see the keys of the chord, see `chf_controls::chords`
+                let chord = channel_f.chords.borrow_mut().take();
+                let mut listing = SdListing {
+                    controller: &mut controller,
+                    volume: &mut volume,
+                };
+                match chord {
+                    Some(Chord::StoreState(slot)) => {
+                        if let Err(e) = listing.store_state(&cpu, crc, slot) {
+                            rprintln!("store state err {:?}", e);
+                        }
+                    }
+                    Some(Chord::LoadState(slot)) => match listing.load_state(&mut cpu, crc, slot) {
+                        Ok(()) => {
+                            // with `frame-dma` all rows are sent with the next frame
+                            #[cfg(not(feature = "frame-dma"))]
+                            repaint(&cpu, &mut encoder, &mut send);
+                            channel_f
+                                .current_sound
+                                .replace(chf_emulator::Sound::Silence);
+                        }
+                        Err(e) => rprintln!("load state err {:?}", e),
+                    },
+                    // the save is only written going back to the menu, not when switched off
+                    Some(Chord::Menu) => {
+                        if let Some(ranges) = save_ranges.filter(|_| save_loaded) {
+                            if let Err(e) = listing.store_save(&cpu, &ranges, crc) {
+                                rprintln!("store save err {:?}", e);
+                            }
+                        }
+                        break;
+                    }
+                    None => {}
                 }
             }
         }
     }
 }
 
-fn draw_str(
-    x: u8,
-    y: u8,
-    value: &str,
-    peer_bsy: &PA9<Input<PullDown>>,
-    spi: &mut Spi<
-        SPI2,
-        Spi2NoRemap,
-        (
-            PB13<Alternate<PushPull>>,
-            PB14<Input<Floating>>,
-            PB15<Alternate<PushPull>>,
-        ),
-    >,
-    delay: &mut Delay,
-    led: &mut PC13<Output<PushPull>>,
-) {
-    let mut xx = x;
-    for c in value.chars() {
-        for i in (0..CHARACTERS.len()).step_by(9) {
-            if CHARACTERS[i] == c as u8 {
-                let data = &CHARACTERS[i + 1..i + 9];
-                draw_character(xx, y, data, peer_bsy, spi, delay, led);
+// the error shown if a BIOS ROM wasn't found
+fn bios_report(found: (Option<&str>, Option<&str>)) -> Option<Text> {
+    let mut text = [[b' '; COLUMNS]; ROWS];
+    let mut lines: [&[u8]; ROWS] = [b"MISSING BIOS", b"IN /BIOS/:", b"", b"", b"", b"", b""];
+    let mut count = 2;
+
+    if found.0.is_none() {
+        lines[count] = b"SL31253 OR";
+        lines[count + 1] = b"SL90025";
+        count += 2;
+    }
+    if found.1.is_none() {
+        lines[count] = b"SL31254";
+        count += 1;
+    }
+    if count == 2 {
+        return None;
+    }
+
+    for (row, line) in text.iter_mut().zip(lines.iter()) {
+        row[..line.len()].copy_from_slice(line);
+    }
+    Some(text)
+}
+
+// draws the characters of the menu text which changed, clearing what was there
+fn draw_text<F: FnMut(u8, u8, u8)>(text: &Text, shown: &Text, pixel: &mut F) {
+    for (row, (line, shown_line)) in text.iter().zip(shown.iter()).enumerate() {
+        for (column, (c, shown_c)) in line.iter().zip(shown_line.iter()).enumerate() {
+            if c == shown_c {
+                continue;
+            }
+
+            let glyph = font::glyph(*c);
+            let x = MENU_X + column as u8 * 8;
+            let y = MENU_Y + row as u8 * 8;
+            for yy in 0..8 {
+                for xx in 0..8 {
+                    pixel(x + xx, y + yy, font::is_set(&glyph, xx, yy) as u8);
+                }
             }
         }
-        xx += 8;
     }
 }
 
-fn draw_character(
-    x: u8,
-    y: u8,
-    data: &[u8],
+// clears the screen of the video MCU and sends all pixels of the emulator again
+#[cfg(not(feature = "frame-dma"))]
+fn repaint<F: FnMut(&Command)>(cpu: &Cpu, encoder: &mut Encoder, send: &mut F) {
+    send(&Command::Clear { color: 0 });
+    for y in 0..64 {
+        for x in 0..128 {
+            encoder.pixel(x, y, cpu.pixel(x, y), send);
+        }
+    }
+    encoder.flush(send);
+}
+
+// sends a command to the video MCU, the LED toggles when it had to be repeated or was lost
+fn send_command(
+    command: &Command,
     peer_bsy: &PA9<Input<PullDown>>,
     spi: &mut Spi<
         SPI2,
@@ -411,22 +663,81 @@ fn draw_character(
     >,
     delay: &mut Delay,
     led: &mut PC13<Output<PushPull>>,
-) {
-    for yy in 0..8 {
-        for xx in 0u8..8u8 {
-            let color = data[yy].overflowing_shr((8 - xx) as u32).0 & 1;
-            set_pixel(x + xx, y + yy as u8, color, peer_bsy, spi, delay, led);
-            delay.delay_us(85u16);
+) -> Result<(), LinkError> {
+    let mut link = SpiLink {
+        peer_bsy,
+        spi,
+        delay,
+    };
+
+    match packet::send(&mut link, command) {
+        Ok(1) => Ok(()),
+        Ok(_tries) => {
+            led.toggle().unwrap();
+            Ok(())
+        }
+        Err(e) => {
+            rprintln!("send err {:?}", e);
+            led.toggle().unwrap();
+            Err(e)
         }
     }
 }
 
-fn set_pixel(
-    x: u8,
-    y: u8,
-    c: u8,
+#[cfg(feature = "frame-dma")]
+type SpiDma = SpiTxDma<
+    SPI2,
+    Spi2NoRemap,
+    (
+        PB13<Alternate<PushPull>>,
+        PB14<Input<Floating>>,
+        PB15<Alternate<PushPull>>,
+    ),
+    dma1::C5,
+>;
+
+#[cfg(feature = "frame-dma")]
+static mut BLOCK: [u8; frame::MAX_BLOCK_LEN] = [0u8; frame::MAX_BLOCK_LEN];
+
+#[cfg(feature = "frame-dma")]
+const VRAM_LEN: usize = 128 * 64 / 4;
+
+// sends the rows written during the frame as soon as the video MCU stops drawing pixel lines
+// this also makes the emulation run at the frame rate of the video MCU
+#[cfg(feature = "frame-dma")]
+fn send_rows(spi_dma: SpiDma, cpu: &mut Cpu, peer_bsy: &PA9<Input<PullDown>>) -> SpiDma {
+    // one more row per frame repairs blocks the video MCU dropped
+    let rows = cpu.take_dirty_rows() | 1 << (cpu.frame % 64);
+    send_block(spi_dma, cpu.vram(), rows, peer_bsy)
+}
+
+#[cfg(feature = "frame-dma")]
+fn send_block(
+    spi_dma: SpiDma,
+    vram: &[u8; VRAM_LEN],
+    rows: u64,
     peer_bsy: &PA9<Input<PullDown>>,
-    spi: &mut Spi<
+) -> SpiDma {
+    let block = frame::encode_block(vram, rows, unsafe { &mut BLOCK });
+
+    while peer_bsy.is_low().unwrap_or_default() {}
+    while peer_bsy.is_high().unwrap_or_default() {}
+
+    let (_, spi_dma) = spi_dma.write(block).wait();
+    spi_dma
+}
+
+// the menu's copy of the VRAM, packed like the emulator's
+#[cfg(feature = "frame-dma")]
+fn store_pixel(vram: &mut [u8; VRAM_LEN], x: u8, y: u8, color: u8) {
+    let idx = x as usize + y as usize * 128;
+    let shift = (idx % 4) * 2;
+    vram[idx / 4] = (vram[idx / 4] & !(0b11 << shift)) | ((color & 0b11) << shift);
+}
+
+struct SpiLink<'a> {
+    peer_bsy: &'a PA9<Input<PullDown>>,
+    spi: &'a mut Spi<
         SPI2,
         Spi2NoRemap,
         (
@@ -435,32 +746,13 @@ fn set_pixel(
             PB15<Alternate<PushPull>>,
         ),
     >,
-    delay: &mut Delay,
-    led: &mut PC13<Output<PushPull>>,
-) {
-    let mut was_err = false;
-    let mut snd_byte = 0u8;
-    let mut z: u8 = 0;
-
-    // XXXXXXXY YYYYYCCC + 0x00
-
-    // if it's not a pixel in the safe are and not a "select palette" pixel ... don't transmit it
-    if (x < 4 || x > 101 || y < 4 || y > 62) && x != 125 && x != 126 {
-        return;
-    }
-
-    // set pixel stuff ... quite ugly right now
-    loop {
-        if z == 0 {
-            snd_byte = x.overflowing_shl(1).0 | (y.overflowing_shr(5).0 & 1);
-        } else if z == 1 {
-            snd_byte = y.overflowing_shl(3).0 | c;
-        }
-
-        let res = block!(spi.send(snd_byte));
+    delay: &'a mut Delay,
+}
 
-        if let Err(_) = res {
-            // can we do anything about it here and now?
+impl<'a> Link for SpiLink<'a> {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        if let Err(e) = block!(self.spi.send(byte)) {
+            rprintln!("send err {:?}", e);
         }
 
         // for some reason things get out of sync w/o a delay
@@ -468,42 +760,24 @@ fn set_pixel(
         // during a scanline
         // by looking at the busy pin we know if the other MCU
         // is currently drawing pixels or not
-        let additional_wait_time = if x == 125 || x == 126 {
-            10 // will re-color
-        } else {
-            0
-        };
-
-        let wait_time = if peer_bsy.is_high().unwrap_or_default() {
+        let wait_time = if self.peer_bsy.is_high().unwrap_or_default() {
             65u16
         } else {
             15u16
-        } + additional_wait_time;
-        delay.delay_us(wait_time);
-
-        let res = block!(spi.read());
-        match res {
-            Ok(_v) => {
-                if _v != 0x7f {
-                    led.toggle().unwrap();
-                    was_err = true;
-                }
-            }
-            Err(e) => {
-                rprintln!("send err {:?}", e);
-                was_err = true;
-            }
-        }
-
-        if !was_err {
-            z += 1;
+        };
+        self.delay.delay_us(wait_time);
 
-            if z >= 2 {
-                break;
+        match block!(self.spi.read()) {
+            Ok(reply) => reply,
+            Err(e) => {
+                rprintln!("read err {:?}", e);
+                packet::NAK
             }
         }
+    }
 
-        was_err = false;
+    fn wait_us(&mut self, us: u16) {
+        self.delay.delay_us(us);
     }
 }
 
@@ -516,28 +790,7 @@ pub struct StmChannelF {
 
     current_sound: RefCell<chf_emulator::Sound>,
 
-    key_1: RefCell<bool>,
-    key_2: RefCell<bool>,
-    key_3: RefCell<bool>,
-    key_4: RefCell<bool>,
-
-    r0: RefCell<bool>,
-    l0: RefCell<bool>,
-    u0: RefCell<bool>,
-    d0: RefCell<bool>,
-    ccw0: RefCell<bool>,
-    cw0: RefCell<bool>,
-    pull0: RefCell<bool>,
-    push0: RefCell<bool>,
-
-    r1: RefCell<bool>,
-    l1: RefCell<bool>,
-    u1: RefCell<bool>,
-    d1: RefCell<bool>,
-    ccw1: RefCell<bool>,
-    cw1: RefCell<bool>,
-    pull1: RefCell<bool>,
-    push1: RefCell<bool>,
+    chords: RefCell<Chords>,
 }
 
 impl ChannelF for StmChannelF {
@@ -553,178 +806,525 @@ impl ChannelF for StmChannelF {
     }
 
     fn key_pressed(&self, key: chf_emulator::Key) -> bool {
-        match key {
-            chf_emulator::Key::Start => *self.key_1.borrow(),
-            chf_emulator::Key::Hold => *self.key_2.borrow(),
-            chf_emulator::Key::Mode => *self.key_3.borrow(),
-            chf_emulator::Key::Time => *self.key_4.borrow(),
-
-            chf_emulator::Key::Right0 => *self.r0.borrow(),
-            chf_emulator::Key::Left0 => *self.l0.borrow(),
-            chf_emulator::Key::Forward0 => *self.u0.borrow(),
-            chf_emulator::Key::Back0 => *self.d0.borrow(),
-            chf_emulator::Key::CounterClockwise0 => *self.ccw0.borrow(),
-            chf_emulator::Key::Clockwise0 => *self.cw0.borrow(),
-            chf_emulator::Key::Pull0 => *self.pull0.borrow(),
-            chf_emulator::Key::Push0 => *self.push0.borrow(),
-
-            chf_emulator::Key::Right1 => *self.r1.borrow(),
-            chf_emulator::Key::Left1 => *self.l1.borrow(),
-            chf_emulator::Key::Forward1 => *self.u1.borrow(),
-            chf_emulator::Key::Back1 => *self.d1.borrow(),
-            chf_emulator::Key::CounterClockwise1 => *self.ccw1.borrow(),
-            chf_emulator::Key::Clockwise1 => *self.cw1.borrow(),
-            chf_emulator::Key::Pull1 => *self.pull1.borrow(),
-            chf_emulator::Key::Push1 => *self.push1.borrow(),
-
-            _ => false,
+        controls::input().is_pressed(key)
+    }
+
+    // one read of what the timer interrupt scanned, without the keys of a chord
+    fn input(&self) -> Input {
+        self.chords.borrow_mut().update(controls::input())
+    }
+}
+
+// the top left corner of the menu text
+const MENU_X: u8 = 4;
+const MENU_Y: u8 = 4;
+
+/// The titles of the cartridges, see `chf_menu::database`
+const DATABASE: &str = "CARTS.TXT";
+
+/// Every file in here is checked for being a known BIOS ROM, see `chf_emulator::bios`
+const BIOS_DIR: &str = "BIOS";
+
+/// The save files, named by the CRC-32 of the cartridge - without the directory nothing is saved
+const SAVES_DIR: &str = "SAVES";
+
+/// The save states, see `chf_emulator::state::slot_file_name` - it has to exist, too
+const STATES_DIR: &str = "STATES";
+
+/// The preferences of the menu, see `chf_menu::settings`
+const SETTINGS: &str = "SETTINGS.TXT";
+
+// files looked at in `BIOS_DIR`
+const MAX_BIOS_FILES: usize = 8;
+
+// `Entry::location` of a directory entry, its block and index in the block
+const ENTRIES_PER_BLOCK: u32 = (Block::LEN / lfn::ENTRY_LEN) as u32;
+
+struct SdListing<'a, D: BlockDevice, T: TimeSource> {
+    controller: &'a mut embedded_sdmmc::Controller<D, T>,
+    volume: &'a mut Volume,
+}
+
+impl<'a, D, T> SdListing<'a, D, T>
+where
+    D: BlockDevice,
+    D::Error: core::fmt::Debug,
+    T: TimeSource,
+{
+    fn open_dir(&mut self, path: &[Name]) -> Result<Directory, embedded_sdmmc::Error<D::Error>> {
+        let mut dir = self.controller.open_root_dir(self.volume)?;
+        for name in path {
+            let sub_dir = self.controller.open_dir(self.volume, &dir, name.as_str());
+            self.controller.close_dir(self.volume, dir);
+            dir = sub_dir?;
+        }
+        Ok(dir)
+    }
+
+    // copies the known ROMs of `BIOS_DIR` to their location, returns the names of the ROMs found
+    fn load_bios(
+        &mut self,
+        rom_0000: &mut [u8; bios::ROM_LEN],
+        rom_0400: &mut [u8; bios::ROM_LEN],
+    ) -> Result<(Option<&'static str>, Option<&'static str>), embedded_sdmmc::Error<D::Error>> {
+        let path = [Name::new(BIOS_DIR.as_bytes())];
+        let mut files = [None; MAX_BIOS_FILES];
+        let mut count = 0;
+        let listed = self.list(&path, &mut |entry| {
+            if !entry.is_dir && count < MAX_BIOS_FILES {
+                files[count] = Some(entry.short_name);
+                count += 1;
+            }
+        });
+        match listed {
+            Ok(()) => {}
+            Err(embedded_sdmmc::Error::FileNotFound) => return Ok((None, None)),
+            Err(e) => return Err(e),
+        }
+
+        let dir = self.open_dir(&path)?;
+        let mut found = (None, None);
+        for name in files.iter().flatten() {
+            let mut data = [0u8; bios::ROM_LEN];
+            let mut len = 0;
+            let result = self.read_file(&dir, name.as_str(), &mut |chunk| {
+                let end = (len + chunk.len()).min(bios::ROM_LEN);
+                if len < end {
+                    data[len..end].copy_from_slice(&chunk[..end - len]);
+                }
+                len += chunk.len();
+            });
+            if let Err(e) = result {
+                self.controller.close_dir(self.volume, dir);
+                return Err(e);
+            }
+
+            let rom = if len == bios::ROM_LEN {
+                bios::identify(&data)
+            } else {
+                None
+            };
+            match rom {
+                Some(rom) if rom.location == Location::Rom0000 => {
+                    rom_0000.copy_from_slice(&data);
+                    found.0 = Some(rom.name);
+                }
+                Some(rom) => {
+                    rom_0400.copy_from_slice(&data);
+                    found.1 = Some(rom.name);
+                }
+                None => {}
+            }
+        }
+
+        self.controller.close_dir(self.volume, dir);
+        Ok(found)
+    }
+
+    // calls `chunk` with the contents of the file, block by block
+    fn read_file(
+        &mut self,
+        dir: &Directory,
+        name: &str,
+        chunk: &mut dyn FnMut(&[u8]),
+    ) -> Result<(), embedded_sdmmc::Error<D::Error>> {
+        let mut file = self.controller.open_file_in_dir(
+            self.volume,
+            dir,
+            name,
+            embedded_sdmmc::Mode::ReadOnly,
+        )?;
+
+        let mut buffer = [0u8; Block::LEN];
+        let mut result = Ok(());
+        while !file.eof() {
+            match self.controller.read(self.volume, &mut file, &mut buffer) {
+                Ok(len) => chunk(&buffer[..len]),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        self.controller.close_file(self.volume, file)?;
+        result
+    }
+
+    // creates or replaces the file, `write` fills it
+    fn write_file(
+        &mut self,
+        dir: &Directory,
+        name: &str,
+        write: &mut dyn FnMut(&mut SdStream<D, T>) -> Result<(), embedded_sdmmc::Error<D::Error>>,
+    ) -> Result<(), embedded_sdmmc::Error<D::Error>> {
+        let file = self.controller.open_file_in_dir(
+            self.volume,
+            dir,
+            name,
+            embedded_sdmmc::Mode::ReadWriteCreateOrTruncate,
+        )?;
+
+        let mut stream = SdStream::new(self.controller, self.volume, file);
+        let result = write(&mut stream).and_then(|_| stream.flush());
+        let closed = stream.close();
+        result.and(closed)
+    }
+
+    // the line of the cartridge with the CRC-32 `crc` in `DATABASE`
+    fn record(&mut self, crc: u32) -> Result<Option<Record>, embedded_sdmmc::Error<D::Error>> {
+        let dir = self.controller.open_root_dir(self.volume)?;
+        let mut search = database::Search::new(crc);
+        let result = self.read_file(&dir, DATABASE, &mut |chunk| search.push(chunk));
+        self.controller.close_dir(self.volume, dir);
+
+        match result {
+            Ok(()) => Ok(search.finish()),
+            // no database on the card
+            Err(embedded_sdmmc::Error::FileNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // the defaults if there's no `SETTINGS` file
+    fn read_settings(&mut self) -> Result<Settings, embedded_sdmmc::Error<D::Error>> {
+        let mut text = [0u8; settings::MAX_LEN];
+        let mut len = 0;
+        let dir = self.controller.open_root_dir(self.volume)?;
+        let result = self.read_file(&dir, SETTINGS, &mut |chunk| {
+            let end = (len + chunk.len()).min(settings::MAX_LEN);
+            text[len..end].copy_from_slice(&chunk[..end - len]);
+            len = end;
+        });
+        self.controller.close_dir(self.volume, dir);
+
+        match result {
+            Ok(()) => Ok(Settings::parse(&text[..len])),
+            Err(embedded_sdmmc::Error::FileNotFound) => Ok(Settings::new()),
+            Err(e) => Err(e),
         }
     }
+
+    fn write_settings(
+        &mut self,
+        settings: &Settings,
+    ) -> Result<(), embedded_sdmmc::Error<D::Error>> {
+        let dir = self.controller.open_root_dir(self.volume)?;
+        let result = self.write_file(&dir, SETTINGS, &mut |stream| {
+            let mut result = Ok(());
+            settings.write(&mut |bytes| {
+                if result.is_ok() {
+                    result = stream.write(bytes);
+                }
+            });
+            result
+        });
+        self.controller.close_dir(self.volume, dir);
+        result
+    }
+
+    // restores the save file of the cartridge with the CRC-32 `crc`
+    fn load_save(
+        &mut self,
+        cpu: &mut Cpu,
+        ranges: &SaveRanges,
+        crc: u32,
+    ) -> Result<(), SaveError<embedded_sdmmc::Error<D::Error>>> {
+        let dir = self.open_dir(&[Name::new(SAVES_DIR.as_bytes())])?;
+        let name = save_name(crc);
+        let file = self.controller.open_file_in_dir(
+            self.volume,
+            &dir,
+            name.as_str(),
+            embedded_sdmmc::Mode::ReadOnly,
+        );
+        let result = match file {
+            Ok(file) => {
+                let mut stream = SdStream::new(self.controller, self.volume, file);
+                let result = save::read(cpu, ranges, crc, &mut stream);
+                let closed = stream.close();
+                result.and(closed.map_err(SaveError::Io))
+            }
+            Err(e) => Err(SaveError::Io(e)),
+        };
+        self.controller.close_dir(self.volume, dir);
+        result
+    }
+
+    // a save state of the cartridge with the CRC-32 `crc` in `STATES_DIR`, streamed
+    fn store_state(
+        &mut self,
+        cpu: &Cpu,
+        crc: u32,
+        slot: u8,
+    ) -> Result<(), embedded_sdmmc::Error<D::Error>> {
+        let dir = self.open_dir(&[Name::new(STATES_DIR.as_bytes())])?;
+        let name = state::slot_file_name(crc, slot);
+        let name = core::str::from_utf8(&name).unwrap_or_default();
+        let result = self.write_file(&dir, name, &mut |stream| cpu.save_state(stream));
+        self.controller.close_dir(self.volume, dir);
+        result
+    }
+
+    fn load_state(
+        &mut self,
+        cpu: &mut Cpu,
+        crc: u32,
+        slot: u8,
+    ) -> Result<(), StateError<embedded_sdmmc::Error<D::Error>>> {
+        let dir = self.open_dir(&[Name::new(STATES_DIR.as_bytes())])?;
+        let name = state::slot_file_name(crc, slot);
+        let name = core::str::from_utf8(&name).unwrap_or_default();
+        let file = self.controller.open_file_in_dir(
+            self.volume,
+            &dir,
+            name,
+            embedded_sdmmc::Mode::ReadOnly,
+        );
+        let result = match file {
+            Ok(file) => {
+                let mut stream = SdStream::new(self.controller, self.volume, file);
+                let result = cpu.load_state(&mut stream);
+                let closed = stream.close();
+                result.and(closed.map_err(StateError::Io))
+            }
+            Err(e) => Err(StateError::Io(e)),
+        };
+        self.controller.close_dir(self.volume, dir);
+        result
+    }
+
+    fn store_save(
+        &mut self,
+        cpu: &Cpu,
+        ranges: &SaveRanges,
+        crc: u32,
+    ) -> Result<(), embedded_sdmmc::Error<D::Error>> {
+        let dir = self.open_dir(&[Name::new(SAVES_DIR.as_bytes())])?;
+        let name = save_name(crc);
+        let result = self.write_file(&dir, name.as_str(), &mut |stream| {
+            save::write(cpu, ranges, crc, stream)
+        });
+        self.controller.close_dir(self.volume, dir);
+        result
+    }
+}
+
+// e.g. `0123ABCD.SAV`
+fn save_name(crc: u32) -> Name {
+    let mut name = NameBuffer {
+        bytes: [0u8; 12],
+        len: 0,
+    };
+    core::fmt::write(&mut name, format_args!("{:08X}.SAV", crc)).unwrap_or_default();
+    Name::new(&name.bytes[..name.len])
+}
+
+// an open file read or written through a buffer of one block
+struct SdStream<'a, D: BlockDevice, T: TimeSource> {
+    controller: &'a mut embedded_sdmmc::Controller<D, T>,
+    volume: &'a mut Volume,
+    file: File,
+    buffer: [u8; Block::LEN],
+    pos: usize,
+    len: usize,
 }
 
-pub fn handle_keys<O0, O1, I0, I1, I2, I3, I4, I5, I6, I7, I8, I9, I10, I11>(
-    select_0: &mut O0,
-    select_1: &mut O1,
-
-    button_1: &I0,
-    button_2: &I1,
-    button_3: &I2,
-    button_4: &I3,
-
-    right: &I4,
-    left: &I5,
-    up: &I6,
-    down: &I7,
-    ccw: &I8,
-    cw: &I9,
-    pull: &I10,
-    push: &I11,
-
-    channel_f: &StmChannelF,
-) where
-    O0: OutputPin,
-    O1: OutputPin,
-    I0: InputPin,
-    I1: InputPin,
-    I2: InputPin,
-    I3: InputPin,
-    I4: InputPin,
-    I5: InputPin,
-    I6: InputPin,
-    I7: InputPin,
-    I8: InputPin,
-    I9: InputPin,
-    I10: InputPin,
-    I11: InputPin,
+impl<'a, D, T> SdStream<'a, D, T>
+where
+    D: BlockDevice,
+    T: TimeSource,
 {
-    select_0.set_high().unwrap_or_default();
-    select_1.set_low().unwrap_or_default();
-
-    // fast switching of the select line induces wrong readings with longer wires (interferences?)
-    // waiting here is not really a good thing but it solves the problem for now
-    for _ in 0..200 {
-        unsafe {
-            asm!("nop");
+    fn new(
+        controller: &'a mut embedded_sdmmc::Controller<D, T>,
+        volume: &'a mut Volume,
+        file: File,
+    ) -> SdStream<'a, D, T> {
+        SdStream {
+            controller,
+            volume,
+            file,
+            buffer: [0u8; Block::LEN],
+            pos: 0,
+            len: 0,
         }
     }
 
-    *channel_f.key_1.borrow_mut() = button_1.is_high().unwrap_or(false);
-    *channel_f.key_2.borrow_mut() = button_2.is_high().unwrap_or(false);
+    // writes what's left in the buffer
+    fn flush(&mut self) -> Result<(), embedded_sdmmc::Error<D::Error>> {
+        if self.len > 0 {
+            self.controller
+                .write(self.volume, &mut self.file, &self.buffer[..self.len])?;
+            self.len = 0;
+        }
+        Ok(())
+    }
+
+    fn close(self) -> Result<(), embedded_sdmmc::Error<D::Error>> {
+        self.controller.close_file(self.volume, self.file)
+    }
+}
 
-    *channel_f.l0.borrow_mut() = left.is_high().unwrap_or(false);
-    *channel_f.r0.borrow_mut() = right.is_high().unwrap_or(false);
-    *channel_f.u0.borrow_mut() = up.is_high().unwrap_or(false);
-    *channel_f.d0.borrow_mut() = down.is_high().unwrap_or(false);
-    *channel_f.ccw0.borrow_mut() = ccw.is_high().unwrap_or(false);
-    *channel_f.cw0.borrow_mut() = cw.is_high().unwrap_or(false);
-    *channel_f.push0.borrow_mut() = push.is_high().unwrap_or(false);
-    *channel_f.pull0.borrow_mut() = pull.is_high().unwrap_or(false);
+impl<'a, D, T> StateWriter for SdStream<'a, D, T>
+where
+    D: BlockDevice,
+    T: TimeSource,
+{
+    type Error = embedded_sdmmc::Error<D::Error>;
 
-    select_0.set_low().unwrap_or_default();
-    select_1.set_high().unwrap_or_default();
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        for byte in data {
+            if self.len == Block::LEN {
+                self.flush()?;
+            }
+            self.buffer[self.len] = *byte;
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
 
-    for _ in 0..200 {
-        unsafe {
-            asm!("nop");
+impl<'a, D, T> StateReader for SdStream<'a, D, T>
+where
+    D: BlockDevice,
+    T: TimeSource,
+{
+    type Error = embedded_sdmmc::Error<D::Error>;
+
+    fn read(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        for byte in data.iter_mut() {
+            if self.pos == self.len {
+                self.len = self
+                    .controller
+                    .read(self.volume, &mut self.file, &mut self.buffer)?;
+                self.pos = 0;
+                if self.len == 0 {
+                    return Err(embedded_sdmmc::Error::EndOfFile);
+                }
+            }
+            *byte = self.buffer[self.pos];
+            self.pos += 1;
         }
+        Ok(())
+    }
+
+    fn remaining(&self) -> usize {
+        self.file.left() as usize + self.len - self.pos
+    }
+}
+
+impl<'a, D, T> Listing for SdListing<'a, D, T>
+where
+    D: BlockDevice,
+    D::Error: core::fmt::Debug,
+    T: TimeSource,
+{
+    type Error = embedded_sdmmc::Error<D::Error>;
+
+    fn list(&mut self, path: &[Name], entry: &mut dyn FnMut(&Entry)) -> Result<(), Self::Error> {
+        let dir = self.open_dir(path)?;
+        let result = self
+            .controller
+            .iterate_dir(self.volume, &dir, |dir_entry: &DirEntry| {
+                let mut short_name = NameBuffer {
+                    bytes: [0u8; 12],
+                    len: 0,
+                };
+                core::fmt::write(&mut short_name, format_args!("{}", dir_entry.name))
+                    .unwrap_or_default();
+                let short_name = Name::new(&short_name.bytes[..short_name.len]);
+
+                // `.` and `..` of sub directories, the browser adds its own `..`
+                if dir_entry.attributes.is_volume()
+                    || dir_entry.attributes.is_hidden()
+                    || short_name.as_bytes().first() == Some(&b'.')
+                {
+                    return;
+                }
+
+                entry(&Entry {
+                    short_name,
+                    is_dir: dir_entry.attributes.is_directory(),
+                    location: dir_entry.entry_block.0 * ENTRIES_PER_BLOCK
+                        + dir_entry.entry_offset / lfn::ENTRY_LEN as u32,
+                });
+            });
+        self.controller.close_dir(self.volume, dir);
+        result
+    }
+
+    // embedded-sdmmc skips the long name entries, they are read from the raw blocks
+    fn long_name(&mut self, _path: &[Name], entry: &Entry) -> Result<Option<Name>, Self::Error> {
+        let block = entry.location / ENTRIES_PER_BLOCK;
+        let offset = (entry.location % ENTRIES_PER_BLOCK) as usize * lfn::ENTRY_LEN;
+
+        // the long name may start in the block before, it's not found if it's in another cluster
+        let mut blocks = [Block::new(), Block::new()];
+        self.controller
+            .device()
+            .read(&mut blocks, BlockIdx(block.saturating_sub(1)), "lfn")
+            .map_err(embedded_sdmmc::Error::DeviceError)?;
+
+        let mut entries = [0u8; 2 * Block::LEN];
+        entries[..Block::LEN].copy_from_slice(&blocks[0].contents);
+        entries[Block::LEN..].copy_from_slice(&blocks[1].contents);
+        let offset = if block == 0 {
+            offset
+        } else {
+            Block::LEN + offset
+        };
+
+        Ok(lfn::long_name(&entries, offset))
     }
 
-    *channel_f.key_3.borrow_mut() = button_3.is_high().unwrap_or(false);
-    *channel_f.key_4.borrow_mut() = button_4.is_high().unwrap_or(false);
+    fn title(&mut self, path: &[Name], entry: &Entry) -> Result<Option<Name>, Self::Error> {
+        let dir = self.open_dir(path)?;
+        let mut crc = Crc32::new();
+        let result = self.read_file(&dir, entry.short_name.as_str(), &mut |chunk| {
+            crc.update(chunk)
+        });
+        self.controller.close_dir(self.volume, dir);
+        result?;
 
-    *channel_f.l1.borrow_mut() = left.is_high().unwrap_or(false);
-    *channel_f.r1.borrow_mut() = right.is_high().unwrap_or(false);
-    *channel_f.u1.borrow_mut() = up.is_high().unwrap_or(false);
-    *channel_f.d1.borrow_mut() = down.is_high().unwrap_or(false);
-    *channel_f.ccw1.borrow_mut() = ccw.is_high().unwrap_or(false);
-    *channel_f.cw1.borrow_mut() = cw.is_high().unwrap_or(false);
-    *channel_f.push1.borrow_mut() = push.is_high().unwrap_or(false);
-    *channel_f.pull1.borrow_mut() = pull.is_high().unwrap_or(false);
+        Ok(self.record(crc.finish())?.map(|record| record.title))
+    }
+}
 
-    select_0.set_low().unwrap_or_default();
-    select_1.set_low().unwrap_or_default();
+struct NameBuffer {
+    bytes: [u8; 12],
+    len: usize,
 }
 
-struct FakeTimeSource;
-
-impl TimeSource for FakeTimeSource {
-    fn get_timestamp(&self) -> embedded_sdmmc::Timestamp {
-        embedded_sdmmc::Timestamp {
-            year_since_1970: 0,
-            zero_indexed_month: 0,
-            zero_indexed_day: 0,
-            hours: 0,
-            minutes: 0,
-            seconds: 0,
+impl core::fmt::Write for NameBuffer {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.bytes() {
+            if self.len < self.bytes.len() {
+                self.bytes[self.len] = c;
+                self.len += 1;
+            }
         }
+        Ok(())
     }
 }
 
-const ROM_0000: &'static [u8] = include_bytes!("../../chf-emulator/roms/SL31253.bin");
-const ROM_0400: &'static [u8] = include_bytes!("../../chf-emulator/roms/SL31254.bin");
+/// The time of the RTC, see `chf_menu::clock` - it counts the seconds since 1970
+struct RtcTimeSource<'a> {
+    rtc: &'a RefCell<Rtc>,
+}
 
-static mut CARTRIDGE: [u8; 4096] = [0u8; 4096];
+impl<'a> TimeSource for RtcTimeSource<'a> {
+    fn get_timestamp(&self) -> Timestamp {
+        let time = DateTime::from_seconds(self.rtc.borrow().current_time());
+        Timestamp::from_fat(time.fat_date(), time.fat_time())
+    }
+}
+
+#[cfg(feature = "embedded-bios")]
+const EMBEDDED_0000: &[u8] = include_bytes!("../../chf-emulator/roms/SL31253.bin");
+#[cfg(feature = "embedded-bios")]
+const EMBEDDED_0400: &[u8] = include_bytes!("../../chf-emulator/roms/SL31254.bin");
 
-const CHARACTERS: &'static [u8] = &[
-    b'A', 0b00011000, 0b00100100, 0b01000010, 0b01000010, 0b01111110, 0b01000010, 0b01000010,
-    0b01000010, b'B', 0b11111000, 0b10000100, 0b10000100, 0b11111100, 0b10000010, 0b10000010,
-    0b10000010, 0b11111100, b'C', 0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b10000000,
-    0b10000000, 0b10000000, 0b11111110, b'D', 0b11111000, 0b10000100, 0b10000010, 0b10000010,
-    0b10000010, 0b10000010, 0b10000100, 0b11111000, b'E', 0b11111110, 0b10000000, 0b10000000,
-    0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b11111110, b'F', 0b11111110, 0b10000000,
-    0b10000000, 0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b10000000, b'G', 0b11111110,
-    0b10000000, 0b10000000, 0b10001110, 0b10000010, 0b10000010, 0b10000010, 0b11111110, b'H',
-    0b10000010, 0b10000010, 0b10000010, 0b11111110, 0b10000010, 0b10000010, 0b10000010, 0b10000010,
-    b'I', 0b00111000, 0b00010000, 0b00010000, 0b00010000, 0b00010000, 0b00010000, 0b00010000,
-    0b00111000, b'J', 0b00000100, 0b00000100, 0b00000100, 0b00000100, 0b00000100, 0b00000100,
-    0b01000100, 0b00111000, b'K', 0b10000010, 0b10000100, 0b10001000, 0b11110000, 0b10001000,
-    0b10000100, 0b10000010, 0b10000010, b'L', 0b10000000, 0b10000000, 0b10000000, 0b10000000,
-    0b10000000, 0b10000000, 0b10000000, 0b11111110, b'M', 0b10000010, 0b11000110, 0b10011010,
-    0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10000010, b'N', 0b10000010, 0b11000010,
-    0b10100010, 0b10010010, 0b10010010, 0b10001010, 0b10000110, 0b10000010, b'O', 0b01111100,
-    0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b01111100, b'P',
-    0b11111110, 0b10000010, 0b10000010, 0b11111100, 0b10000000, 0b10000000, 0b10000000, 0b10000000,
-    b'Q', 0b01111100, 0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b10001010, 0b10000110,
-    0b01111110, b'R', 0b11111110, 0b10000010, 0b10000010, 0b11111100, 0b10000010, 0b10000010,
-    0b10000010, 0b10000010, b'S', 0b01111100, 0b10000010, 0b10000000, 0b10000000, 0b01111100,
-    0b00000010, 0b10000010, 0b01111000, b'T', 0b11111110, 0b00010000, 0b00010000, 0b00010000,
-    0b00010000, 0b00010000, 0b00010000, 0b00010000, b'U', 0b10000010, 0b10000010, 0b10000010,
-    0b10000010, 0b10000010, 0b10000010, 0b10000010, 0b01111100, b'V', 0b10000010, 0b10000010,
-    0b10000010, 0b01000100, 0b01000100, 0b01000100, 0b00101000, 0b00010000, b'W', 0b10000010,
-    0b10000010, 0b10000010, 0b10000010, 0b10010010, 0b10101010, 0b11000110, 0b10000010, b'X',
-    0b10000010, 0b10000010, 0b01000100, 0b01000100, 0b00011000, 0b00100100, 0b00100100, 0b10000010,
-    b'Y', 0b10000010, 0b10000010, 0b01000100, 0b00101000, 0b00010000, 0b00010000, 0b00010000,
-    0b00010000, b'Z', 0b11111110, 0b00000100, 0b00000100, 0b00010000, 0b00100000, 0b01000000,
-    0b01000000, 0b11111110, b'0', 0b01111100, 0b10000010, 0b10000010, 0b10000010, 0b10000010,
-    0b10000010, 0b10000010, 0b01111100, b'1', 0b00010000, 0b00110000, 0b01010000, 0b00010000,
-    0b00010000, 0b00010000, 0b00010000, 0b00010000, b'2', 0b11111110, 0b00000010, 0b00000010,
-    0b00000010, 0b11111110, 0b10000000, 0b10000000, 0b11111110, b'3', 0b11111100, 0b00000010,
-    0b00000010, 0b11111100, 0b00000010, 0b00000010, 0b00000010, 0b11111100, b'4', 0b10000010,
-    0b10000010, 0b10000010, 0b11111110, 0b00000010, 0b00000010, 0b00000010, 0b00000010, b'5',
-    0b11111110, 0b10000000, 0b10000000, 0b10000000, 0b11111110, 0b00000010, 0b00000010, 0b11111110,
-    b'6', 0b01111110, 0b10000000, 0b10000000, 0b11111100, 0b10000010, 0b10000010, 0b10000010,
-    0b11111100, b'7', 0b11111110, 0b00000010, 0b00000100, 0b00001000, 0b00010000, 0b00010000,
-    0b00010000, 0b00010000, b'8', 0b01111100, 0b10000010, 0b10000010, 0b01111100, 0b10000010,
-    0b10000010, 0b10000010, 0b01111100, b'9', 0b11111110, 0b10000010, 0b10000010, 0b11111110,
-    0b00000010, 0b00000010, 0b00000010, 0b00000010, 0b11111110,
-];
+static mut BIOS_0000: [u8; bios::ROM_LEN] = [0u8; bios::ROM_LEN];
+static mut BIOS_0400: [u8; bios::ROM_LEN] = [0u8; bios::ROM_LEN];
+
+static mut CARTRIDGE: [u8; 4096] = [0u8; 4096];
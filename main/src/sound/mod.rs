@@ -0,0 +1,102 @@
+//! PCM playback through a PWM DAC
+//!
+//! TIM2 channel 1 on PA0 runs at 281.25 kHz with 8 bit duty, an RC low pass turns it into the
+//! audio signal. The update of TIM4 requests DMA1 channel 7 at `SAMPLE_RATE`, it writes the
+//! duties from a circular buffer. The interrupt of the DMA refills the half just played from
+//! `QUEUE`, the main loop pushes the samples of `chf_emulator::audio` there.
+
+use core::mem::MaybeUninit;
+
+use chf_emulator::audio::{SampleQueue, SILENCE};
+use stm32f1xx_hal::dma::dma1;
+use stm32f1xx_hal::pac::{self, interrupt, Interrupt};
+
+/// Samples per second played
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// The rate of TIM2, its period is 256 ticks of 72 MHz
+pub const PWM_RATE: u32 = 72_000_000 / 256;
+
+/// Filled by the main loop, played with a delay of at most 20 ms more
+pub static QUEUE: SampleQueue = SampleQueue::new();
+
+// 20 ms per half
+const HALF_LEN: usize = SAMPLE_RATE as usize / 50;
+
+static mut BUFFER: [u8; 2 * HALF_LEN] = [SILENCE; 2 * HALF_LEN];
+static mut LAST: u8 = SILENCE;
+static mut TIMER_TIM4: MaybeUninit<pac::TIM4> = MaybeUninit::uninit();
+static mut CHANNEL: MaybeUninit<dma1::C7> = MaybeUninit::uninit();
+
+/// Starts playing, the PWM of TIM2 channel 1 has to run at `PWM_RATE`
+///
+/// Splitting DMA1 stops all its channels, so it has to be split before.
+pub fn init(tim4: pac::TIM4, channel: dma1::C7) {
+    unsafe {
+        let rcc = &*pac::RCC::ptr();
+        rcc.apb1enr.modify(|_, w| w.tim4en().set_bit());
+
+        // bytes to the 16 bit duty register
+        let dma = &*pac::DMA1::ptr();
+        dma.ch7
+            .par
+            .write(|w| w.bits(&(*pac::TIM2::ptr()).ccr1 as *const _ as u32));
+        dma.ch7.mar.write(|w| w.bits(BUFFER.as_ptr() as u32));
+        dma.ch7.ndtr.write(|w| w.bits(BUFFER.len() as u32));
+        dma.ch7.cr.write(|w| {
+            w.mem2mem()
+                .clear_bit()
+                .pl()
+                .high()
+                .msize()
+                .bits8()
+                .psize()
+                .bits16()
+                .minc()
+                .set_bit()
+                .pinc()
+                .clear_bit()
+                .circ()
+                .set_bit()
+                .dir()
+                .set_bit() // from memory
+                .htie()
+                .set_bit()
+                .tcie()
+                .set_bit()
+                .en()
+                .set_bit()
+        });
+
+        pac::NVIC::unmask(Interrupt::DMA1_CHANNEL7);
+    }
+
+    // a DMA request per sample
+    tim4.psc.modify(|_, w| w.psc().bits(0));
+    tim4.arr
+        .modify(|_, w| w.arr().bits((72_000_000 / SAMPLE_RATE - 1) as u16));
+    tim4.dier.modify(|_, w| w.ude().set_bit());
+    tim4.cr1.modify(|_, w| w.cen().set_bit());
+
+    unsafe {
+        *(TIMER_TIM4.as_mut_ptr()) = tim4;
+        *(CHANNEL.as_mut_ptr()) = channel;
+    }
+}
+
+#[interrupt]
+fn DMA1_CHANNEL7() {
+    unsafe {
+        let dma = &*pac::DMA1::ptr();
+        // half transfer: the first half was played, transfer complete: the second one
+        let half = if dma.isr.read().htif7().bit_is_set() {
+            &mut BUFFER[..HALF_LEN]
+        } else {
+            &mut BUFFER[HALF_LEN..]
+        };
+        dma.ifcr.write(|w| w.cgif7().set_bit());
+
+        // running out of samples holds the last one, that's silent
+        QUEUE.fill(half, &mut LAST);
+    }
+}
@@ -0,0 +1,83 @@
+use chf_link::{
+    packet::{self, Link},
+    Command,
+};
+
+use crate::video::Video;
+
+/// The SPI wire between the two MCUs, flips a bit now and then if asked to
+pub struct Wire {
+    random: u32,
+    error_one_in: u32,
+
+    pub bytes: usize,
+    pub errors: usize,
+    pub retries: usize,
+    pub failed: usize,
+}
+
+impl Wire {
+    /// `error_one_in` = 0 is a perfect wire
+    pub fn new(error_one_in: u32) -> Wire {
+        Wire {
+            random: 0x1234_5678,
+            error_one_in,
+
+            bytes: 0,
+            errors: 0,
+            retries: 0,
+            failed: 0,
+        }
+    }
+
+    /// Sends a command like the main MCU does, until it's acknowledged
+    pub fn send(&mut self, video: &mut Video, command: &Command) {
+        let mut link = SimLink { wire: self, video };
+        match packet::send(&mut link, command) {
+            Ok(tries) => self.retries += tries - 1,
+            Err(e) => {
+                println!("send err {:?} {:?}", e, command);
+                self.failed += 1;
+            }
+        }
+    }
+
+    /// Sends a block of rows like the main MCU does via DMA
+    pub fn send_block(&mut self, video: &mut Video, block: &[u8]) {
+        self.bytes += block.len();
+        let block: Vec<u8> = block.iter().map(|byte| self.transmit(*byte)).collect();
+        video.receive_block(&block);
+    }
+
+    fn transmit(&mut self, byte: u8) -> u8 {
+        // xorshift, good enough to inject errors
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 17;
+        self.random ^= self.random << 5;
+
+        if self.error_one_in != 0 && self.random < u32::MAX / self.error_one_in {
+            self.errors += 1;
+            byte ^ (1 << (self.random % 8))
+        } else {
+            byte
+        }
+    }
+}
+
+struct SimLink<'a> {
+    wire: &'a mut Wire,
+    video: &'a mut Video,
+}
+
+impl<'a> Link for SimLink<'a> {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        // the reply was prepared before the byte arrived
+        self.wire.bytes += 1;
+        let reply = self.wire.transmit(self.video.reply());
+        let byte = self.wire.transmit(byte);
+        self.video.push(byte);
+        reply
+    }
+
+    fn wait_us(&mut self, _us: u16) {}
+}
@@ -0,0 +1,229 @@
+use std::{cell::RefCell, env, fs, fs::File, io::BufWriter};
+
+use chf_emulator::{bios, Cpu, Input, Sound};
+use chf_link::{encoder::Encoder, frame, Command};
+use minifb::{Key, Scale, Window, WindowOptions};
+
+use link::Wire;
+use video::Video;
+
+mod link;
+mod video;
+
+const ROMS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../chf-emulator/roms");
+
+// a Channel F pixel on a TV is a bit taller than wide
+const PIXEL_WIDTH: usize = 4;
+const PIXEL_HEIGHT: usize = 6;
+const WIDTH: usize = video::WIDTH * PIXEL_WIDTH;
+const HEIGHT: usize = video::HEIGHT * PIXEL_HEIGHT;
+
+const KEYS: [(Key, chf_emulator::Key); 12] = [
+    (Key::Key1, chf_emulator::Key::Start),
+    (Key::Key2, chf_emulator::Key::Hold),
+    (Key::Key3, chf_emulator::Key::Mode),
+    (Key::Key4, chf_emulator::Key::Time),
+    (Key::A, chf_emulator::Key::Left0),
+    (Key::D, chf_emulator::Key::Right0),
+    (Key::W, chf_emulator::Key::Forward0),
+    (Key::S, chf_emulator::Key::Back0),
+    (Key::Q, chf_emulator::Key::CounterClockwise0),
+    (Key::E, chf_emulator::Key::Clockwise0),
+    (Key::Y, chf_emulator::Key::Pull0),
+    (Key::Z, chf_emulator::Key::Push0),
+];
+
+const USAGE: &str =
+    "sim [cartridge] [--frame-dma] [--errors <one in n bytes>] [--png <file> <frames>]";
+
+/// Runs the emulation loop of the main MCU against a simulated video MCU
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let mut cartridge = Vec::new();
+    let mut frame_dma = false;
+    let mut error_one_in = 0;
+    let mut png: Option<(String, u32)> = None;
+
+    let mut idx = 1;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            "--frame-dma" => frame_dma = true,
+            "--errors" if idx + 1 < args.len() => {
+                error_one_in = args[idx + 1].parse().expect(USAGE);
+                idx += 1;
+            }
+            "--png" if idx + 2 < args.len() => {
+                png = Some((args[idx + 1].clone(), args[idx + 2].parse().expect(USAGE)));
+                idx += 2;
+            }
+            file if !file.starts_with("--") => cartridge = fs::read(file).unwrap(),
+            _ => {
+                println!("{}", USAGE);
+                return;
+            }
+        }
+        idx += 1;
+    }
+
+    let channel_f = SimChannelF {
+        pixel: RefCell::new(None),
+        keys: RefCell::new(Input::default()),
+    };
+    let (rom_0000, rom_0400) = bios::load_or_free(ROMS_DIR);
+    let mut cpu = Cpu::new(&rom_0000, &rom_0400, &cartridge, &channel_f);
+    cpu.reset();
+
+    let mut video = Video::new();
+    let mut wire = Wire::new(error_one_in);
+    let mut encoder = Encoder::new();
+
+    // what the main MCU does once the cartridge is loaded
+    wire.send(&mut video, &Command::Clear { color: 0 });
+    if frame_dma {
+        wire.send(&mut video, &Command::FrameMode);
+        assert!(video.is_frame_mode());
+    }
+
+    let mut picture = vec![0u32; video::WIDTH * video::HEIGHT];
+    let mut buffer = vec![0u32; WIDTH * HEIGHT];
+    let mut next_report = 0;
+
+    let mut window = match png {
+        Some(_) => None,
+        None => {
+            let mut window = Window::new(
+                "Channel F - main + video MCU - ESC to exit",
+                WIDTH,
+                HEIGHT,
+                WindowOptions {
+                    scale: Scale::X1,
+                    ..WindowOptions::default()
+                },
+            )
+            .expect("Unable to Open Window");
+            window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+            Some(window)
+        }
+    };
+
+    loop {
+        if let Some(window) = &window {
+            if !window.is_open() || window.is_key_down(Key::Escape) {
+                break;
+            }
+
+            let mut keys = channel_f.keys.borrow_mut();
+            for (key, channel_f_key) in KEYS.iter() {
+                keys.set(*channel_f_key, window.is_key_down(*key));
+            }
+        }
+
+        // one frame of the main MCU's emulation loop
+        {
+            let mut send = |command: &Command| wire.send(&mut video, command);
+
+            loop {
+                let frame_done = cpu.step();
+
+                if let Some((x, y, color)) = channel_f.pixel.borrow_mut().take() {
+                    if !frame_dma {
+                        encoder.pixel(x, y, color, &mut send);
+                    }
+                }
+
+                if frame_done {
+                    break;
+                }
+            }
+
+            if !frame_dma {
+                encoder.flush(&mut send);
+            }
+        }
+
+        if frame_dma {
+            let rows = cpu.take_dirty_rows() | 1 << (cpu.frame % 64);
+            let mut block = [0u8; frame::MAX_BLOCK_LEN];
+            wire.send_block(
+                &mut video,
+                frame::encode_block(cpu.vram(), rows, &mut block),
+            );
+        }
+
+        video.picture(&mut picture);
+        scale(&picture, &mut buffer);
+
+        if cpu.frame >= next_report {
+            next_report += 60;
+            println!(
+                "frame {}: {} bytes sent, {} errors, {} commands, {} retries, {} failed, {} blocks, {} dropped",
+                cpu.frame,
+                wire.bytes,
+                wire.errors,
+                video.commands,
+                wire.retries,
+                wire.failed,
+                video.blocks,
+                video.dropped_blocks
+            );
+        }
+
+        match (&mut window, &png) {
+            (Some(window), _) => window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap(),
+            (None, Some((file, frames))) => {
+                if cpu.frame >= *frames {
+                    save_png(file, &buffer);
+                    break;
+                }
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+fn scale(picture: &[u32], buffer: &mut [u32]) {
+    for (idx, pixel) in buffer.iter_mut().enumerate() {
+        let x = idx % WIDTH / PIXEL_WIDTH;
+        let y = idx / WIDTH / PIXEL_HEIGHT;
+        *pixel = picture[y * video::WIDTH + x];
+    }
+}
+
+fn save_png(file: &str, buffer: &[u32]) {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(file).unwrap()),
+        WIDTH as u32,
+        HEIGHT as u32,
+    );
+    encoder.set_color(png::ColorType::RGB);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = buffer
+        .iter()
+        .flat_map(|pixel| vec![(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8])
+        .collect();
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(&data)
+        .unwrap();
+}
+
+/// Like the main MCU: remembers the last pixel written until the emulation loop picks it up
+struct SimChannelF {
+    pixel: RefCell<Option<(u8, u8, u8)>>,
+    keys: RefCell<Input>,
+}
+
+impl chf_emulator::ChannelF for SimChannelF {
+    fn sound(&self, _frequency: Sound) {}
+
+    fn set_pixel(&self, x: u8, y: u8, value: u8) {
+        *self.pixel.borrow_mut() = Some((x, y, value));
+    }
+
+    fn key_pressed(&self, key: chf_emulator::Key) -> bool {
+        self.keys.borrow().is_pressed(key)
+    }
+}
@@ -0,0 +1,74 @@
+use chf_link::{frame, packet::Receiver, screen::Screen, Command};
+
+/// Columns the video MCU puts out on every line, starting at column 0
+pub const WIDTH: usize = 104;
+pub const HEIGHT: usize = 64;
+
+/// The video MCU: receives like the firmware does and keeps the screen the same way
+pub struct Video {
+    receiver: Receiver,
+    frame_mode: bool,
+    vid_ram: [u8; 128 * 64],
+    indexed_pixels: [u8; 128 * 64],
+
+    pub commands: usize,
+    pub blocks: usize,
+    pub dropped_blocks: usize,
+}
+
+impl Video {
+    pub fn new() -> Video {
+        Video {
+            receiver: Receiver::new(),
+            frame_mode: false,
+            vid_ram: [0u8; 128 * 64],
+            indexed_pixels: [0u8; 128 * 64],
+
+            commands: 0,
+            blocks: 0,
+            dropped_blocks: 0,
+        }
+    }
+
+    pub fn reply(&self) -> u8 {
+        self.receiver.reply()
+    }
+
+    pub fn is_frame_mode(&self) -> bool {
+        self.frame_mode
+    }
+
+    /// A byte received via SPI in command mode
+    pub fn push(&mut self, byte: u8) {
+        if let Some(command) = self.receiver.push(byte) {
+            self.commands += 1;
+            if command == Command::FrameMode {
+                self.frame_mode = true;
+            }
+            self.screen().apply(command);
+        }
+    }
+
+    /// A block received via DMA in frame mode
+    pub fn receive_block(&mut self, block: &[u8]) {
+        let mut screen = Screen::new(&mut self.vid_ram, &mut self.indexed_pixels);
+        let result = frame::decode_block(block, |y, pixels| screen.apply_row(y, 0, pixels));
+
+        match result {
+            Ok(_) => self.blocks += 1,
+            Err(_) => self.dropped_blocks += 1,
+        }
+    }
+
+    /// What goes out via SCART, `WIDTH` x `HEIGHT` ARGB pixels
+    pub fn picture(&mut self, buffer: &mut [u32]) {
+        let screen = self.screen();
+        for (y, line) in buffer.chunks_mut(WIDTH).enumerate() {
+            line.copy_from_slice(&screen.rgb_row(y as u8)[..WIDTH]);
+        }
+    }
+
+    fn screen(&mut self) -> Screen<'_> {
+        Screen::new(&mut self.vid_ram, &mut self.indexed_pixels)
+    }
+}
@@ -6,8 +6,17 @@ use nb::block;
 use panic_halt as _;
 use rtt_target::rtt_init_print;
 
+use core::sync::atomic::{compiler_fence, Ordering};
+
 use cortex_m_rt::entry;
 
+use chf_link::{
+    frame::{self, BlockError, MAX_BLOCK_LEN},
+    packet::Receiver,
+    screen::{self, Screen},
+    Command,
+};
+
 use spi_slave::Spi1Slave;
 use stm32f1xx_hal::{
     pac::{self},
@@ -23,12 +32,10 @@ use video::VID_RAM;
 mod spi_slave;
 mod video;
 
-const COLORS: [u8; 16] = [
-    0b000000, 0b111111, 0b111111, 0b111111, //
-    0b101011, 0b000011, 0b110000, 0b001000, //
-    0b010101, 0b000011, 0b110000, 0b001000, //
-    0b011101, 0b000011, 0b110000, 0b001000, //
-];
+#[cfg(all(feature = "vga", any(feature = "ntsc", feature = "composite")))]
+compile_error!("feature `vga` can't be combined with `ntsc` or `composite`");
+
+static mut BLOCK: [u8; MAX_BLOCK_LEN] = [0u8; MAX_BLOCK_LEN];
 
 #[entry]
 fn main() -> ! {
@@ -66,6 +73,8 @@ fn main() -> ! {
     let _pb7 = gpiob.pb7.into_push_pull_output(&mut gpiob.crl);
     let _pb8 = gpiob.pb8.into_push_pull_output(&mut gpiob.crh);
     let _pb9 = gpiob.pb9.into_alternate_push_pull(&mut gpiob.crh); // timer controlled
+    #[cfg(feature = "vga")]
+    let _pb10 = gpiob.pb10.into_push_pull_output(&mut gpiob.crh); // vertical sync
 
     // for testing use the onboard led
     let mut gpioc = dp.GPIOC.split(&mut rcc.apb2);
@@ -102,18 +111,18 @@ fn main() -> ! {
     };
     let mut spi = Spi1Slave::spi1slave(dp.SPI1, pins, spi_mode, &mut rcc.apb2);
 
-    spi.send(0x7f).unwrap();
-    spi.clear_ovr();
-
-    let mut d;
+    let mut receiver = Receiver::new();
+    #[cfg(not(feature = "composite"))]
+    let mut screen = Screen::new(unsafe { &mut VID_RAM }, &mut indexed_pixels);
+    #[cfg(feature = "composite")]
+    let mut screen =
+        Screen::with_colors(unsafe { &mut VID_RAM }, &mut indexed_pixels, &screen::LUMA);
 
-    // XXXXXXXY YYYYYCCC
-
-    let mut z = 0u8;
-    let mut last_byte = 0u8;
-    let mut was_ovr = false;
+    spi.send(receiver.reply()).unwrap();
+    spi.clear_ovr();
 
     let mut cnt = 0;
+    let mut frame_mode = false;
 
     loop {
         cnt += 1;
@@ -122,13 +131,12 @@ fn main() -> ! {
             led.toggle().unwrap();
         }
 
-        let snd_byte = if !was_ovr { 0x7f } else { 0xff };
-
-        let res = block!(spi.send(snd_byte));
+        let res = block!(spi.send(receiver.reply()));
         if let Err(e) = res {
             match e {
                 stm32f1xx_hal::spi::Error::Overrun => {
                     spi.clear_ovr();
+                    receiver.overrun();
                     continue;
                 }
                 stm32f1xx_hal::spi::Error::ModeFault => {}
@@ -139,13 +147,21 @@ fn main() -> ! {
 
         let res = block!(spi.read());
 
-        d = match res {
-            Ok(v) => {
-                was_ovr = false;
-                v
+        match res {
+            Ok(d) => {
+                // the main MCU sends `FrameMode` again as long as it didn't get the ACK, full
+                // frame mode starts with the first block - `BLOCK_START` never starts a command
+                if frame_mode && d == frame::BLOCK_START && receiver.is_idle() {
+                    break;
+                }
+
+                // broken commands are dropped and NAKed, the main MCU sends them again
+                if let Some(command) = receiver.push(d) {
+                    frame_mode |= command == Command::FrameMode;
+                    screen.apply(command);
+                }
             }
             Err(e) => {
-                was_ovr = true;
                 match e {
                     stm32f1xx_hal::spi::Error::Overrun => {
                         spi.clear_ovr();
@@ -154,71 +170,40 @@ fn main() -> ! {
                     stm32f1xx_hal::spi::Error::Crc => {}
                     stm32f1xx_hal::spi::Error::_Extensible => {}
                 }
-                255
-            }
-        };
-
-        if z == 1 && !was_ovr {
-            let x = (last_byte & 0b11111110) >> 1;
-            let y = ((last_byte & 1) << 5) | ((d & 0b11111000) >> 3);
-            let mut c = d & 0b111;
-
-            if x == 125 || x == 126 {
-                handle_palette_change(y, x, c, &mut indexed_pixels);
-            } else {
-                let palette = unsafe {
-                    ((VID_RAM[y as usize * 128usize + 125usize] & 2 >> 1)
-                        | (VID_RAM[y as usize * 128usize + 126usize]))
-                        & 0b11
-                } as usize;
-                c = COLORS[c as usize + palette * 4usize];
-
-                let offset = y as usize * 128usize + x as usize;
-                indexed_pixels[offset] = d & 0b111;
-                unsafe {
-                    VID_RAM[offset] = c;
-                }    
+                receiver.overrun();
             }
-
-            z = 0;
-        } else if !was_ovr && z == 0 {
-            last_byte = d;
-            z = z + 1;
         }
     }
-}
 
-fn handle_palette_change(y: u8, x: u8, new_palette_value: u8, indexed_pixels: &mut [u8]) {
-    let current_palette = unsafe {
-        ((VID_RAM[y as usize * 128usize + 125usize]  & 2 >> 1)
-            | (VID_RAM[y as usize * 128usize + 126usize]))
-            & 0b11
-    } as usize;
-
-    let new_palette = if x == 125 {
-        unsafe {
-            ((new_palette_value  & 2 >> 1) | (VID_RAM[y as usize * 128usize + 126usize])) & 0b11
-        }
-    } else {
-        unsafe {
-            ((VID_RAM[y as usize * 128usize + 125usize]  & 2 >> 1) | (new_palette_value)) & 0b11
-        }
-    } as usize;
+    receive_frames(spi, &mut screen);
+}
 
-    if current_palette == new_palette {
-        return;
-    }
+// full frame mode: the main MCU sends a block of rows via DMA while no pixel lines are drawn
+fn receive_frames(mut spi: Spi1Slave<pac::SPI1>, screen: &mut Screen) -> ! {
+    loop {
+        // the main MCU doesn't send while pixel lines are drawn, a good time to start over
+        while !video::is_drawing() {}
+        spi.start_rx_dma(unsafe { &mut BLOCK });
+        while video::is_drawing() {}
+
+        let len = loop {
+            compiler_fence(Ordering::SeqCst);
+            let received = MAX_BLOCK_LEN - spi.rx_dma_remaining();
+            let block = unsafe { &BLOCK[..received] };
+
+            match frame::block_len(block) {
+                Ok(len) if received >= len => break Some(len),
+                Err(BlockError::BadHeader) => break None,
+                _ if video::is_drawing() => break None,
+                _ => {}
+            }
+        };
 
-    for xx in 0..102 {
-        unsafe {
-            VID_RAM[y as usize * 128usize + xx] =
-                COLORS[new_palette * 4 + indexed_pixels[xx + y as usize * 128usize] as usize];
+        // broken blocks are dropped, the main MCU sends one more row per frame to repair that
+        if let Some(len) = len {
+            let _ = frame::decode_block(unsafe { &BLOCK[..len] }, |y, pixels| {
+                screen.apply_row(y, 0, pixels)
+            });
         }
     }
-
-    let offset = y as usize * 128usize + x as usize;
-    indexed_pixels[offset] = new_palette_value;
-    unsafe {
-        VID_RAM[offset] = new_palette_value;
-    }
 }
@@ -6,6 +6,7 @@ use stm32f1xx_hal::{
         gpioa::{PA4, PA5, PA6, PA7},
         Alternate, Floating, Input, PullDown, PushPull,
     },
+    pac,
     rcc::{Enable, GetBusFreq, Reset},
     spi::{Error, SpiRegisterBlock},
 };
@@ -88,6 +89,52 @@ where
         Spi1Slave { spi, _pins: pins }
     }
 
+    /// Receives into `buffer` via DMA1 channel 2 (SPI1 RX) until it's full or this is called again
+    pub fn start_rx_dma(&mut self, buffer: &'static mut [u8]) {
+        let rcc = unsafe { &*pac::RCC::ptr() };
+        rcc.ahbenr.modify(|_, w| w.dma1en().set_bit());
+
+        let dma = unsafe { &*pac::DMA1::ptr() };
+        dma.ch2.cr.modify(|_, w| w.en().clear_bit());
+        self.clear_ovr();
+
+        dma.ch2
+            .par
+            .write(|w| unsafe { w.pa().bits(&self.spi.dr as *const _ as u32) });
+        dma.ch2
+            .mar
+            .write(|w| unsafe { w.ma().bits(buffer.as_ptr() as u32) });
+        dma.ch2.ndtr.write(|w| w.ndt().bits(buffer.len() as u16));
+        dma.ch2.cr.write(|w| {
+            w.mem2mem()
+                .clear_bit()
+                .pl()
+                .high()
+                .msize()
+                .bits8()
+                .psize()
+                .bits8()
+                .minc()
+                .set_bit()
+                .pinc()
+                .clear_bit()
+                .circ()
+                .clear_bit()
+                .dir()
+                .clear_bit()
+                .en()
+                .set_bit()
+        });
+
+        self.spi.cr2.modify(|_, w| w.rxdmaen().set_bit());
+    }
+
+    /// Bytes still to receive via DMA
+    pub fn rx_dma_remaining(&self) -> usize {
+        let dma = unsafe { &*pac::DMA1::ptr() };
+        dma.ch2.ndtr.read().ndt().bits() as usize
+    }
+
     pub fn clear_ovr(&self) {
         let _v = unsafe { ptr::read_volatile(&self.spi.dr as *const _ as *const u8) };
         self.spi.sr.read();
@@ -5,6 +5,9 @@ use stm32f1xx_hal::{
     pac,
 };
 
+use chf_link::timing::{
+    self, Line, Standard, Timing, DRAWN_COLUMNS, MAX_LINES, PIXEL_PINS, V_SYNC_PIN,
+};
 use core::mem::MaybeUninit;
 
 static mut TIMER_TIM4: MaybeUninit<pac::TIM4> = MaybeUninit::uninit();
@@ -58,7 +61,8 @@ pub fn init_video(
 }
 
 pub fn start_video() {
-    schedule(HALF_SCANLINE_ARR, SHORT_SYNC_CRR);
+    let last = DATA[LINE_COUNT - 1];
+    schedule(last.arr, last.ccr);
 }
 
 #[inline(always)]
@@ -158,8 +162,30 @@ fn configure_tim1(tim: &stm32f1xx_hal::pac::TIM1) {
 
 static mut IDX: usize = 0usize;
 
-const START_AT_SCANLINE: usize = 80;
-const STOP_AT_SCANLINE: usize = START_AT_SCANLINE + 64 * 3;
+#[cfg(not(any(feature = "ntsc", feature = "vga")))]
+const STANDARD: Standard = timing::PAL;
+#[cfg(all(feature = "ntsc", not(feature = "vga")))]
+const STANDARD: Standard = timing::NTSC;
+#[cfg(feature = "vga")]
+const STANDARD: Standard = timing::VGA;
+
+const TIMING: Timing = timing::timing(&STANDARD, ISR_OVERHEAD_CORRECTION);
+static DATA: [Line; MAX_LINES] = TIMING.lines;
+const LINE_COUNT: usize = TIMING.len;
+
+const START_AT_SCANLINE: usize = TIMING.start_at;
+const STOP_AT_SCANLINE: usize = TIMING.stop_at;
+
+// ldrb, eor, lsl, orr, str, add, subs and bne of the drawing loop, the nops make up the rest of
+// a pixel
+const LOOP_TICKS: u16 = 11;
+const PIXEL_NOPS: u16 = STANDARD.pixel_ticks - LOOP_TICKS;
+
+/// True while pixel lines are drawn, that's when the busy pin is high
+pub fn is_drawing() -> bool {
+    let idx = unsafe { core::ptr::read_volatile(&IDX) };
+    idx >= START_AT_SCANLINE && idx < STOP_AT_SCANLINE
+}
 
 #[interrupt]
 fn TIM4() {
@@ -173,8 +199,19 @@ fn TIM4() {
         let new_crr = DATA[IDX].ccr;
         schedule(new_arr, new_crr);
 
+        // VGA has its vertical sync on PB10
+        #[cfg(feature = "vga")]
+        {
+            let gpiob_set = 0x40010c10;
+            if DATA[IDX].v_sync {
+                *(gpiob_set as *mut u32) = V_SYNC_PIN << 16;
+            } else {
+                *(gpiob_set as *mut u32) = V_SYNC_PIN;
+            }
+        }
+
         if has_pixels && IDX >= START_AT_SCANLINE && IDX < STOP_AT_SCANLINE {
-            let mul = (IDX - START_AT_SCANLINE) / 3 * 128;
+            let mul = (IDX - START_AT_SCANLINE) / TIMING.lines_per_row * 128;
             draw_pxls(
                 (VID_RAM.as_mut_ptr() as *const _ as u32)
                     .overflowing_add(mul as u32)
@@ -183,7 +220,7 @@ fn TIM4() {
         }
 
         IDX += 1;
-        if IDX >= DATA.len() {
+        if IDX >= LINE_COUNT {
             IDX = 0;
         }
 
@@ -208,36 +245,32 @@ fn draw_pxls(line_pixel_ptr: u32) {
             "mov {gpio_write_data},#0",
             "loop:",
 
+            // only the pixel pins are set and reset, see `timing::pixel_bsrr` - the vertical sync
+            // is on the same port
             "ldrb {gpio_write_data},[{xpxl_data_ptr}]",
-            "lsl {gpio_write_data},#3",
+            "eor {pxl_reset}, {gpio_write_data}, #0x3f",
+            "lsl {pxl_reset}, #19",
+            "orr {gpio_write_data}, {pxl_reset}, {gpio_write_data}, lsl #3",
             "str {gpio_write_data}, [{gpio_reg}]",
+            ".rept {nops}",
             "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
-            "nop",
+            ".endr",
             "add {xpxl_data_ptr}, #1",
 
             "subs {pxl_count}, #1",
             "bne loop",
 
-            "mov {gpio_write_data},#0x0",
-            "str {gpio_write_data}, [{gpio_reg}]",
+            "str {pxls_off}, [{gpio_reg}]",
 
-            pxl_count = in(reg) 104, // should be approx 128, probably just 104
+            pxl_count = in(reg) DRAWN_COLUMNS, // should be approx 128, probably just 104
             xpxl_data_ptr = in(reg) line_pixel_ptr,
             gpio_write_data = out(reg) _,
-            gpio_reg = in(reg) 0x40010c0c,
+            pxl_reset = out(reg) _,
+            pxls_off = in(reg) PIXEL_PINS << 16,
+            gpio_reg = in(reg) 0x40010c10, // GPIOB BSRR
             cntr = in(reg) 0,
-            cntr_dst = in(reg) 1270, // START AT THIS TIM4 CNT VALUE
+            cntr_dst = in(reg) TIMING.pixel_start as u32, // START AT THIS TIM4 CNT VALUE
+            nops = const PIXEL_NOPS,
             tim4_cnt = in(reg) 0x4000_0824 // TIM4 CNT
         );
     }
@@ -251,1649 +284,3 @@ fn TIM1_UP() {
         asm!("wfi");
     }
 }
-
-struct Data {
-    arr: u16,
-    ccr: u16,
-    has_pixels: bool,
-}
-
-const FULL_SCANLINE_ARR: u16 = 2307 * 2 - ISR_OVERHEAD_CORRECTION; // 64
-const HALF_SCANLINE_ARR: u16 = 2305 - ISR_OVERHEAD_CORRECTION;
-
-const BROAD_SYNC_CRR: u16 = 1966 - ISR_OVERHEAD_CORRECTION; // 4.7
-const SHORT_SYNC_CRR: u16 = 182 - ISR_OVERHEAD_CORRECTION; // 2.35
-const H_SYNC_CRR: u16 = 344 - ISR_OVERHEAD_CORRECTION; // 4.7
-                                                       // front porch (after pixel data) 1.64
-                                                       // back purch (before pixel data, after h-sync) 5.7
-
-const _DATA: [Data; 4] = [
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    // scanline 6 - 23
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-];
-
-const DATA: [Data; 312 + 5 + 3] = [
-    // scanline 1 - 5
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: BROAD_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: BROAD_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: BROAD_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: BROAD_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: BROAD_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    // scanline 6 - 23
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: false,
-    },
-    // scanline 24 - 309
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    Data {
-        arr: FULL_SCANLINE_ARR,
-        ccr: H_SYNC_CRR,
-        has_pixels: true,
-    },
-    // scanline 310 - 312
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-    Data {
-        arr: HALF_SCANLINE_ARR,
-        ccr: SHORT_SYNC_CRR,
-        has_pixels: false,
-    },
-];